@@ -0,0 +1,110 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Snapshot of large config compared with owned value tree. Run with
+//! `cargo bench --bench snapshot`.
+
+use librustconfig::config::Config;
+
+use std::time::{Duration, Instant};
+
+/// Number of groups in generated config.
+const GROUPS : usize = 1000;
+
+/// Number of settings in every group.
+const SETTINGS : usize = 100;
+
+/// Number of timed rounds.
+const ROUNDS : u32 = 10;
+
+// Generate config with GROUPS * SETTINGS string settings.
+fn generate() -> String {
+    let mut text = String::new();
+    for group in 0..GROUPS {
+        text.push_str(&format!("group{} : {{\n", group));
+        for setting in 0..SETTINGS {
+            text.push_str(&format!("  value{} = \"{}\";\n", setting,
+                setting));
+        }
+        text.push_str("};\n");
+    }
+    text
+}
+
+// Print average time of build and drop per setting.
+fn report(name : &str, build : Duration, drop : Duration) {
+    let settings = ((GROUPS * SETTINGS) as u32 * ROUNDS) as f64;
+    println!("{:<10} build {:>7.1} ns/setting, drop {:>7.1} ns/setting",
+        name, build.as_nanos() as f64 / settings,
+        drop.as_nanos() as f64 / settings);
+}
+
+fn main() {
+    let mut cfg = Config::new();
+    cfg.load_from_string(generate()).expect("generated config is valid");
+
+    let (mut build, mut drop) = (Duration::default(), Duration::default());
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        let snapshot = cfg.to_snapshot();
+        build += start.elapsed();
+        assert_eq!(snapshot.len(), GROUPS * (SETTINGS + 1));
+
+        let start = Instant::now();
+        std::mem::drop(snapshot);
+        drop += start.elapsed();
+    }
+    report("snapshot", build, drop);
+
+    let (mut build, mut drop) = (Duration::default(), Duration::default());
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        let value = cfg.read_many(&[""]);
+        build += start.elapsed();
+        assert!(value[0].is_some());
+
+        let start = Instant::now();
+        std::mem::drop(value);
+        drop += start.elapsed();
+    }
+    report("value", build, drop);
+
+    let snapshot = cfg.to_snapshot();
+    let paths : Vec<String> = (0..GROUPS).map(|group| {
+        format!("group{}.value{}", group, group % SETTINGS)
+    }).collect();
+    let start = Instant::now();
+    let mut found = 0;
+    for _ in 0..ROUNDS {
+        found += paths.iter().filter(|p| snapshot.get(p).is_some()).count();
+    }
+    println!("{:<10} {:>7.1} ns/lookup", "get",
+        start.elapsed().as_nanos() as f64 / found as f64);
+}
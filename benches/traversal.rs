@@ -0,0 +1,99 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Traversal of large config. Run with `cargo bench --bench traversal`.
+
+use librustconfig::config::{Config, OptionReader};
+
+use std::time::{Duration, Instant};
+
+/// Number of groups in generated config.
+const GROUPS : usize = 1000;
+
+/// Number of settings in every group.
+const SETTINGS : usize = 100;
+
+/// Number of timed traversals.
+const ROUNDS : u32 = 10;
+
+// Generate config with GROUPS * SETTINGS integer settings.
+fn generate() -> String {
+    let mut text = String::new();
+    for group in 0..GROUPS {
+        text.push_str(&format!("group{} : {{\n", group));
+        for setting in 0..SETTINGS {
+            text.push_str(&format!("  value{} = {};\n", setting, setting));
+        }
+        text.push_str("};\n");
+    }
+    text
+}
+
+// Run traversal ROUNDS times and print average time per setting.
+fn bench<F>(name : &str, root : &OptionReader, traverse : F)
+    where F: Fn(&OptionReader) -> i64 {
+    let mut total = Duration::default();
+    let mut checksum = 0;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        checksum += traverse(root);
+        total += start.elapsed();
+    }
+
+    let settings = (GROUPS * SETTINGS) as u32 * ROUNDS;
+    println!("{:<10} {:>8.1} ns/setting (checksum {})", name,
+        total.as_nanos() as f64 / settings as f64, checksum);
+}
+
+fn main() {
+    let mut cfg = Config::new();
+    cfg.load_from_string(generate()).expect("generated config is valid");
+    let root = cfg.value("").expect("root group exists");
+
+    bench("children", &root, |root| {
+        root.children().map(|group| {
+            group.children().filter_map(|option| option.as_int64())
+                .sum::<i64>()
+        }).sum()
+    });
+
+    bench("entries", &root, |root| {
+        root.entries().map(|(_, group)| {
+            group.children().filter_map(|option| option.as_int64())
+                .sum::<i64>()
+        }).sum()
+    });
+
+    bench("names", &root, |root| {
+        root.entries().map(|(_, group)| {
+            group.entries().map(|(name, _)| name.len() as i64).sum::<i64>()
+        }).sum()
+    });
+}
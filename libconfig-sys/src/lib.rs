@@ -124,6 +124,10 @@ extern "C" {
     pub fn config_set_auto_convert (config : *mut config_t, flag : c_int);
     pub fn config_get_auto_convert (config : *const config_t) -> c_int;
 
+    pub fn config_set_float_precision (config : *mut config_t,
+        digits : c_ushort);
+    pub fn config_get_float_precision (config : *const config_t) -> c_ushort;
+
     pub fn config_read_string (config : *mut config_t, str : *const c_schar)
         -> c_int;
 
@@ -0,0 +1,139 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Appending elements to collections.
+//!
+//! `append_*` methods add element at the end of array or list. Array
+//! elements must be scalars of the same type, so value of another type is
+//! refused. Groups can only be appended to lists.
+
+use crate::config::OptionWriter;
+use crate::freeze;
+use libconfig_sys as raw;
+
+use std::os::raw::c_short;
+use std::ptr;
+
+impl<'a> OptionWriter<'a> {
+
+    /// Append i32 element to array or list.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// cfg.root().create_array("ports");
+    /// let ports = cfg.writer("ports").unwrap();
+    /// assert!(ports.append_int32(80).and_then(|ports| ports.append_int32(443))
+    ///     .is_some());
+    /// assert!(ports.append_string("http").is_none());
+    /// assert_eq!(cfg.value("ports.[1]").unwrap().as_int32(), Some(443));
+    /// ```
+    pub fn append_int32(&self, value : i32)
+        -> Option<&OptionWriter<'a>> {
+        self.collection(raw::CONFIG_TYPE_INT)?;
+        self.write_int32("", value).ok()
+    }
+
+    /// Append f64 element to array or list.
+    pub fn append_float64(&self, value : f64)
+        -> Option<&OptionWriter<'a>> {
+        self.collection(raw::CONFIG_TYPE_FLOAT)?;
+        self.write_float64("", value).ok()
+    }
+
+    /// Append bool element to array or list.
+    pub fn append_bool(&self, value : bool)
+        -> Option<&OptionWriter<'a>> {
+        self.collection(raw::CONFIG_TYPE_BOOL)?;
+        self.write_bool("", value).ok()
+    }
+
+    /// Append string element to array or list.
+    pub fn append_string<S>(&self, value : S) -> Option<&OptionWriter<'a>>
+        where S: AsRef<str> {
+        self.collection(raw::CONFIG_TYPE_STRING)?;
+        self.write_string("", value).ok()
+    }
+
+    /// Append empty group to list, return writer of the new group.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// cfg.root().create_list("servers");
+    /// let mut servers = cfg.writer("servers").unwrap();
+    /// let server = servers.append_group().unwrap();
+    /// server.write_int32("port", 80);
+    /// assert_eq!(cfg.value("servers.[0].port").unwrap().as_int32(), Some(80));
+    /// ```
+    pub fn append_group(&mut self) -> Option<OptionWriter<'_>> {
+        let list = self.collection(raw::CONFIG_TYPE_GROUP)?;
+        if !freeze::writable(list) {
+            return None;
+        }
+
+        let option = unsafe {
+            raw::config_setting_add(list, ptr::null(),
+                raw::CONFIG_TYPE_GROUP as i32)
+        };
+        if option.is_null() {
+            None
+        } else {
+            Some(OptionWriter {
+                element : Some(option),
+                marker : self.marker
+            })
+        }
+    }
+
+    // Return collection element if element of given type can be appended.
+    fn collection(&self, kind : c_short) -> Option<*mut raw::config_setting_t> {
+        let element = self.element?;
+        if raw::config_setting_is_list(element) == raw::CONFIG_TRUE {
+            return Some(element);
+        }
+        if raw::config_setting_is_array(element) != raw::CONFIG_TRUE
+            || kind == raw::CONFIG_TYPE_GROUP {
+            return None;
+        }
+
+        let first = unsafe { raw::config_setting_get_elem(element, 0) };
+        if first.is_null()
+            || raw::config_setting_type(first) == i32::from(kind) {
+            Some(element)
+        } else {
+            None
+        }
+    }
+}
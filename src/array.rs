@@ -0,0 +1,132 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Writing arrays from slices.
+//!
+//! `write_*_array` methods add array member to group and fill it with all
+//! values in one call. Array is removed again if any value can't be written,
+//! so partially written arrays are never left behind.
+
+use crate::config::{Errors, OptionWriter, Result};
+use libconfig_sys as raw;
+
+use std::ffi::CString;
+
+impl<'a> OptionWriter<'a> {
+
+    /// Add new i32 array to current group.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// assert!(cfg.root().write_int32_array("ports", &[80, 443]).is_ok());
+    /// assert_eq!(cfg.value("ports.[1]").unwrap().as_int32(), Some(443));
+    /// ```
+    pub fn write_int32_array<S>(&self, name : S, values : &[i32])
+        -> Result<&OptionWriter<'a>> where S: AsRef<str> {
+        self.write_array(name.as_ref(), values, |array, value| unsafe {
+            raw::config_setting_set_int_elem(array, -1, *value)
+        })
+    }
+
+    /// Add new i64 array to current group.
+    pub fn write_int64_array<S>(&self, name : S, values : &[i64])
+        -> Result<&OptionWriter<'a>> where S: AsRef<str> {
+        self.write_array(name.as_ref(), values, |array, value| unsafe {
+            raw::config_setting_set_int64_elem(array, -1, *value)
+        })
+    }
+
+    /// Add new f64 array to current group.
+    pub fn write_float64_array<S>(&self, name : S, values : &[f64])
+        -> Result<&OptionWriter<'a>> where S: AsRef<str> {
+        self.write_array(name.as_ref(), values, |array, value| unsafe {
+            raw::config_setting_set_float_elem(array, -1, *value)
+        })
+    }
+
+    /// Add new bool array to current group.
+    pub fn write_bool_array<S>(&self, name : S, values : &[bool])
+        -> Result<&OptionWriter<'a>> where S: AsRef<str> {
+        self.write_array(name.as_ref(), values, |array, value| unsafe {
+            raw::config_setting_set_bool_elem(array, -1, *value as i32)
+        })
+    }
+
+    /// Add new string array to current group. Returns InvalidString if any
+    /// value contains NUL character.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::{Config, Errors};
+    ///
+    /// let mut cfg = Config::new();
+    /// let hosts = vec!["a".to_string(), "b".to_string()];
+    /// assert!(cfg.root().write_string_array("hosts", &hosts).is_ok());
+    /// assert_eq!(cfg.root().write_string_array("bad", &["a\0b"]).err(),
+    ///     Some(Errors::InvalidString));
+    /// assert!(cfg.value("bad").is_none());
+    /// ```
+    pub fn write_string_array<S, V>(&self, name : S, values : &[V])
+        -> Result<&OptionWriter<'a>> where S: AsRef<str>, V: AsRef<str> {
+        let values = values.iter()
+            .map(|value| CString::new(value.as_ref()))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| Errors::InvalidString)?;
+        self.write_array(name.as_ref(), &values, |array, value| unsafe {
+            raw::config_setting_set_string_elem(array, -1, value.as_ptr())
+        })
+    }
+
+    // Add array and append values with write, remove array if any value
+    // fails.
+    fn write_array<T, F>(&self, name : &str, values : &[T], write : F)
+        -> Result<&OptionWriter<'a>>
+        where F: Fn(*mut raw::config_setting_t, &T)
+            -> *mut raw::config_setting_t {
+        let element = self.element.ok_or(Errors::ElementNotExists)?;
+        if raw::config_setting_is_group(element) != raw::CONFIG_TRUE {
+            return Err(Errors::TypeMismatch);
+        }
+
+        let array = self.add(name, raw::CONFIG_TYPE_ARRAY)?;
+        if values.iter().all(|value| !write(array, value).is_null()) {
+            return Ok(self);
+        }
+
+        unsafe {
+            let index = raw::config_setting_index(array);
+            raw::config_setting_remove_elem(element, index as u32);
+        }
+        Err(Errors::WriteError)
+    }
+}
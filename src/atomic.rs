@@ -0,0 +1,159 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Crash safe saving.
+//!
+//! Config is written to a temporary file next to the target, flushed to disk
+//! and renamed over the target. Rename within one directory is atomic, so
+//! the target holds either the previous or the new config, never a partially
+//! written one.
+
+use crate::config::{Config, Errors, Result};
+
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Number of temporary files created by this process.
+static TEMP_COUNTER : AtomicUsize = AtomicUsize::new(0);
+
+// Return path of file next to target with suffix appended to its name.
+fn sibling(file_name : &Path, suffix : &str) -> Result<PathBuf> {
+    let mut name = OsString::from(file_name.file_name()
+        .ok_or(Errors::SaveError)?);
+    name.push(suffix);
+    Ok(file_name.with_file_name(name))
+}
+
+// Flush directory entry changes of file's directory to disk. Not every
+// platform can open directories, the rename is done either way.
+fn sync_directory(file_name : &Path) {
+    if let Some(directory) = file_name.parent() {
+        let directory = if directory.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            directory
+        };
+        if let Ok(directory) = File::open(directory) {
+            let _ = directory.sync_all();
+        }
+    }
+}
+
+impl Config {
+
+    /// Save config to file without risk of leaving partially written file
+    /// after crash. Config is written to a temporary file in the same
+    /// directory, synced to disk and renamed over the target. Permissions
+    /// of the existing file are kept.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    /// use std::path::Path;
+    /// use std::fs;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("port = 80;").is_ok() {
+    ///     assert!(cfg.save_to_file_atomic(Path::new("atomic.cfg")).is_ok());
+    ///     fs::remove_file(Path::new("atomic.cfg")).unwrap();
+    /// }
+    /// ```
+    pub fn save_to_file_atomic(&self, file_name : &Path) -> Result<()> {
+        self.save_atomic(file_name, false)
+    }
+
+    /// Save config to file like `save_to_file_atomic`, keeping previous
+    /// version of the file with `.bak` appended to its name.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    /// use std::path::Path;
+    /// use std::fs;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("port = 80;").is_ok() &&
+    ///     cfg.save_to_file_atomic(Path::new("app.cfg")).is_ok() {
+    ///     assert!(cfg.set("port", 8080).is_ok());
+    ///     assert!(cfg.save_to_file_atomic_with_backup(Path::new("app.cfg"))
+    ///         .is_ok());
+    ///     assert_eq!(fs::read_to_string("app.cfg.bak").unwrap(),
+    ///         "port = 80;\n");
+    ///     fs::remove_file(Path::new("app.cfg")).unwrap();
+    ///     fs::remove_file(Path::new("app.cfg.bak")).unwrap();
+    /// }
+    /// ```
+    pub fn save_to_file_atomic_with_backup(&self, file_name : &Path)
+        -> Result<()> {
+        self.save_atomic(file_name, true)
+    }
+
+    // Write config to temporary file and rename it over the target.
+    // Temporary name is unique per save, so concurrent saves from other
+    // threads and processes never share the file.
+    fn save_atomic(&self, file_name : &Path, backup : bool) -> Result<()> {
+        self.check_savable()?;
+        let temp_name = sibling(file_name, &format!(".{}.{}.tmp",
+            process::id(), TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)))?;
+        let file = OpenOptions::new().write(true).create_new(true)
+            .open(&temp_name).map_err(|_| Errors::SaveError)?;
+
+        let result = self.write_replacement(file_name, &temp_name, file,
+            backup);
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_name);
+        }
+        result
+    }
+
+    // Write synced temporary file and move it in place of the target.
+    fn write_replacement(&self, file_name : &Path, temp_name : &Path,
+        file : File, backup : bool) -> Result<()> {
+        self.save_to_writer(BufWriter::new(&file))?;
+        file.sync_all().map_err(|_| Errors::SaveError)?;
+
+        if let Ok(metadata) = fs::metadata(file_name) {
+            fs::set_permissions(temp_name, metadata.permissions())
+                .map_err(|_| Errors::SaveError)?;
+            if backup {
+                fs::copy(file_name, sibling(file_name, ".bak")?)
+                    .map_err(|_| Errors::SaveError)?;
+            }
+        }
+
+        fs::rename(temp_name, file_name).map_err(|_| Errors::SaveError)?;
+        sync_directory(file_name);
+        Ok(())
+    }
+}
@@ -0,0 +1,692 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Command line tool for scripting edits of configuration files.
+//!
+//! ```text
+//! rustconfig get app.cfg server.port
+//! rustconfig set app.cfg server.port 9090 --type int
+//! rustconfig convert app.cfg --to json
+//! rustconfig validate app.cfg --schema schema.cfg
+//! rustconfig fmt --check app.cfg
+//! rustconfig diff old.cfg new.cfg
+//! ```
+
+use librustconfig::config::{Config, OptionType};
+use librustconfig::diff::ChangeKind;
+use librustconfig::schema::{Schema, ValidationError, ValidationErrorKind};
+use librustconfig::value::Value;
+
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+const USAGE : &str = "\
+usage: rustconfig <command> [arguments]
+
+commands:
+    get <file> <path>                   print value of setting
+    set <file> <path> <value> [--type <int|int64|float|bool|string>]
+                                        change setting and save file
+    convert <file> --to <cfg|json|toml|yaml> [--from <format>]
+        [--output <file>]               convert file to another format
+    validate <file> --schema <schema> [--strict]
+                                        check file against schema, --strict
+                                        also reports undeclared settings
+    fmt <file>... [--check]             format files, --check only lists
+                                        files that are not formatted, files
+                                        that can't be formatted are skipped
+    diff <old> <new> [--json]           print added, removed and changed
+                                        settings";
+
+// Command failure, usage errors are reported together with help text.
+enum Failure {
+    Usage(String),
+    Error(String)
+}
+
+type Outcome = std::result::Result<(), Failure>;
+
+fn usage<T>(message : &str) -> std::result::Result<T, Failure> {
+    Err(Failure::Usage(message.to_string()))
+}
+
+fn error<T>(message : String) -> std::result::Result<T, Failure> {
+    Err(Failure::Error(message))
+}
+
+// Command arguments split to positional arguments, named options and
+// flags.
+struct Arguments<'a> {
+    positional : Vec<&'a str>,
+    options : HashMap<&'a str, &'a str>,
+    flags : HashSet<&'a str>
+}
+
+impl<'a> Arguments<'a> {
+    // Split arguments, every option from list expects value, flags don't.
+    fn parse(args : &'a [String], options : &[&str], flags : &[&str])
+        -> std::result::Result<Arguments<'a>, Failure> {
+        let mut result = Arguments {
+            positional : Vec::new(),
+            options : HashMap::new(),
+            flags : HashSet::new()
+        };
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if !arg.starts_with("--") {
+                result.positional.push(arg.as_str());
+                continue;
+            }
+
+            let name = &arg[2..];
+            if flags.contains(&name) {
+                result.flags.insert(name);
+                continue;
+            }
+            if !options.contains(&name) {
+                return usage(&format!("unknown option '{}'", arg));
+            }
+            match iter.next() {
+                Some(value) => { result.options.insert(name, value); },
+                None => { return usage(&format!("{} expects value", arg)); }
+            }
+        }
+        Ok(result)
+    }
+
+    fn option(&self, name : &str) -> Option<&'a str> {
+        self.options.get(name).copied()
+    }
+
+    fn flag(&self, name : &str) -> bool {
+        self.flags.contains(name)
+    }
+}
+
+fn load(file : &str) -> std::result::Result<Config, Failure> {
+    let mut cfg = Config::new();
+    let result = cfg.load_from_file(Path::new(file));
+    match (result, cfg.parse_error()) {
+        (Ok(()), _) => { Ok(cfg) },
+        (Err(_), Some(diagnostic)) => { error(diagnostic.to_string()) },
+        (Err(err), None) => {
+            error(format!("{}: can't load file: {}", file, err))
+        }
+    }
+}
+
+// Parse value of given type, guess type when not set.
+fn parse_value(text : &str, kind : Option<&str>)
+    -> std::result::Result<Value, Failure> {
+    let invalid = |kind : &str| {
+        Failure::Error(format!("'{}' is not a valid {} value", text, kind))
+    };
+
+    match kind {
+        Some("int") => {
+            text.parse::<i32>().map(Value::from).map_err(|_| invalid("int"))
+        },
+        Some("int64") => {
+            text.parse::<i64>().map(Value::from)
+                .map_err(|_| invalid("int64"))
+        },
+        Some("float") => {
+            text.parse::<f64>().map(Value::from)
+                .map_err(|_| invalid("float"))
+        },
+        Some("bool") => {
+            text.parse::<bool>().map(Value::from)
+                .map_err(|_| invalid("bool"))
+        },
+        Some("string") => { Ok(Value::from(text)) },
+        Some(kind) => { usage(&format!("unknown value type '{}'", kind)) },
+        None => {
+            if let Ok(val) = text.parse::<i64>() {
+                Ok(Value::Int(val))
+            } else if let Ok(val) = text.parse::<f64>() {
+                Ok(Value::Float(val))
+            } else if let Ok(val) = text.parse::<bool>() {
+                Ok(Value::Bool(val))
+            } else {
+                Ok(Value::from(text))
+            }
+        }
+    }
+}
+
+fn get(args : &[String]) -> Outcome {
+    let args = Arguments::parse(args, &[], &[])?;
+    let (file, path) = match args.positional.as_slice() {
+        [file, path] => { (*file, *path) },
+        _ => { return usage("get expects <file> <path>"); }
+    };
+
+    let cfg = load(file)?;
+    match cfg.read_many(&[path]).pop().flatten() {
+        Some(Value::Str(text)) => { println!("{}", text); },
+        Some(value) => { println!("{}", value); },
+        None => { return error(format!("{}: setting not found", path)); }
+    }
+    Ok(())
+}
+
+fn set(args : &[String]) -> Outcome {
+    let args = Arguments::parse(args, &["type"], &[])?;
+    let (file, path, text) = match args.positional.as_slice() {
+        [file, path, text] => { (*file, *path, *text) },
+        _ => { return usage("set expects <file> <path> <value>"); }
+    };
+
+    let value = parse_value(text, args.option("type"))?;
+    let mut cfg = load(file)?;
+    cfg.set(path, value).map_err(|err| {
+        Failure::Error(format!("{}: can't set value: {}", path, err))
+    })?;
+    cfg.save_to_file(Path::new(file)).map_err(|err| {
+        Failure::Error(format!("{}: can't save file: {}", file, err))
+    })
+}
+
+// Supported document formats.
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Config,
+    Json,
+    Toml,
+    Yaml
+}
+
+impl Format {
+    fn from_name(name : &str) -> Option<Format> {
+        match name {
+            "cfg" | "conf" | "libconfig" => { Some(Format::Config) },
+            "json" => { Some(Format::Json) },
+            "toml" => { Some(Format::Toml) },
+            "yaml" | "yml" => { Some(Format::Yaml) },
+            _ => { None }
+        }
+    }
+
+    // Guess format by file extension, libconfig syntax by default.
+    fn from_file(file : &str) -> Format {
+        Path::new(file).extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(Format::from_name)
+            .unwrap_or(Format::Config)
+    }
+}
+
+// Convert owned value to serde document tree.
+fn to_document(value : &Value) -> serde_json::Value {
+    match value {
+        Value::Int(val) => { serde_json::Value::from(*val) },
+        Value::Float(val) => { serde_json::Value::from(*val) },
+        Value::Bool(val) => { serde_json::Value::from(*val) },
+        Value::Str(val) => { serde_json::Value::from(val.as_str()) },
+        Value::Array(elements) | Value::List(elements) => {
+            serde_json::Value::Array(elements.iter().map(to_document)
+                .collect())
+        },
+        Value::Group(children) => {
+            serde_json::Value::Object(children.iter()
+                .map(|(name, child)| (name.clone(), to_document(child)))
+                .collect())
+        }
+    }
+}
+
+// Convert serde document tree to owned value. Sequences of scalars of one
+// type become arrays, other sequences become lists.
+fn from_document(document : &serde_json::Value, path : &str)
+    -> std::result::Result<Value, Failure> {
+    match document {
+        serde_json::Value::Null => {
+            error(format!("{}: null values are not supported", path))
+        },
+        serde_json::Value::Bool(val) => { Ok(Value::Bool(*val)) },
+        serde_json::Value::Number(val) => {
+            match (val.as_i64(), val.as_f64()) {
+                (Some(val), _) => { Ok(Value::Int(val)) },
+                (None, Some(val)) => { Ok(Value::Float(val)) },
+                _ => { error(format!("{}: number out of range", path)) }
+            }
+        },
+        serde_json::Value::String(val) => { Ok(Value::Str(val.clone())) },
+        serde_json::Value::Array(elements) => {
+            let elements = elements.iter().enumerate()
+                .map(|(index, element)| {
+                    from_document(element, &format!("{}.[{}]", path, index))
+                })
+                .collect::<std::result::Result<Vec<Value>, Failure>>()?;
+            let scalar = |value : &Value| {
+                !matches!(value, Value::Array(_) | Value::List(_) |
+                    Value::Group(_))
+            };
+            let same_type = elements.windows(2).all(|pair| {
+                std::mem::discriminant(&pair[0]) ==
+                    std::mem::discriminant(&pair[1])
+            });
+            if same_type && elements.iter().all(scalar) {
+                Ok(Value::Array(elements))
+            } else {
+                Ok(Value::List(elements))
+            }
+        },
+        serde_json::Value::Object(children) => {
+            let mut group = IndexMap::new();
+            for (name, child) in children.iter() {
+                let child_path = if path.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{}.{}", path, name)
+                };
+                group.insert(name.clone(), from_document(child,
+                    &child_path)?);
+            }
+            Ok(Value::Group(group))
+        }
+    }
+}
+
+// Read whole file as owned value.
+fn read_document(file : &str, format : Format)
+    -> std::result::Result<Value, Failure> {
+    if format == Format::Config {
+        return load(file)?.read_many(&[""]).pop().flatten()
+            .ok_or_else(|| Failure::Error(format!("{}: empty config", file)));
+    }
+
+    let text = fs::read_to_string(file).map_err(|err| {
+        Failure::Error(format!("{}: can't read file: {}", file, err))
+    })?;
+    let document : serde_json::Value = match format {
+        Format::Json => {
+            serde_json::from_str(&text).map_err(|err| err.to_string())
+        },
+        Format::Toml => {
+            toml::from_str(&text).map_err(|err| err.to_string())
+        },
+        _ => {
+            serde_yaml::from_str(&text).map_err(|err| err.to_string())
+        }
+    }.map_err(|err| {
+        Failure::Error(format!("{}: can't parse file: {}", file, err))
+    })?;
+    from_document(&document, "")
+}
+
+// Write owned value as document text.
+fn write_document(value : &Value, format : Format)
+    -> std::result::Result<String, Failure> {
+    let children = match value {
+        Value::Group(children) => { children },
+        _ => { return error("document root must be a group".to_string()); }
+    };
+
+    if format == Format::Config {
+        let mut cfg = Config::new();
+        for (name, child) in children.iter() {
+            cfg.set(name, child.clone()).map_err(|err| {
+                Failure::Error(format!("{}: can't write setting: {}", name,
+                    err))
+            })?;
+        }
+        return cfg.save_to_string().map_err(|err| {
+            Failure::Error(format!("can't write document: {}", err))
+        });
+    }
+
+    let document = to_document(value);
+    match format {
+        Format::Json => {
+            serde_json::to_string_pretty(&document).map(|text| text + "\n")
+                .map_err(|err| err.to_string())
+        },
+        Format::Toml => {
+            toml::to_string(&document).map_err(|err| err.to_string())
+        },
+        _ => {
+            serde_yaml::to_string(&document).map_err(|err| err.to_string())
+        }
+    }.map_err(|err| {
+        Failure::Error(format!("can't write document: {}", err))
+    })
+}
+
+fn convert(args : &[String]) -> Outcome {
+    let args = Arguments::parse(args, &["to", "from", "output"], &[])?;
+    let file = match args.positional.as_slice() {
+        [file] => { *file },
+        _ => { return usage("convert expects <file>"); }
+    };
+
+    let format = |name : &str| {
+        Format::from_name(name).ok_or_else(|| {
+            Failure::Usage(format!("unknown format '{}'", name))
+        })
+    };
+    let to = match args.option("to") {
+        Some(name) => { format(name)? },
+        None => { return usage("convert expects --to <format>"); }
+    };
+    let from = match args.option("from") {
+        Some(name) => { format(name)? },
+        None => { Format::from_file(file) }
+    };
+
+    let text = write_document(&read_document(file, from)?, to)?;
+    match args.option("output") {
+        Some(output) => {
+            fs::write(output, text).map_err(|err| {
+                Failure::Error(format!("{}: can't write file: {}", output,
+                    err))
+            })
+        },
+        None => {
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
+
+fn type_name(value_type : OptionType) -> &'static str {
+    match value_type {
+        OptionType::IntegerType => { "int" },
+        OptionType::Int64Type => { "int64" },
+        OptionType::FloatType => { "float" },
+        OptionType::BooleanType => { "bool" },
+        OptionType::StringType => { "string" },
+        OptionType::GroupType => { "group" },
+        OptionType::ArrayType => { "array" },
+        OptionType::ListType => { "list" },
+        OptionType::NoneType => { "none" }
+    }
+}
+
+// Return human readable description of validation problem.
+fn describe(error : &ValidationError) -> String {
+    match &error.kind {
+        ValidationErrorKind::Missing => {
+            "required setting is missing".to_string()
+        },
+        ValidationErrorKind::WrongType { expected, found } => {
+            format!("expected {}, found {}", type_name(*expected),
+                found.map_or("none", type_name))
+        },
+        ValidationErrorKind::OutOfRange => {
+            "value is out of allowed range".to_string()
+        },
+        ValidationErrorKind::PatternMismatch => {
+            "value doesn't match pattern".to_string()
+        },
+        ValidationErrorKind::NotAllowed => {
+            "value is not one of allowed values".to_string()
+        },
+        ValidationErrorKind::Coerced { from } => {
+            format!("value converted from {}", type_name(*from))
+        },
+        ValidationErrorKind::Unknown { suggestion : Some(suggestion) } => {
+            format!("unknown setting, did you mean '{}'?", suggestion)
+        },
+        ValidationErrorKind::Unknown { suggestion : None } => {
+            "unknown setting".to_string()
+        }
+    }
+}
+
+fn validate(args : &[String]) -> Outcome {
+    let args = Arguments::parse(args, &["schema"], &["strict"])?;
+    let file = match args.positional.as_slice() {
+        [file] => { *file },
+        _ => { return usage("validate expects <file>"); }
+    };
+    let schema_file = match args.option("schema") {
+        Some(schema_file) => { schema_file },
+        None => { return usage("validate expects --schema <schema>"); }
+    };
+
+    let schema = Schema::load_from_file(Path::new(schema_file))
+        .map_err(|err| {
+            Failure::Error(format!("{}: can't load schema: {}", schema_file,
+                err))
+        })?;
+    let cfg = load(file)?;
+    let mut problems = cfg.validate(&schema);
+    if args.flag("strict") {
+        problems.extend(cfg.lint(&schema));
+    }
+
+    let mut errors = 0;
+    for problem in problems.iter() {
+        let level = if problem.is_warning() {
+            "warning"
+        } else {
+            errors += 1;
+            "error"
+        };
+        println!("{}: {}: {}: {}", file, level, problem.path,
+            describe(problem));
+    }
+
+    match errors {
+        0 => { Ok(()) },
+        _ => {
+            error(format!("{}: {} error(s), {} warning(s)", file, errors,
+                problems.len() - errors))
+        }
+    }
+}
+
+// Return true if text has comments or include directives, formatter
+// writes parsed settings only and would drop them.
+fn has_comments(text : &str) -> bool {
+    let mut chars = text.chars().peekable();
+    let mut line_start = true;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => { chars.next(); },
+                        '"' => { break; },
+                        _ => {}
+                    }
+                }
+            },
+            '#' => { return true; },
+            '/' if matches!(chars.peek(), Some('/') | Some('*')) => {
+                return true;
+            },
+            '@' if line_start => { return true; },
+            _ => {}
+        }
+        if c == '\n' {
+            line_start = true;
+        } else if !c.is_whitespace() {
+            line_start = false;
+        }
+    }
+    false
+}
+
+// Format one file, return false if it was not formatted. With check file
+// is only compared with its formatted text.
+fn format_file(file : &str, check : bool)
+    -> std::result::Result<bool, String> {
+    let text = fs::read_to_string(file)
+        .map_err(|err| format!("can't read file: {}", err))?;
+    if has_comments(&text) {
+        return Err("comments and includes can't be kept by formatter"
+            .to_string());
+    }
+
+    let mut cfg = Config::new();
+    cfg.load_from_string(text.as_str())
+        .map_err(|err| format!("can't parse file: {}", err))?;
+    let formatted = cfg.save_to_string()
+        .map_err(|err| format!("can't format file: {}", err))?;
+    if formatted == text {
+        return Ok(true);
+    }
+
+    if !check {
+        fs::write(file, formatted)
+            .map_err(|err| format!("can't write file: {}", err))?;
+    }
+    Ok(false)
+}
+
+fn fmt(args : &[String]) -> Outcome {
+    let args = Arguments::parse(args, &[], &["check"])?;
+    if args.positional.is_empty() {
+        return usage("fmt expects <file>");
+    }
+
+    let check = args.flag("check");
+    let mut unformatted = 0;
+    let mut failed = 0;
+    for file in args.positional.iter() {
+        match format_file(file, check) {
+            Ok(true) => {},
+            Ok(false) => {
+                if check {
+                    println!("{}", file);
+                    unformatted += 1;
+                }
+            },
+            Err(message) => {
+                eprintln!("rustconfig: {}: {}", file, message);
+                failed += 1;
+            }
+        }
+    }
+
+    match (unformatted, failed) {
+        (0, 0) => { Ok(()) },
+        (_, 0) => { error(format!("{} file(s) not formatted", unformatted)) },
+        (0, _) => { error(format!("{} file(s) can't be formatted", failed)) },
+        _ => {
+            error(format!("{} file(s) not formatted, {} file(s) can't be \
+                formatted", unformatted, failed))
+        }
+    }
+}
+
+fn diff(args : &[String]) -> Outcome {
+    let args = Arguments::parse(args, &[], &["json"])?;
+    let (old_file, new_file) = match args.positional.as_slice() {
+        [old_file, new_file] => { (*old_file, *new_file) },
+        _ => { return usage("diff expects <old> <new>"); }
+    };
+
+    let changes = load(old_file)?.diff(&load(new_file)?);
+    if !args.flag("json") {
+        for change in changes.iter() {
+            match &change.kind {
+                ChangeKind::Added(value) => {
+                    println!("+ {} = {};", change.path, value);
+                },
+                ChangeKind::Removed(value) => {
+                    println!("- {} = {};", change.path, value);
+                },
+                ChangeKind::Changed { old, new } => {
+                    println!("~ {} = {} -> {};", change.path, old, new);
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    let document : Vec<serde_json::Value> = changes.iter().map(|change| {
+        let mut entry = serde_json::Map::new();
+        entry.insert("path".to_string(), change.path.as_str().into());
+        match &change.kind {
+            ChangeKind::Added(value) => {
+                entry.insert("change".to_string(), "added".into());
+                entry.insert("value".to_string(), to_document(value));
+            },
+            ChangeKind::Removed(value) => {
+                entry.insert("change".to_string(), "removed".into());
+                entry.insert("value".to_string(), to_document(value));
+            },
+            ChangeKind::Changed { old, new } => {
+                entry.insert("change".to_string(), "changed".into());
+                entry.insert("old".to_string(), to_document(old));
+                entry.insert("new".to_string(), to_document(new));
+            }
+        }
+        serde_json::Value::Object(entry)
+    }).collect();
+
+    let text = serde_json::to_string_pretty(&document).map_err(|err| {
+        Failure::Error(format!("can't write document: {}", err))
+    })?;
+    println!("{}", text);
+    Ok(())
+}
+
+fn main() {
+    let args : Vec<String> = env::args().skip(1).collect();
+    let outcome = match args.split_first() {
+        Some((command, rest)) => {
+            match command.as_str() {
+                "get" => { get(rest) },
+                "set" => { set(rest) },
+                "convert" => { convert(rest) },
+                "validate" => { validate(rest) },
+                "fmt" => { fmt(rest) },
+                "diff" => { diff(rest) },
+                "-h" | "--help" | "help" => {
+                    println!("{}", USAGE);
+                    Ok(())
+                },
+                _ => { usage(&format!("unknown command '{}'", command)) }
+            }
+        },
+        None => { usage("missing command") }
+    };
+
+    match outcome {
+        Ok(()) => {},
+        Err(Failure::Usage(message)) => {
+            eprintln!("rustconfig: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        },
+        Err(Failure::Error(message)) => {
+            eprintln!("rustconfig: {}", message);
+            process::exit(1);
+        }
+    }
+}
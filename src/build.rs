@@ -0,0 +1,152 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Helpers for build scripts.
+//!
+//! Default config shipped inside a binary is checked at build time, so
+//! syntax errors fail compilation instead of first start. Build script
+//! parses the file with `check` or also embeds it with `embed`:
+//!
+//! ```no_run
+//! // In `main` of build.rs.
+//! librustconfig::build::embed("config/defaults.cfg", "DEFAULT_CONFIG");
+//! ```
+//!
+//! Generated file is included in the crate and the constant parsed with
+//! `Config::load_from_string`:
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/default_config.rs"));
+//!
+//! let mut cfg = Config::new();
+//! cfg.load_from_string(DEFAULT_CONFIG)?;
+//! ```
+
+use crate::config::{Config, Errors};
+
+use std::env;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Config file failed to parse.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuildError {
+    /// Parsed file, or included file containing the error.
+    pub file : PathBuf,
+    /// Line of the error, 0 if file can't be read.
+    pub line : u32,
+    /// Error description.
+    pub message : String
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.file.display(), self.line, self.message)
+    }
+}
+
+impl error::Error for BuildError {}
+
+/// Parse config file, error contains file and line of the problem.
+///
+/// # Example
+/// ```
+/// use librustconfig::build;
+/// use std::fs;
+///
+/// let file_name = std::env::temp_dir().join("build-validate.cfg");
+/// fs::write(&file_name, "port = 80;\nhost = ;\n").unwrap();
+/// assert_eq!(build::validate(&file_name).err().map(|error| error.line),
+///     Some(2));
+/// fs::remove_file(&file_name).unwrap();
+/// ```
+pub fn validate<P>(file_name : P) -> Result<Config, BuildError>
+    where P: AsRef<Path> {
+    let file_name = file_name.as_ref();
+    let mut cfg = Config::new();
+    match cfg.load_from_file(file_name) {
+        Ok(()) => { Ok(cfg) },
+        Err(Errors::FileNotExists) => {
+            Err(BuildError {
+                file : file_name.to_path_buf(),
+                line : 0,
+                message : "file not found".to_string()
+            })
+        },
+        Err(_) => {
+            let diagnostic = cfg.parse_error();
+            Err(BuildError {
+                file : diagnostic.and_then(|error| error.file.clone())
+                    .unwrap_or_else(|| file_name.to_path_buf()),
+                line : diagnostic.map_or(0, |error| error.line),
+                message : diagnostic.map_or_else(|| "parse error".to_string(),
+                    |error| error.message.clone())
+            })
+        }
+    }
+}
+
+/// Parse config file from build script. Cargo reruns the build script when
+/// the file changes, parse error fails the build with file and line.
+pub fn check<P>(file_name : P) where P: AsRef<Path> {
+    let file_name = file_name.as_ref();
+    println!("cargo:rerun-if-changed={}", file_name.display());
+    if let Err(error) = validate(file_name) {
+        panic!("invalid config {}", error);
+    }
+}
+
+/// Parse config file from build script and write `pub const` string with
+/// its text into `OUT_DIR`. File is named after the constant in lower case,
+/// e.g. `default_config.rs` for `DEFAULT_CONFIG`. Return path of generated
+/// file.
+pub fn embed<P>(file_name : P, name : &str) -> PathBuf where P: AsRef<Path> {
+    let file_name = file_name.as_ref();
+    check(file_name);
+
+    let text = fs::read_to_string(file_name).unwrap_or_else(|error| {
+        panic!("can't read config {}: {}", file_name.display(), error)
+    });
+    let out_dir = env::var_os("OUT_DIR")
+        .expect("OUT_DIR is not set, embed must be called from build script");
+    let output = Path::new(&out_dir)
+        .join(format!("{}.rs", name.to_lowercase()));
+    fs::write(&output, constant_source(name, &text)).unwrap_or_else(|error| {
+        panic!("can't write {}: {}", output.display(), error)
+    });
+    output
+}
+
+// Return Rust source declaring string constant.
+pub(crate) fn constant_source(name : &str, text : &str) -> String {
+    format!("pub const {} : &str = {:?};\n", name, text)
+}
@@ -0,0 +1,103 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Bulk reads.
+//!
+//! Many paths are read in one walk of the setting tree, common path prefixes
+//! are resolved only once.
+
+use crate::config::Config;
+use crate::path;
+use crate::value::{self, Value};
+use libconfig_sys as raw;
+
+use std::collections::HashMap;
+
+/// Requested path, index in result list and segments not yet resolved.
+type Request<'a> = (usize, &'a [&'a str]);
+
+impl Config {
+
+    /// Read values of many paths in one walk of the setting tree. Values are
+    /// returned in the order of paths, `None` for missing settings.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    /// use librustconfig::value::Value;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { host = \"a\"; port = 80; };")
+    ///     .is_ok() {
+    ///     let values = cfg.read_many(&["server.host", "server.port",
+    ///         "server.missing"]);
+    ///     assert_eq!(values, vec![Some(Value::Str("a".to_string())),
+    ///         Some(Value::Int(80)), None]);
+    /// }
+    /// ```
+    pub fn read_many<S>(&self, paths : &[S]) -> Vec<Option<Value>>
+        where S: AsRef<str> {
+        let segments : Vec<path::Segments<'_>> = paths.iter()
+            .map(|p| path::split(p.as_ref())).collect();
+        let mut values = vec![None; paths.len()];
+
+        if let Some(root) = self.root_element.and_then(|r| self.follow(r)) {
+            let requests = segments.iter().enumerate()
+                .map(|(index, s)| (index, s.as_slice())).collect();
+            self.read_requests(root, requests, &mut values);
+        }
+        values
+    }
+
+    // Read requested paths below option.
+    fn read_requests(&self, option : *mut raw::config_setting_t,
+        requests : Vec<Request<'_>>, values : &mut [Option<Value>]) {
+        let mut children : HashMap<&str, Vec<Request<'_>>> = HashMap::new();
+
+        for (index, segments) in requests {
+            match segments.split_first() {
+                Some((segment, rest)) => {
+                    children.entry(segment).or_default().push((index, rest));
+                },
+                None => {
+                    self.reader(Some(option)).mark_read();
+                    values[index] = value::read(option);
+                }
+            }
+        }
+
+        for (segment, requests) in children {
+            if let Some(child) = path::child(option, segment)
+                .and_then(|child| self.follow(child)) {
+                self.read_requests(child, requests, values);
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Memoized path lookups.
+//!
+//! Config keeps resolved settings of looked up paths. Every write to config
+//! advances its mutation generation, which invalidates its cache, so cached
+//! settings are never used after they could be removed. Writers are handed
+//! out only by mutable config, so handing out writer counts as write.
+//!
+//! Readers returned by index operators of config are kept in the cache as
+//! well and dropped with it when generation changes.
+//!
+//! Optionally members of wide groups are indexed by name, so paths through
+//! groups with thousands of members don't need linear member search.
+
+use crate::config::{Config, OptionReader};
+use crate::index::{self, Readers};
+use crate::key;
+use libconfig_sys as raw;
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::rc::Rc;
+
+/// Maximal number of cached paths, cache is cleared when it is full.
+const LOOKUP_CACHE_CAPACITY : usize = 1024;
+
+/// Separators of path segments accepted by libconfig lookups.
+const LOOKUP_SEPARATORS : &[char] = &['.', '/', ':'];
+
+/// Members of group by name.
+type GroupIndex = HashMap<Box<str>, *mut raw::config_setting_t>;
+
+/// Mutation generation of config, shared with its write guards.
+pub(crate) type Generation = Rc<Cell<u64>>;
+
+/// Settings found by path.
+#[derive(Default)]
+pub(crate) struct LookupCache {
+    generation : u64,
+    entries : HashMap<String, Option<*mut raw::config_setting_t>>,
+    // Minimal number of members of indexed group, None if disabled.
+    wide_group_length : Option<u32>,
+    groups : HashMap<usize, GroupIndex>,
+    readers : Readers
+}
+
+impl LookupCache {
+
+    // Drop cached settings and readers if config was written since they
+    // were cached. Generation changes only while config is borrowed
+    // mutably, so no reader is borrowed then.
+    fn sync(&mut self, generation : u64) {
+        if self.generation != generation {
+            self.entries.clear();
+            self.groups.clear();
+            self.readers.clear();
+            self.generation = generation;
+        }
+    }
+
+    // Find member of group, using index if group is wide.
+    fn member(&mut self, group : *mut raw::config_setting_t, name : &str)
+        -> Option<*mut raw::config_setting_t> {
+        let length = unsafe { raw::config_setting_length(group) } as u32;
+        if raw::config_setting_is_group(group) != raw::CONFIG_TRUE ||
+            self.wide_group_length.is_none_or(|wide| length < wide) {
+            return crate::path::child(group, name);
+        }
+
+        self.groups.entry(group as usize).or_insert_with(|| {
+            (0..length).filter_map(|index| {
+                let child = unsafe {
+                    raw::config_setting_get_elem(group, index)
+                };
+                if child.is_null() {
+                    return None;
+                }
+                let child_name = raw::config_setting_name(child);
+                if child_name.is_null() {
+                    return None;
+                }
+                let child_name = unsafe { CStr::from_ptr(child_name) }
+                    .to_str().ok()?;
+                Some((child_name.into(), child))
+            }).collect()
+        }).get(name).copied()
+    }
+
+    // Find setting at path walking from root.
+    fn find(&mut self, root : *mut raw::config_setting_t, path : &str)
+        -> Option<*mut raw::config_setting_t> {
+        let mut current = root;
+        for segment in path.split(LOOKUP_SEPARATORS)
+            .filter(|segment| !segment.is_empty()) {
+            current = if crate::path::index(segment).is_some() {
+                crate::path::child(current, segment)?
+            } else {
+                self.member(current, segment)?
+            };
+        }
+        Some(current)
+    }
+}
+
+// Invalidate lookup cache of config with generation, settings are going to
+// change.
+pub(crate) fn invalidate(generation : &Generation) {
+    generation.set(generation.get().wrapping_add(1));
+}
+
+impl Config {
+
+    /// Index members of groups with at least `min_length` members by name,
+    /// so lookups through very wide groups don't search members one by
+    /// one. Indexes are built on first lookup and dropped on any write.
+    /// None disables indexing, which is the default.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// cfg.index_wide_groups(Some(1000));
+    /// ```
+    pub fn index_wide_groups(&mut self, min_length : Option<u32>) {
+        let mut cache = self.lookup_cache.borrow_mut();
+        cache.wide_group_length = min_length;
+        cache.entries.clear();
+        cache.groups.clear();
+    }
+
+    // Find setting at path using lookup cache.
+    pub(crate) fn lookup(&self, path : &str)
+        -> Option<*mut raw::config_setting_t> {
+        let mut cache = self.lookup_cache.borrow_mut();
+        cache.sync(self.generation.get());
+        if let Some(option) = cache.entries.get(path) {
+            return *option;
+        }
+
+        let option = if cache.wide_group_length.is_some() {
+            let root = raw::config_root_setting(&self.config);
+            if root.is_null() {
+                None
+            } else {
+                cache.find(root, path)
+            }
+        } else {
+            key::with_c_str(path, |name| unsafe {
+                raw::config_lookup(&self.config, name.as_ptr())
+            }).filter(|option| !option.is_null())
+        };
+
+        if cache.entries.len() >= LOOKUP_CACHE_CAPACITY {
+            cache.entries.clear();
+        }
+        cache.entries.insert(path.to_string(), option);
+        option
+    }
+    // Return reader of element kept in lookup cache until config is written.
+    pub(crate) fn stored_reader(&self,
+        element : Option<*mut raw::config_setting_t>)
+        -> &OptionReader<'static> {
+        let mut cache = self.lookup_cache.borrow_mut();
+        cache.sync(self.generation.get());
+        let reader = index::stored_reader(&mut cache.readers, element,
+            &self.tracker, self.resolve_references);
+        // Boxed reader doesn't move and lives until generation changes.
+        unsafe { &*reader }
+    }
+}
@@ -0,0 +1,158 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Deep copies of configs and setting subtrees.
+//!
+//! Cloned config owns its own libconfig setting tree, so it can be changed
+//! without affecting the original, e.g. per-tenant copies of template
+//! config. Setting types and integer formats are kept exactly.
+
+use crate::config::{Config, Errors, OptionReader, OptionWriter, Result};
+use crate::freeze;
+use crate::value::{self, Value};
+use libconfig_sys as raw;
+
+use std::ffi::CStr;
+
+impl Clone for Config {
+
+    /// Deep copy config with all its settings. Clone is never frozen and
+    /// doesn't track reads.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut template = Config::new();
+    /// if template.load_from_string("server : { port = 80; };").is_ok() {
+    ///     let mut tenant = template.clone();
+    ///     assert!(tenant.set("server.port", 8080).is_ok());
+    ///     assert_eq!(template.value("server.port").unwrap().as_int32(),
+    ///         Some(80));
+    /// }
+    /// ```
+    fn clone(&self) -> Config {
+        let mut cfg = self.copy_settings();
+        let include_dir = raw::config_get_include_dir(&self.config);
+        if !include_dir.is_null() {
+            unsafe {
+                raw::config_set_include_dir(&mut cfg.config, include_dir);
+            }
+        }
+        cfg.parse_error = self.parse_error.clone();
+        *cfg.origins.borrow_mut() = self.origins.borrow().clone();
+
+        if let (Some(source), Some(root)) =
+            (self.root_element, cfg.root_element) {
+            if value::copy_children(source, root).is_err() {
+                panic!("librustconfig: can't clone config");
+            }
+        }
+        cfg
+    }
+}
+
+impl OptionReader<'_> {
+
+    /// Copy setting with all its children into owned value, None if setting
+    /// not exists. Same as `to_value`, value can be written to any config
+    /// with `OptionWriter::from_value` or `Config::set`.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("limits : { rps = 10; };").is_ok() {
+    ///     let limits = cfg.value("limits").unwrap().clone_subtree().unwrap();
+    ///     let mut other = Config::new();
+    ///     assert!(other.set("tenant.limits", limits).is_ok());
+    ///     assert_eq!(other.value("tenant.limits.rps").unwrap().as_int32(),
+    ///         Some(10));
+    /// }
+    /// ```
+    pub fn clone_subtree(&self) -> Option<Value> {
+        self.to_value()
+    }
+}
+
+impl<'a> OptionWriter<'a> {
+
+    /// Add deep copy of setting from another config to current group or
+    /// collection, keeping its name, type and format. Root setting is copied
+    /// member by member.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut template = Config::new();
+    /// let mut tenant = Config::new();
+    /// if template.load_from_string("db : { port = 0x1F90; };").is_ok() &&
+    ///     tenant.load_from_string("name = \"acme\";").is_ok() {
+    ///     let db = template.value("db").unwrap();
+    ///     assert!(tenant.root().copy_from(&db).is_ok());
+    ///     assert_eq!(tenant.value("db.port").unwrap().as_int32(),
+    ///         Some(8080));
+    /// }
+    /// ```
+    pub fn copy_from(&self, source : &OptionReader)
+        -> Result<&OptionWriter<'a>> {
+        let parent = self.element.ok_or(Errors::ElementNotExists)?;
+        let option = source.element.ok_or(Errors::ElementNotExists)?;
+        if !freeze::writable(parent) {
+            return Err(Errors::ConfigFrozen);
+        }
+        if raw::config_setting_is_root(option) == raw::CONFIG_TRUE {
+            if raw::config_setting_is_group(parent) != raw::CONFIG_TRUE {
+                return Err(Errors::TypeMismatch);
+            }
+            value::copy_children(option, parent)?;
+            return Ok(self);
+        }
+
+        let name = raw::config_setting_name(option);
+        let name = if name.is_null() {
+            ""
+        } else {
+            unsafe { CStr::from_ptr(name) }.to_str()
+                .map_err(|_| Errors::InvalidName)?
+        };
+        let copy = self.add(name, raw::config_setting_type(option) as i16)?;
+        if let Err(error) = value::fill(option, copy) {
+            unsafe {
+                let index = raw::config_setting_index(copy);
+                raw::config_setting_remove_elem(parent, index as u32);
+            }
+            return Err(error);
+        }
+        Ok(self)
+    }
+}
@@ -0,0 +1,169 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Structural equality of configs.
+//!
+//! Settings are equal if they have the same type and value, groups if they
+//! have members with the same names, in any order, and equal values,
+//! collections if their elements are equal in order. Formatting, such as
+//! integer notation, and source positions are ignored.
+
+use crate::config::{Config, OptionReader};
+use libconfig_sys as raw;
+
+use std::ffi::CStr;
+
+impl PartialEq for Config {
+
+    /// Compare setting trees of configs.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// let mut other = Config::new();
+    /// if cfg.load_from_string("a = 255; b = \"x\";").is_ok() &&
+    ///     other.load_from_string("b = \"x\";\na = 0xFF;").is_ok() {
+    ///     assert!(cfg == other);
+    /// }
+    /// ```
+    fn eq(&self, other : &Config) -> bool {
+        match (self.root_element, other.root_element) {
+            (Some(left), Some(right)) => { equal(left, right) },
+            (None, None) => { true },
+            _ => { false }
+        }
+    }
+}
+
+impl PartialEq for OptionReader<'_> {
+
+    /// Compare settings with all their children. Names of compared settings
+    /// themselves are ignored, so settings of different paths or configs can
+    /// be compared.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("a : { port = 80; };\nb : { port = 80; };")
+    ///     .is_ok() {
+    ///     assert!(cfg.value("a").unwrap() == cfg.value("b").unwrap());
+    /// }
+    /// ```
+    fn eq(&self, other : &OptionReader<'_>) -> bool {
+        match (self.element, other.element) {
+            (Some(left), Some(right)) => { equal(left, right) },
+            (None, None) => { true },
+            _ => { false }
+        }
+    }
+}
+
+// Compare settings recursively.
+fn equal(left : *mut raw::config_setting_t,
+    right : *mut raw::config_setting_t) -> bool {
+    let value_type = raw::config_setting_type(left);
+    if value_type != raw::config_setting_type(right) {
+        return false;
+    }
+
+    unsafe {
+        match value_type as i16 {
+            raw::CONFIG_TYPE_INT => {
+                raw::config_setting_get_int(left) ==
+                    raw::config_setting_get_int(right)
+            },
+            raw::CONFIG_TYPE_INT64 => {
+                raw::config_setting_get_int64(left) ==
+                    raw::config_setting_get_int64(right)
+            },
+            raw::CONFIG_TYPE_FLOAT => {
+                raw::config_setting_get_float(left) ==
+                    raw::config_setting_get_float(right)
+            },
+            raw::CONFIG_TYPE_BOOL => {
+                raw::config_setting_get_bool(left) ==
+                    raw::config_setting_get_bool(right)
+            },
+            raw::CONFIG_TYPE_STRING => {
+                let left = raw::config_setting_get_string(left);
+                let right = raw::config_setting_get_string(right);
+                left.is_null() == right.is_null() && (left.is_null() ||
+                    CStr::from_ptr(left) == CStr::from_ptr(right))
+            },
+            raw::CONFIG_TYPE_GROUP => { equal_groups(left, right) },
+            raw::CONFIG_TYPE_ARRAY | raw::CONFIG_TYPE_LIST => {
+                equal_elements(left, right)
+            },
+            _ => { true }
+        }
+    }
+}
+
+// Compare group members by name.
+fn equal_groups(left : *mut raw::config_setting_t,
+    right : *mut raw::config_setting_t) -> bool {
+    let length = unsafe { raw::config_setting_length(left) };
+    if length != unsafe { raw::config_setting_length(right) } {
+        return false;
+    }
+
+    (0..length).all(|index| {
+        let child = unsafe {
+            raw::config_setting_get_elem(left, index as u32)
+        };
+        if child.is_null() {
+            return false;
+        }
+        let name = raw::config_setting_name(child);
+        if name.is_null() {
+            return false;
+        }
+        let member = unsafe { raw::config_setting_get_member(right, name) };
+        !member.is_null() && equal(child, member)
+    })
+}
+
+// Compare collection elements by index.
+fn equal_elements(left : *mut raw::config_setting_t,
+    right : *mut raw::config_setting_t) -> bool {
+    let length = unsafe { raw::config_setting_length(left) };
+    if length != unsafe { raw::config_setting_length(right) } {
+        return false;
+    }
+
+    (0..length as u32).all(|index| unsafe {
+        equal(raw::config_setting_get_elem(left, index),
+            raw::config_setting_get_elem(right, index))
+    })
+}
@@ -28,49 +28,261 @@
 /*                                                                            */
 /******************************************************************************/
 
+use crate::cache::{self, Generation, LookupCache};
+use crate::encoding;
+use crate::freeze;
+use crate::index::ChildReaders;
+use crate::key;
+use crate::track::Tracker;
+use crate::value::{self, Value};
 use libconfig_sys as raw;
 
-use std::{mem::MaybeUninit, path};
+use std::{fmt, fs, io, mem::MaybeUninit, path, ptr};
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::ffi::{CStr, CString};
+use std::os::raw::{c_int, c_short};
+use std::marker::PhantomData;
 
 /// Configuration file.
 pub struct Config {
-    config : raw::config_t,
-    root_element : Option<*mut raw::config_setting_t>
+    pub(crate) config : raw::config_t,
+    pub(crate) root_element : Option<*mut raw::config_setting_t>,
+    pub(crate) resolve_references : bool,
+    pub(crate) interpolated : bool,
+    pub(crate) line_ending : LineEnding,
+    pub(crate) format_options : FormatOptions,
+    pub(crate) parse_error : Option<ParseDiagnostic>,
+    pub(crate) tracker : Option<Tracker>,
+    pub(crate) origins : RefCell<HashMap<String, Origin>>,
+    pub(crate) lookup_cache : RefCell<LookupCache>,
+    pub(crate) generation : Generation
 }
 
 /// Option value type.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum OptionType {
     IntegerType,
     Int64Type,
     FloatType,
     StringType,
-    BooleanType
+    BooleanType,
+    GroupType,
+    ArrayType,
+    ListType,
+    /// Setting without value, e.g. created with unknown type.
+    NoneType
+}
+
+/// Notation of saved integer setting.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntFormat {
+    /// Decimal, e.g. `255`.
+    Default,
+    /// Hexadecimal, e.g. `0xFF`.
+    Hex
+}
+
+/// Line ending of saved config text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineEnding {
+    /// Unix style `\n`.
+    Lf,
+    /// Windows style `\r\n`.
+    Crlf
+}
+
+/// Style of saved config text. Default style matches libconfig defaults.
+///
+/// # Example
+/// ```
+/// use librustconfig::config::{Config, FormatOptions};
+///
+/// let mut cfg = Config::new();
+/// if cfg.load_from_string("server : { port = 80; ratio = 0.5; };").is_ok() {
+///     cfg.set_format_options(FormatOptions::new()
+///         .tab_width(4)
+///         .float_precision(2)
+///         .semicolon_separators(false)
+///         .open_brace_on_separate_line(false));
+///     assert_eq!(cfg.save_to_string().unwrap(),
+///         "server : {\n    port = 80\n    ratio = 0.5\n}\n");
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FormatOptions {
+    pub(crate) tab_width : u16,
+    pub(crate) float_precision : Option<u16>,
+    pub(crate) semicolon_separators : bool,
+    pub(crate) colon_assignment_for_groups : bool,
+    pub(crate) colon_assignment_for_non_groups : bool,
+    pub(crate) open_brace_on_separate_line : bool
+}
+
+/// Source of setting value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Origin {
+    /// File the setting was read from.
+    pub file : path::PathBuf,
+    /// Index of merged layer, 0 for config loaded from single file.
+    pub layer : usize,
+    /// Line in the file.
+    pub line : u32
 }
 
-/// Writer for configuration option.
-#[derive(Clone, Copy)]
-pub struct OptionWriter {
-    element : Option<*mut raw::config_setting_t>
+/// How `Config::merge` resolves settings present in both configs. Groups
+/// are always merged member by member.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MergeStrategy {
+    /// Setting of merged config replaces existing one.
+    Overwrite,
+    /// Existing setting is kept, only missing settings are added.
+    KeepExisting,
+    /// Elements of merged array or list are appended to existing one of the
+    /// same kind, other settings are replaced.
+    AppendArrays
+}
+
+/// Kind of libconfig load failure.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParseErrorKind {
+    /// File or included file can't be read.
+    FileIo,
+    /// Text is not valid libconfig syntax.
+    Parse
+}
+
+/// Details of last failed load reported by libconfig.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseDiagnostic {
+    /// File containing the error, None for config parsed from string.
+    pub file : Option<path::PathBuf>,
+    /// Line of the error, 0 if unknown.
+    pub line : u32,
+    /// Error description, e.g. `syntax error`.
+    pub message : String,
+    /// Kind of failure.
+    pub kind : ParseErrorKind
+}
+
+// Format as `file:line: message`.
+impl fmt::Display for ParseDiagnostic {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.file {
+            Some(file) => {
+                write!(f, "{}:{}: {}", file.display(), self.line,
+                    self.message)
+            },
+            None => { write!(f, "line {}: {}", self.line, self.message) }
+        }
+    }
+}
+
+/// Writer for configuration option. Writer borrows config mutably, so no
+/// reader can be alive while settings are written or deleted.
+///
+/// # Example
+/// ```compile_fail
+/// use librustconfig::config::Config;
+///
+/// let mut cfg = Config::new();
+/// cfg.load_from_string("port = 80;").unwrap();
+/// let port = cfg.value("port").unwrap();
+/// cfg.writer("port").unwrap().delete().unwrap();
+/// port.as_int32();
+/// ```
+///
+/// Writer is consumed by `delete` and writers of new children borrow their
+/// parent writer, so no writer of deleted setting can be used.
+///
+/// ```compile_fail
+/// use librustconfig::config::Config;
+///
+/// let mut cfg = Config::new();
+/// let mut root = cfg.root();
+/// let mut group = root.create_section("g").unwrap();
+/// let nested = group.create_section("h").unwrap();
+/// group.delete().unwrap();
+/// nested.write_int32("x", 1);
+/// ```
+///
+/// ```compile_fail
+/// use librustconfig::config::Config;
+///
+/// let mut cfg = Config::new();
+/// let writer = cfg.root();
+/// let copy = writer;
+/// writer.delete().unwrap();
+/// copy.write_int32("x", 1);
+/// ```
+pub struct OptionWriter<'a> {
+    pub(crate) element : Option<*mut raw::config_setting_t>,
+    pub(crate) marker : PhantomData<&'a mut Config>
 }
 
 /// Writer for collection (array, list) option.
-#[derive(Clone, Copy)]
-pub struct CollectionWriter {
-    element : Option<*mut raw::config_setting_t>
+pub struct CollectionWriter<'a> {
+    pub(crate) element : Option<*mut raw::config_setting_t>,
+    pub(crate) marker : PhantomData<&'a mut Config>
 }
 
-/// Reader for configuration option.
-pub struct OptionReader {
-    element : Option<*mut raw::config_setting_t>
+/// Reader for configuration option. Reader borrows config and readers of
+/// its children borrow the reader, so none of them can be used after config
+/// is dropped or reloaded.
+///
+/// # Example
+/// ```compile_fail
+/// use librustconfig::config::Config;
+///
+/// let mut cfg = Config::new();
+/// let root = cfg.value("").unwrap();
+/// cfg.load_from_string("port = 80;").unwrap();
+/// root.value("port");
+/// ```
+///
+/// ```compile_fail
+/// use librustconfig::config::Config;
+///
+/// let mut cfg = Config::new();
+/// cfg.load_from_string("name = \"a\";").unwrap();
+/// let name = cfg.value("name").unwrap();
+/// cfg.set("name", 1).unwrap();
+/// name.as_str();
+/// ```
+pub struct OptionReader<'a> {
+    pub(crate) element : Option<*mut raw::config_setting_t>,
+    pub(crate) tracker : Option<Tracker>,
+    // Reader was found following references, `@@` strings are unescaped.
+    pub(crate) resolved : bool,
+    pub(crate) children : ChildReaders,
+    pub(crate) marker : PhantomData<&'a Config>
 }
 
 /// Reader for collection (array, list) option.
-pub struct CollectionReaderIterator {
+pub struct CollectionReaderIterator<'a> {
     element : Option<*mut raw::config_setting_t>,
+    tracker : Option<Tracker>,
+    resolved : bool,
     pos : i32,
-    size : i32
+    size : i32,
+    marker : PhantomData<&'a Config>
+}
+
+/// Iterator over group members with their names.
+pub struct EntriesIterator<'a> {
+    elements : CollectionReaderIterator<'a>
+}
+
+/// Iterator over children of group, array or list with names of group
+/// members.
+pub struct NamedChildrenIterator<'a> {
+    elements : CollectionReaderIterator<'a>
+}
+
+/// Configuration view where all paths are relative to a group.
+pub struct ScopedConfig<'a> {
+    config : &'a Config,
+    prefix : String
 }
 
 /// Config errors codes.
@@ -80,11 +292,95 @@ pub enum Errors {
     FileNotExists,
     SaveError,
     ElementNotExists,
-    DeleteError
+    DeleteError,
+    BackendError,
+    UnresolvedVariable,
+    RecursiveVariable,
+    WriteError,
+    ConfigFrozen,
+    TypeMismatch,
+    OutOfRange,
+    InvalidName,
+    DuplicateName,
+    InvalidString
+}
+
+impl fmt::Display for Errors {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Errors::ParseError => { "can't parse config" },
+            Errors::FileNotExists => { "file does not exist" },
+            Errors::SaveError => { "can't save config" },
+            Errors::ElementNotExists => { "setting does not exist" },
+            Errors::DeleteError => { "can't delete setting" },
+            Errors::BackendError => { "key/value store request failed" },
+            Errors::UnresolvedVariable => { "unresolved variable" },
+            Errors::RecursiveVariable => { "recursive variable" },
+            Errors::WriteError => { "can't write setting" },
+            Errors::ConfigFrozen => { "config is frozen" },
+            Errors::TypeMismatch => { "setting has another type" },
+            Errors::OutOfRange => { "value is out of range" },
+            Errors::InvalidName => { "invalid setting name" },
+            Errors::DuplicateName => { "setting already exists" },
+            Errors::InvalidString => { "string contains NUL character" }
+        })
+    }
 }
 
+impl std::error::Error for Errors {}
+
 /// Config result type.
-type Result<T> = std::result::Result<T, Errors>;
+pub(crate) type Result<T> = std::result::Result<T, Errors>;
+
+// Create missing group of path in parent. Nothing is created unless all
+// remaining segments are group member names.
+fn create_group(parent : *mut raw::config_setting_t, segments : &[&str])
+    -> Result<*mut raw::config_setting_t> {
+    if raw::config_setting_is_group(parent) != raw::CONFIG_TRUE ||
+        segments.iter().any(|segment| crate::path::index(segment).is_some()) {
+        return Err(Errors::ElementNotExists);
+    }
+
+    let option = key::with_c_str(segments[0], |name| unsafe {
+        raw::config_setting_add(parent, name.as_ptr(),
+            raw::CONFIG_TYPE_GROUP as i32)
+    }).ok_or(Errors::InvalidString)?;
+    if option.is_null() {
+        return Err(Errors::WriteError);
+    }
+    Ok(option)
+}
+
+// Convert file path to C string. Unix paths are passed to libconfig as raw
+// bytes, so non-UTF-8 names work too.
+#[cfg(unix)]
+fn c_path(path : &path::Path) -> Result<CString> {
+    use std::os::unix::ffi::OsStrExt;
+    CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| Errors::InvalidString)
+}
+
+// Convert file path to C string. libconfig opens files with narrow char
+// fopen, on other platforms paths are passed as UTF-8, paths that are not
+// valid Unicode can't be represented.
+#[cfg(not(unix))]
+fn c_path(path : &path::Path) -> Result<CString> {
+    let path = path.to_str().ok_or(Errors::InvalidString)?;
+    CString::new(path).map_err(|_| Errors::InvalidString)
+}
+
+// Convert C string file path returned by libconfig to path.
+#[cfg(unix)]
+pub(crate) fn path_from_c(path : &CStr) -> path::PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    path::PathBuf::from(std::ffi::OsStr::from_bytes(path.to_bytes()))
+}
+
+// Convert C string file path returned by libconfig to path.
+#[cfg(not(unix))]
+pub(crate) fn path_from_c(path : &CStr) -> path::PathBuf {
+    path::PathBuf::from(path.to_string_lossy().into_owned())
+}
 
 impl Config {
     
@@ -115,11 +411,22 @@ impl Config {
     
         Config {
             config : cfg,
-            root_element : element
+            root_element : element,
+            resolve_references : false,
+            interpolated : false,
+            line_ending : LineEnding::Lf,
+            format_options : FormatOptions::default(),
+            parse_error : None,
+            tracker : None,
+            origins : RefCell::new(HashMap::new()),
+            lookup_cache : RefCell::new(LookupCache::default()),
+            generation : Generation::default()
         }
     }
     
     /// Load config file from file and parse it.
+    /// Files with byte order mark and UTF-16 files are transcoded to UTF-8
+    /// before parsing.
     /// 
     /// # Example
     /// ```
@@ -132,22 +439,18 @@ impl Config {
     /// }
     /// ```
     pub fn load_from_file(&mut self, file_name : &path::Path) -> Result<()> {
+        let _guard = self.write_guard()?;
         if file_name.exists() {
-            unsafe {
-                let result = raw::config_read_file(&mut self.config, 
-                    CString::new(file_name.as_os_str().to_str().unwrap())
-                        .unwrap().as_ptr()
-                );
-                
-                if result == raw::CONFIG_TRUE {
-                    self.root_element = 
-                        Some(raw::config_root_setting(&self.config));
-                    Ok(())
-                } else {
-                    self.root_element = None;
-                    Err(Errors::ParseError)
-                }
+            if let Some(text) = encoding::transcoded(file_name)? {
+                let result = self.load_from_string(text);
+                self.set_error_file(file_name);
+                return result;
             }
+            let c_file_name = c_path(file_name)?;
+            let result = unsafe {
+                raw::config_read_file(&mut self.config, c_file_name.as_ptr())
+            };
+            self.parsed(result, encoding::file_line_ending(file_name))
         } else {
             Err(Errors::FileNotExists)
         }
@@ -166,29 +469,136 @@ impl Config {
     /// ```
     pub fn load_from_string<S>(&mut self, config_string : S) -> Result<()>
         where S: Into<String> {
+        let _guard = self.write_guard()?;
+        let mut text = config_string.into();
+        if text.starts_with(encoding::BYTE_ORDER_MARK) {
+            text.remove(0);
+        }
+        let line_ending = encoding::line_ending(text.as_bytes());
+        let text = CString::new(text).map_err(|_| Errors::InvalidString)?;
           
         let result = unsafe { 
-            raw::config_read_string(&mut self.config, 
-                CString::new(config_string.into()).unwrap().as_ptr())
+            raw::config_read_string(&mut self.config, text.as_ptr())
         };
-        
-        if result == raw::CONFIG_TRUE {
-            let option = raw::config_root_setting(&self.config);
-            
-            if option.is_null() {
-                self.root_element = None;
-                Err(Errors::ParseError)
+        self.parsed(result, line_ending)
+    }
+
+    /// Read whole stream and parse it, e.g. from stdin, socket or archive.
+    /// Text with byte order mark and UTF-16 text are transcoded to UTF-8
+    /// like files. Returns ParseError if the stream can't be read.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_reader(&b"port = 80;"[..]).is_ok() {
+    ///     assert_eq!(cfg.value("port").unwrap().as_int32(), Some(80));
+    /// }
+    /// ```
+    pub fn load_from_reader<R>(&mut self, mut reader : R) -> Result<()>
+        where R: io::Read {
+        let _guard = self.write_guard()?;
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).map_err(|_| Errors::ParseError)?;
+        let text = encoding::decode(&bytes)?;
+        self.load_from_string(text)
+    }
+   
+    /// Return details of last failed load, None if last load succeeded.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("port = 80;\nhost = ;").is_err() {
+    ///     let error = cfg.parse_error().unwrap();
+    ///     assert_eq!(error.line, 2);
+    ///     println!("{}", error);
+    /// }
+    /// ```
+    pub fn parse_error(&self) -> Option<&ParseDiagnostic> {
+        self.parse_error.as_ref()
+    }
+
+    // Update state after libconfig parsed text with result. Recorded reads,
+    // origins and interpolation belong to previous settings. Failed parse
+    // keeps its diagnostic and leaves config empty.
+    pub(crate) fn parsed(&mut self, result : c_int, line_ending : LineEnding)
+        -> Result<()> {
+        self.clear_reads();
+        self.origins.borrow_mut().clear();
+        self.interpolated = false;
+        if result != raw::CONFIG_TRUE {
+            self.parse_error = Some(self.read_parse_error());
+            self.clear_settings();
+            self.root_element = None;
+            return Err(Errors::ParseError);
+        }
+
+        let option = raw::config_root_setting(&self.config);
+        if option.is_null() {
+            self.root_element = None;
+            return Err(Errors::ParseError);
+        }
+        self.root_element = Some(option);
+        self.line_ending = line_ending;
+        self.parse_error = None;
+        Ok(())
+    }
+
+    // Record file of text parsed from memory in diagnostic of failed load.
+    pub(crate) fn set_error_file(&mut self, file_name : &path::Path) {
+        if let Some(diagnostic) = self.parse_error.as_mut() {
+            diagnostic.file = Some(file_name.to_path_buf());
+        }
+    }
+
+    // Remove all root settings, e.g. settings parsed before error. Members
+    // are removed one by one, config_clear needs libconfig 1.7.
+    pub(crate) fn clear_settings(&mut self) {
+        let root = raw::config_root_setting(&self.config);
+        if root.is_null() {
+            return;
+        }
+        unsafe {
+            let mut length = raw::config_setting_length(root);
+            while length > 0 {
+                length -= 1;
+                raw::config_setting_remove_elem(root, length as u32);
+            }
+        }
+    }
+
+    // Read error of failed load reported by libconfig.
+    fn read_parse_error(&self) -> ParseDiagnostic {
+        let file = raw::config_error_file(&self.config);
+        let text = raw::config_error_text(&self.config);
+        ParseDiagnostic {
+            file : if file.is_null() {
+                None
             } else {
-                self.root_element = Some(option);
-                Ok(())
+                Some(path_from_c(unsafe { CStr::from_ptr(file) }))
+            },
+            line : raw::config_error_line(&self.config).max(0) as u32,
+            message : if text.is_null() {
+                "parse error".to_string()
+            } else {
+                unsafe { CStr::from_ptr(text) }.to_string_lossy().into_owned()
+            },
+            kind : match raw::config_error_type(&self.config) {
+                raw::config_error_t::CONFIG_ERR_FILE_IO => {
+                    ParseErrorKind::FileIo
+                },
+                _ => { ParseErrorKind::Parse }
             }
-        } else {
-            self.root_element = None;
-            Err(Errors::ParseError)
         }
     }
-   
-   /// Save current config to file.
+
+   /// Save current config to file with configured line ending. Text is
+   /// written in one pass by the same writer as `save_to_writer`. Config
+   /// with NaN or infinity values is not saved and file is left untouched.
    /// 
    /// # Example
    /// ```
@@ -203,19 +613,16 @@ impl Config {
    /// fs::remove_file(Path::new("test.cfg"));
    /// ```
     pub fn save_to_file(&mut self, file_name : &path::Path) -> Result<()> {
-        let result = unsafe { raw::config_write_file(&mut self.config, 
-            CString::new(file_name.as_os_str().to_str().unwrap())
-                .unwrap().as_ptr())
-        };
-        
-        if result == raw::CONFIG_TRUE {
-            Ok(())
-        } else {
-            Err(Errors::SaveError)
-        }
+        // Reject names libconfig could not load back, like load_from_file.
+        c_path(file_name)?;
+        self.check_savable()?;
+        let file = fs::File::create(file_name)
+            .map_err(|_| Errors::SaveError)?;
+        self.save_to_writer(io::BufWriter::new(file))
     }
     
-    /// Set current config include directory.
+    /// Set current config include directory. Returns InvalidString if path
+    /// contains NUL character.
     /// 
     /// # Example
     /// ```
@@ -223,17 +630,19 @@ impl Config {
     /// use std::path::Path;
     /// 
     /// let mut cfg = Config::new();
-    /// cfg.include_dir(Path::new("/config"));
+    /// assert!(cfg.include_dir(Path::new("/config")).is_ok());
     /// ```
-    pub fn include_dir(&mut self, path : &path::Path) -> () {
+    pub fn include_dir(&mut self, path : &path::Path) -> Result<()> {
+        let c_path = c_path(path)?;
         unsafe {
-            raw::config_set_include_dir(&mut self.config, 
-                CString::new(path.as_os_str().to_str().unwrap())
-                    .unwrap().as_ptr())
+            raw::config_set_include_dir(&mut self.config, c_path.as_ptr())
         }
+        Ok(())
     }
         
     /// Read value from path.
+    /// Collection elements are addressed by `[N]` segments, e.g.
+    /// `servers.[2].host`.
     /// 
     /// # Example
     /// ```
@@ -245,26 +654,244 @@ impl Config {
     ///     None => { /* ... */ }
     /// }
     /// ```
-    pub fn value<S>(&self, path : S) -> Option<OptionReader>
-        where S: Into<String> {
-        OptionReader::new(self.root_element).value(path)
+    pub fn value<S>(&self, path : S) -> Option<OptionReader<'_>>
+        where S: AsRef<str> {
+        if self.resolve_references {
+            return self.value_resolved(path.as_ref());
+        }
+        self.lookup(path.as_ref()).map(|option| self.reader(Some(option)))
     }
-    
+
+    /// Read value from path given as separate segments, so setting names
+    /// don't need to be joined into dotted path. Collection elements are
+    /// addressed by `[N]` segments. Setting names can't contain dots, libconfig
+    /// doesn't allow them.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("hosts : { web : { port = 80; }; };").is_ok() {
+    ///     let port = cfg.value_segments(&["hosts", "web", "port"]);
+    ///     assert_eq!(port.unwrap().as_int32(), Some(80));
+    /// }
+    /// ```
+    pub fn value_segments<S>(&self, segments : &[S]) -> Option<OptionReader<'_>>
+        where S: AsRef<str> {
+        let mut current = self.follow(self.root_element?)?;
+        for segment in segments.iter() {
+            current = self.follow(crate::path::child(current,
+                segment.as_ref())?)?;
+        }
+        Some(self.reader(Some(current)))
+    }
+
+    /// Read value addressed by JSON Pointer (RFC 6901), e.g.
+    /// `/server/hosts/0/port`. Collection elements are addressed by their
+    /// index, `~1` and `~0` escape `/` and `~`.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { hosts = ( { port = 80; } ); };")
+    ///     .is_ok() {
+    ///     let port = cfg.value_ptr("/server/hosts/0/port");
+    ///     assert_eq!(port.unwrap().as_int32(), Some(80));
+    /// }
+    /// ```
+    pub fn value_ptr<S>(&self, pointer : S) -> Option<OptionReader<'_>>
+        where S: AsRef<str> {
+        let mut current = self.follow(self.root_element?)?;
+        for token in crate::path::pointer_tokens(pointer.as_ref())? {
+            let segment = if raw::config_setting_is_group(current) ==
+                raw::CONFIG_TRUE {
+                if crate::path::index(&token).is_some() {
+                    return None;
+                }
+                token
+            } else {
+                format!("[{}]", crate::path::pointer_index(&token)?)
+            };
+            current = self.follow(crate::path::child(current, &segment)?)?;
+        }
+        Some(self.reader(Some(current)))
+    }
+
+    /// Copy group with all its settings into new independent config.
+    /// Returns None if path not exists or is not a group.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("plugins : { redis : { port = 6379; }; };")
+    ///     .is_ok() {
+    ///     if let Some(redis) = cfg.extract("plugins.redis") {
+    ///         assert_eq!(redis.value("port").unwrap().as_int32(),
+    ///             Some(6379));
+    ///     }
+    /// }
+    /// ```
+    pub fn extract<S>(&self, path : S) -> Option<Config>
+        where S: AsRef<str> {
+        let option = self.value(path)?;
+        if !option.is_section()? {
+            return None;
+        }
+
+        let cfg = self.copy_settings();
+        value::copy_children(option.element?, cfg.root_element?).ok()?;
+        Some(cfg)
+    }
+
+    // Create empty config with the same parser and format settings.
+    pub(crate) fn copy_settings(&self) -> Config {
+        let mut cfg = Config::new();
+        unsafe {
+            raw::config_set_options(&mut cfg.config,
+                raw::config_get_options(&self.config));
+        }
+        raw::config_set_default_format(&mut cfg.config,
+            raw::config_get_default_format(&self.config));
+        raw::config_set_tab_width(&mut cfg.config,
+            raw::config_get_tab_width(&self.config));
+        unsafe {
+            raw::config_set_float_precision(&mut cfg.config,
+                raw::config_get_float_precision(&self.config));
+        }
+        cfg.resolve_references = self.resolve_references;
+        cfg.interpolated = self.interpolated;
+        cfg.line_ending = self.line_ending;
+        cfg.format_options = self.format_options;
+        cfg
+    }
+
+    /// Set value of setting at path. Collection elements are addressed by
+    /// `[N]` segments, e.g. `servers.[0].port`. Existing setting is updated
+    /// in place, group member of another type is replaced. New setting is
+    /// added to parent group, missing parent groups are created. `[N]`
+    /// segment equal to collection length appends new element.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("servers = ( { port = 8080; } );").is_ok() {
+    ///     assert!(cfg.set("servers.[0].port", 80).is_ok());
+    ///     assert_eq!(cfg.value("servers.[0].port").unwrap().as_int32(),
+    ///         Some(80));
+    ///     assert!(cfg.set("log.file.path", "/var/log/app").is_ok());
+    /// }
+    /// ```
+    pub fn set<S, V>(&mut self, path : S, value : V) -> Result<()>
+        where S: AsRef<str>, V: Into<Value> {
+        let _guard = self.write_guard()?;
+        let path = path.as_ref();
+        let value = value.into();
+        if path.contains('\0') || value::contains_nul(&value) {
+            return Err(Errors::InvalidString);
+        }
+        let segments = crate::path::split(path);
+        let (name, groups) = segments.split_last()
+            .ok_or(Errors::ElementNotExists)?;
+        crate::merge::forget_origins(&mut self.origins.borrow_mut(),
+            &segments.join(&crate::path::PATH_SEPARATOR.to_string()));
+
+        let mut parent = self.root_element.ok_or(Errors::ElementNotExists)?;
+        for (depth, segment) in groups.iter().enumerate() {
+            parent = match crate::path::child(parent, segment) {
+                Some(option) => { option },
+                None => { create_group(parent, &groups[depth..])? }
+            };
+        }
+
+        let index = crate::path::index(name);
+        match crate::path::child(parent, name) {
+            Some(option) => {
+                if value::assign(option, &value)? {
+                    return Ok(());
+                }
+                if index.is_some() {
+                    return Err(Errors::WriteError);
+                }
+
+                let result = key::with_c_str(name, |name| unsafe {
+                    raw::config_setting_remove(parent, name.as_ptr())
+                }).ok_or(Errors::InvalidString)?;
+                if result != raw::CONFIG_TRUE {
+                    return Err(Errors::WriteError);
+                }
+                value::write(parent, name, &value).map(|_| ())
+            },
+            None => {
+                let is_group = raw::config_setting_is_group(parent) ==
+                    raw::CONFIG_TRUE;
+                match index {
+                    Some(index) => {
+                        let length = unsafe {
+                            raw::config_setting_length(parent)
+                        };
+                        if is_group || index as i32 != length {
+                            return Err(Errors::ElementNotExists);
+                        }
+                        value::write(parent, "", &value).map(|_| ())
+                    },
+                    None => {
+                        if !is_group {
+                            return Err(Errors::ElementNotExists);
+                        }
+                        value::write(parent, name, &value).map(|_| ())
+                    }
+                }
+            }
+        }
+    }
+
+    /// Create read-only view of config where all paths are relative to the
+    /// group at path. Settings of the view are written with `writer`.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { port = 8080; };").is_ok() {
+    ///     let server = cfg.scoped("server");
+    ///     assert_eq!(server.value("port").unwrap().as_int32(), Some(8080));
+    /// }
+    /// ```
+    pub fn scoped<S>(&self, path : S) -> ScopedConfig<'_>
+        where S: AsRef<str> {
+        ScopedConfig {
+            config : self,
+            prefix : path.as_ref().to_string()
+        }
+    }
+
     /// Create new group section.
     /// 
     /// # Example
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// match cfg.create_section("root") {
     ///     Some(s) => { /* ... */ },
     ///     None => { /* ... */ }
     /// }
     /// ```
-    pub fn create_section<S>(&self, path : S) -> Option<OptionWriter>
-        where S: Into<String> {
-        OptionWriter::new(self.root_element).create_section(path)
+    pub fn create_section<S>(&mut self, path : S)
+        -> Option<OptionWriter<'_>>
+        where S: AsRef<str> {
+        cache::invalidate(&self.generation);
+        OptionWriter::new(self.root_element)
+            .add(path.as_ref(), raw::CONFIG_TYPE_GROUP).ok()
+            .map(|option| OptionWriter::new(Some(option)))
     }
 
     /// Get the root section writer
@@ -273,31 +900,124 @@ impl Config {
     /// ```
     /// use librustconfig::config::Config;
     ///
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// let root_writer = cfg.root();
     /// root_writer.write_string("interface", "eno1");
     /// ```
-    pub fn root(&self) -> OptionWriter {
+    pub fn root(&mut self) -> OptionWriter<'_> {
+        cache::invalidate(&self.generation);
         return OptionWriter::new(self.root_element);
     }
+
+    /// Get writer of existing setting at path.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("ports = [ 80 ];").is_ok() {
+    ///     assert!(cfg.writer("ports").unwrap().append_int32(443).is_some());
+    ///     assert_eq!(cfg.value("ports.[1]").unwrap().as_int32(), Some(443));
+    /// }
+    /// ```
+    pub fn writer<S>(&mut self, path : S) -> Option<OptionWriter<'_>>
+        where S: AsRef<str> {
+        let option = self.lookup(path.as_ref())?;
+        cache::invalidate(&self.generation);
+        Some(OptionWriter::new(Some(option)))
+    }
 }
 
+// Settings are owned by config and not shared with other configs. Readers
+// and writers, which share its read tracker, borrow the config, so nothing
+// referring to it stays behind when it is moved to another thread.
+unsafe impl Send for Config {}
+
 /// Destructor.
 /// Clear config and delete all allocated memory data.
 impl Drop for Config {
     fn drop (&mut self) {
+        self.thaw();
         unsafe { 
             raw::config_destroy(&mut self.config); 
         }
     }
 }
 
-impl OptionWriter {
+impl<'a> ScopedConfig<'a> {
+
+    /// Return path of view group.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let cfg = Config::new();
+    /// assert_eq!(cfg.scoped("server").prefix(), "server");
+    /// ```
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Read value from path relative to view group.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let cfg = Config::new();
+    /// match cfg.scoped("server").value("port") {
+    ///     Some(val) => { /* ... */ },
+    ///     None => { /* ... */ }
+    /// }
+    /// ```
+    pub fn value<S>(&self, path : S) -> Option<OptionReader<'_>>
+        where S: AsRef<str> {
+        self.config.value(crate::path::join(&self.prefix, path.as_ref()))
+    }
+
+    /// Create view nested in this view.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let cfg = Config::new();
+    /// let tls = cfg.scoped("server").scoped("tls");
+    /// assert_eq!(tls.prefix(), "server.tls");
+    /// ```
+    pub fn scoped<S>(&self, path : S) -> ScopedConfig<'a>
+        where S: AsRef<str> {
+        ScopedConfig {
+            config : self.config,
+            prefix : crate::path::join(&self.prefix, path.as_ref())
+        }
+    }
+
+    /// Copy view group into new independent config.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// cfg.create_section("server");
+    /// assert!(cfg.scoped("server").extract().is_some());
+    /// ```
+    pub fn extract(&self) -> Option<Config> {
+        self.config.extract(self.prefix.as_str())
+    }
+}
+
+impl<'a> OptionWriter<'a> {
     
     // Constructor.
-    fn new(elem : Option<*mut raw::config_setting_t>) -> OptionWriter {
+    pub(crate) fn new(elem : Option<*mut raw::config_setting_t>)
+        -> OptionWriter<'a> {
         OptionWriter {
-            element : elem
+            element : elem,
+            marker : PhantomData
         }
     }
     
@@ -307,7 +1027,7 @@ impl OptionWriter {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// match cfg.create_section("group") {
     ///     Some(group) => {
     ///         /* ... */
@@ -317,10 +1037,13 @@ impl OptionWriter {
     ///     },
     ///     None => { /* ... */ }
     /// }
-    pub fn delete(&self) -> Result<()> {
+    pub fn delete(self) -> Result<()> {
         if self.element.is_none() {
             return Err(Errors::ElementNotExists)
         }
+        if !freeze::writable(self.element.unwrap()) {
+            return Err(Errors::ConfigFrozen)
+        }
 
         if OptionReader::new(self.element).is_section().unwrap() {
             let result = {
@@ -330,7 +1053,8 @@ impl OptionWriter {
                     return Err(Errors::DeleteError);
                 }
 
-                let parent = OptionReader::new(self.element).parent();
+                let option = OptionReader::new(self.element);
+                let parent = option.parent();
                 if parent.is_none() {
                     return Err(Errors::DeleteError);
                 }
@@ -345,7 +1069,8 @@ impl OptionWriter {
             }
         } else {
             let result = {
-                let parent = OptionReader::new(self.element).parent();
+                let option = OptionReader::new(self.element);
+                let parent = option.parent();
                 let index = unsafe { 
                     raw::config_setting_index(self.element.unwrap())
                 };
@@ -365,36 +1090,67 @@ impl OptionWriter {
         }
     }
 
+    // Add child setting of type. Reason of failure is found before calling
+    // libconfig, which only reports that nothing was added.
+    pub(crate) fn add(&self, name : &str, value_type : c_short)
+        -> Result<*mut raw::config_setting_t> {
+        let parent = self.element.ok_or(Errors::ElementNotExists)?;
+        if !freeze::writable(parent) {
+            return Err(Errors::ConfigFrozen);
+        }
+
+        match raw::config_setting_type(parent) as c_short {
+            raw::CONFIG_TYPE_GROUP => {
+                if name.contains('\0') {
+                    return Err(Errors::InvalidString);
+                }
+                if !crate::path::valid_name(name) {
+                    return Err(Errors::InvalidName);
+                }
+                if crate::path::child(parent, name).is_some() {
+                    return Err(Errors::DuplicateName);
+                }
+            },
+            raw::CONFIG_TYPE_ARRAY => {
+                // Array elements are scalars of one type.
+                let first = unsafe { raw::config_setting_get_elem(parent, 0) };
+                let scalar = !matches!(value_type, raw::CONFIG_TYPE_GROUP |
+                    raw::CONFIG_TYPE_ARRAY | raw::CONFIG_TYPE_LIST);
+                if !scalar || (!first.is_null() &&
+                    raw::config_setting_type(first) as c_short != value_type) {
+                    return Err(Errors::TypeMismatch);
+                }
+            },
+            raw::CONFIG_TYPE_LIST => {},
+            _ => { return Err(Errors::TypeMismatch) }
+        }
+
+        let option = key::with_c_str(name, |name| unsafe {
+            raw::config_setting_add(parent, name.as_ptr(), value_type.into())
+        }).ok_or(Errors::InvalidString)?;
+        if option.is_null() {
+            Err(Errors::WriteError)
+        } else {
+            Ok(option)
+        }
+    }
+
     /// Create new group section.
     /// 
     /// # Examples
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// match cfg.create_section("root.group") {
     ///     Some(s) => { /* ... */ },
     ///     None => { /* ... */ }
     /// }
     /// ```
-    pub fn create_section<S>(&self, path : S) -> Option<OptionWriter> 
-        where S: Into<String> {
-            
-        if self.element.is_none() {
-            return None
-        }
-        
-        let option = unsafe {
-            raw::config_setting_add(self.element.unwrap(), 
-                CString::new(path.into()).unwrap().as_ptr(), 
-                raw::CONFIG_TYPE_GROUP as i32)
-        };
-        
-        if option.is_null() {
-            None
-        } else {
-            Some(OptionWriter::new(Some(option)))
-        }
+    pub fn create_section<S>(&mut self, path : S) -> Option<OptionWriter<'_>>
+        where S: AsRef<str> {
+        self.add(path.as_ref(), raw::CONFIG_TYPE_GROUP).ok()
+            .map(|option| OptionWriter::new(Some(option)))
     }
     
     /// Create new array group section.
@@ -403,31 +1159,17 @@ impl OptionWriter {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
-    /// let group = cfg.create_section("group").unwrap();
+    /// let mut cfg = Config::new();
+    /// let mut group = cfg.create_section("group").unwrap();
     /// match group.create_array("array") {
     ///     Some(s) => { /* ... */ },
     ///     None => { /* ... */ }
     /// }
     /// ```
-    pub fn create_array<S>(&self, path : S) -> Option<CollectionWriter> 
-        where S: Into<String> {
-            
-        if self.element.is_none() {
-            return None
-        }
-        
-        let option = unsafe {
-            raw::config_setting_add(self.element.unwrap(), 
-                CString::new(path.into()).unwrap().as_ptr(), 
-                raw::CONFIG_TYPE_ARRAY as i32)
-        };
-        
-        if option.is_null() {
-            None
-        } else {
-            Some(CollectionWriter::new(Some(option)))
-        }
+    pub fn create_array<S>(&mut self, path : S) -> Option<CollectionWriter<'_>>
+        where S: AsRef<str> {
+        self.add(path.as_ref(), raw::CONFIG_TYPE_ARRAY).ok()
+            .map(|option| CollectionWriter::new(Some(option)))
     }
 
     /// Create new list group section.
@@ -436,73 +1178,43 @@ impl OptionWriter {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
-    /// let group = cfg.create_section("group").unwrap();
+    /// let mut cfg = Config::new();
+    /// let mut group = cfg.create_section("group").unwrap();
     /// match group.create_list("root.list") {
     ///     Some(s) => { /* ... */ },
     ///     None => { /* ... */ }
     /// }
     /// ```
-    pub fn create_list<S>(&self, path : S) -> Option<CollectionWriter> 
-        where S: Into<String> {
-            
-        if self.element.is_none() {
-            return None
-        }
-        
-        let option = unsafe {
-            raw::config_setting_add(self.element.unwrap(), 
-                CString::new(path.into()).unwrap().as_ptr(), 
-                raw::CONFIG_TYPE_LIST as i32)
-        };
-        
-        if option.is_null() {
-            None
-        } else {
-            Some(CollectionWriter::new(Some(option)))
-        }
+    pub fn create_list<S>(&mut self, path : S) -> Option<CollectionWriter<'_>>
+        where S: AsRef<str> {
+        self.add(path.as_ref(), raw::CONFIG_TYPE_LIST).ok()
+            .map(|option| CollectionWriter::new(Some(option)))
     }
 
     /// Add new integer value to current group.
+    /// Returns DuplicateName if group already has setting with the name,
+    /// InvalidName if name is not valid setting name and TypeMismatch if
+    /// current option can't hold integer settings.
     /// 
     /// # Example
     /// ```
-    /// use librustconfig::config::Config;
+    /// use librustconfig::config::{Config, Errors};
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// match cfg.create_section("section") {
     ///     Some(s) => { 
-    ///         s.write_int32("ival", 321); 
+    ///         assert!(s.write_int32("ival", 321).is_ok());
+    ///         assert_eq!(s.write_int32("ival", 123).err(),
+    ///             Some(Errors::DuplicateName));
     ///     },
     ///     None => { /* ... */ }
     /// }
     /// ```
     pub fn write_int32<S>(&self, name : S, value : i32) -> 
-        Option<OptionWriter> where S: Into<String> {
-            
-        if self.element.is_none() {
-            return None
-        };
-        
-        let option = unsafe {
-            raw::config_setting_add(self.element.unwrap(),
-                CString::new(name.into()).unwrap().as_ptr(),
-                raw::CONFIG_TYPE_INT as i32)
-        };
-        
-        if option.is_null() {
-            None
-        } else {
-            let result = unsafe {
-                raw::config_setting_set_int(option, value)  
-            };
-            
-            if result == raw::CONFIG_TRUE {
-                Some(*self)
-            } else {
-                None
-            }
-        }
+        Result<&OptionWriter<'a>> where S: AsRef<str> {
+        let option = self.add(name.as_ref(), raw::CONFIG_TYPE_INT)?;
+        let result = unsafe { raw::config_setting_set_int(option, value) };
+        self.written(result)
     }
 
     /// Add new int64 value to current group.
@@ -511,40 +1223,19 @@ impl OptionWriter {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// match cfg.create_section("section") {
     ///     Some(s) => { 
-    ///         s.write_int64("ival", 321000); 
+    ///         assert!(s.write_int64("ival", 321000).is_ok());
     ///     },
     ///     None => { /* ... */ }
     /// }
     /// ```
     pub fn write_int64<S>(&self, name : S, value : i64) -> 
-        Option<OptionWriter> where S: Into<String> {
-            
-        if self.element.is_none() {
-            return None
-        };
-        
-        let option = unsafe {
-            raw::config_setting_add(self.element.unwrap(),
-                CString::new(name.into()).unwrap().as_ptr(),
-                raw::CONFIG_TYPE_INT64 as i32)
-        };
-        
-        if option.is_null() {
-            None
-        } else {
-            let result = unsafe {
-                raw::config_setting_set_int64(option, value)  
-            };
-            
-            if result == raw::CONFIG_TRUE {
-                Some(*self)
-            } else {
-                None
-            }
-        }
+        Result<&OptionWriter<'a>> where S: AsRef<str> {
+        let option = self.add(name.as_ref(), raw::CONFIG_TYPE_INT64)?;
+        let result = unsafe { raw::config_setting_set_int64(option, value) };
+        self.written(result)
     }
 
     /// Add new float value to current group.
@@ -553,40 +1244,19 @@ impl OptionWriter {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// match cfg.create_section("section") {
     ///     Some(s) => { 
-    ///         s.write_float64("ival", 321.001); 
+    ///         assert!(s.write_float64("ival", 321.001).is_ok());
     ///     },
     ///     None => { /* ... */ }
     /// }
     /// ```
     pub fn write_float64<S>(&self, name : S, value : f64) -> 
-        Option<OptionWriter> where S: Into<String> {
-            
-        if self.element.is_none() {
-            return None
-        };
-        
-        let option = unsafe {
-            raw::config_setting_add(self.element.unwrap(),
-                CString::new(name.into()).unwrap().as_ptr(),
-                raw::CONFIG_TYPE_FLOAT as i32)
-        };
-        
-        if option.is_null() {
-            None
-        } else {
-            let result = unsafe {
-                raw::config_setting_set_float(option, value)  
-            };
-            
-            if result == raw::CONFIG_TRUE {
-                Some(*self)
-            } else {
-                None
-            }
-        }
+        Result<&OptionWriter<'a>> where S: AsRef<str> {
+        let option = self.add(name.as_ref(), raw::CONFIG_TYPE_FLOAT)?;
+        let result = unsafe { raw::config_setting_set_float(option, value) };
+        self.written(result)
     }
 
     /// Add new boolean value to current group.
@@ -595,125 +1265,97 @@ impl OptionWriter {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// match cfg.create_section("section") {
     ///     Some(s) => { 
-    ///         s.write_bool("ival", false); 
+    ///         assert!(s.write_bool("ival", false).is_ok());
     ///     },
     ///     None => { /* ... */ }
     /// }
     /// ```
     pub fn write_bool<S>(&self, name : S, value : bool) -> 
-        Option<OptionWriter> where S: Into<String> {
-            
-        if self.element.is_none() {
-            return None
-        };
-        
-        let option = unsafe {
-            raw::config_setting_add(self.element.unwrap(),
-                CString::new(name.into()).unwrap().as_ptr(),
-                raw::CONFIG_TYPE_BOOL as i32)
-        };
-        
-        if option.is_null() {
-            None
-        } else {
-            let val = {
-                match value {
-                    true => { raw::CONFIG_TRUE },
-                    false => { raw::CONFIG_FALSE }
-                }
-            };
-            let result = unsafe {
-                raw::config_setting_set_bool(option, val)  
-            };
-            
-            if result == raw::CONFIG_TRUE {
-                Some(*self)
-            } else {
-                None
+        Result<&OptionWriter<'a>> where S: AsRef<str> {
+        let option = self.add(name.as_ref(), raw::CONFIG_TYPE_BOOL)?;
+        let val = {
+            match value {
+                true => { raw::CONFIG_TRUE },
+                false => { raw::CONFIG_FALSE }
             }
-        }
+        };
+        let result = unsafe { raw::config_setting_set_bool(option, val) };
+        self.written(result)
     }
 
-    /// Add new string value to current group.
+    /// Add new string value to current group. Returns InvalidString if
+    /// value contains NUL character.
     /// 
     /// # Example
     /// ```
-    /// use librustconfig::config::Config;
+    /// use librustconfig::config::{Config, Errors};
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// match cfg.create_section("section") {
     ///     Some(s) => { 
-    ///         s.write_string("ival", "test string"); 
+    ///         assert!(s.write_string("ival", "test string").is_ok());
+    ///         assert_eq!(s.write_string("bad", "a\0b").err(),
+    ///             Some(Errors::InvalidString));
     ///     },
     ///     None => { /* ... */ }
     /// }
     /// ```
-    pub fn write_string<S>(&self, name : S, value : S) -> 
-        Option<OptionWriter> where S: Into<String> {
-            
-        if self.element.is_none() {
-            return None
-        };
-        
-        let option = unsafe {
-            raw::config_setting_add(self.element.unwrap(),
-                CString::new(name.into()).unwrap().as_ptr(),
-                raw::CONFIG_TYPE_STRING as i32)
+    pub fn write_string<S, V>(&self, name : S, value : V) -> 
+        Result<&OptionWriter<'a>> where S: AsRef<str>, V: AsRef<str> {
+        let value = CString::new(value.as_ref())
+            .map_err(|_| Errors::InvalidString)?;
+        let option = self.add(name.as_ref(), raw::CONFIG_TYPE_STRING)?;
+        let result = unsafe {
+            raw::config_setting_set_string(option, value.as_ptr())
         };
-        
-        if option.is_null() {
-            None
+        self.written(result)
+    }
+
+    // Return writer of current option if value was set.
+    fn written(&self, result : i32) -> Result<&OptionWriter<'a>> {
+        if result == raw::CONFIG_TRUE {
+            Ok(self)
         } else {
-            let result = unsafe {
-                raw::config_setting_set_string(option, 
-                    CString::new(value.into()).unwrap().as_ptr())  
-            };
-            
-            if result == raw::CONFIG_TRUE {
-                Some(*self)
-            } else {
-                None
-            }
+            Err(Errors::WriteError)
         }
     }
 }
 
-impl CollectionWriter {
+impl<'a> CollectionWriter<'a> {
 
     // Constructor.
-    fn new(elem : Option<*mut raw::config_setting_t>) -> CollectionWriter {
+    fn new(elem : Option<*mut raw::config_setting_t>) -> CollectionWriter<'a> {
         CollectionWriter {
-            element : elem
+            element : elem,
+            marker : PhantomData
         }
     }
 
-    /// Add new integer value to current collection.
+    /// Add new integer value to current collection. Returns TypeMismatch if
+    /// array already holds values of another type.
     /// 
     /// # Example
     /// ```
-    /// use librustconfig::config::Config;
+    /// use librustconfig::config::{Config, Errors};
     /// 
-    /// let cfg = Config::new();
-    /// let group = cfg.create_section("group").unwrap();
+    /// let mut cfg = Config::new();
+    /// let mut group = cfg.create_section("group").unwrap();
     /// match group.create_array("int32_collection") {
     ///     Some(s) => { 
-    ///         s.write_int32(321); 
-    ///         s.write_int32(-12);
+    ///         assert!(s.write_int32(321).and_then(|s| s.write_int32(-12))
+    ///             .is_ok());
+    ///         assert_eq!(s.write_string("a").err(),
+    ///             Some(Errors::TypeMismatch));
     ///     },
     ///     None => { /* ... */ }
     /// }
     /// ```
-    pub fn write_int32(&self, value : i32) -> Option<CollectionWriter> {
-        let writer = OptionWriter::new(self.element).write_int32("", value);
-
-        if writer.is_none() {
-            return None
-        }
-
-        Some(CollectionWriter::new(Some(writer.unwrap().element.unwrap())))
+    pub fn write_int32(&self, value : i32) -> Result<&CollectionWriter<'a>> {
+        OptionWriter::new(self.element).write_int32("", value)?;
+        Ok(self)
     }
 
     /// Add new int64 value to current collection.
@@ -722,23 +1364,18 @@ impl CollectionWriter {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
-    /// let group = cfg.create_section("group").unwrap();
+    /// let mut cfg = Config::new();
+    /// let mut group = cfg.create_section("group").unwrap();
     /// match group.create_array("int64_collection") {
     ///     Some(s) => { 
-    ///         s.write_int64(321000); 
+    ///         assert!(s.write_int64(321000).is_ok());
     ///     },
     ///     None => { /* ... */ }
     /// }
     /// ```
-    pub fn write_int64(&self, value : i64) -> Option<CollectionWriter> {
-        let writer = OptionWriter::new(self.element).write_int64("", value);
-
-        if writer.is_none() {
-            return None
-        }
-
-        Some(CollectionWriter::new(Some(writer.unwrap().element.unwrap())))
+    pub fn write_int64(&self, value : i64) -> Result<&CollectionWriter<'a>> {
+        OptionWriter::new(self.element).write_int64("", value)?;
+        Ok(self)
     }
 
     /// Add new float value to current collection.
@@ -747,23 +1384,18 @@ impl CollectionWriter {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
-    /// let group = cfg.create_section("group").unwrap();
+    /// let mut cfg = Config::new();
+    /// let mut group = cfg.create_section("group").unwrap();
     /// match group.create_array("float_collection") {
     ///     Some(s) => { 
-    ///         s.write_float64(321.001); 
+    ///         assert!(s.write_float64(321.001).is_ok());
     ///     },
     ///     None => { /* ... */ }
     /// }
     /// ```
-    pub fn write_float64(&self, value : f64) -> Option<CollectionWriter> {
-        let writer = OptionWriter::new(self.element).write_float64("", value);
-
-        if writer.is_none() {
-            return None
-        }
-
-        Some(CollectionWriter::new(Some(writer.unwrap().element.unwrap())))
+    pub fn write_float64(&self, value : f64) -> Result<&CollectionWriter<'a>> {
+        OptionWriter::new(self.element).write_float64("", value)?;
+        Ok(self)
     }
 
     /// Add new boolean value to current collection.
@@ -772,23 +1404,18 @@ impl CollectionWriter {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
-    /// let group = cfg.create_section("group").unwrap();
+    /// let mut cfg = Config::new();
+    /// let mut group = cfg.create_section("group").unwrap();
     /// match group.create_array("bool_collection") {
     ///     Some(s) => { 
-    ///         s.write_bool(false); 
+    ///         assert!(s.write_bool(false).is_ok());
     ///     },
     ///     None => { /* ... */ }
     /// }
     /// ```
-    pub fn write_bool(&self, value : bool) -> Option<CollectionWriter> {
-        let writer = OptionWriter::new(self.element).write_bool("", value);
-
-        if writer.is_none() {
-            return None
-        }
-
-        Some(CollectionWriter::new(Some(writer.unwrap().element.unwrap())))
+    pub fn write_bool(&self, value : bool) -> Result<&CollectionWriter<'a>> {
+        OptionWriter::new(self.element).write_bool("", value)?;
+        Ok(self)
     }
 
     /// Add new string value to current collection.
@@ -797,64 +1424,43 @@ impl CollectionWriter {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
-    /// let group = cfg.create_section("group").unwrap();
+    /// let mut cfg = Config::new();
+    /// let mut group = cfg.create_section("group").unwrap();
     /// match group.create_array("str_collection") {
     ///     Some(s) => { 
-    ///         s.write_string("test string"); 
+    ///         assert!(s.write_string("test string").is_ok());
     ///     },
     ///     None => { /* ... */ }
     /// }
     /// ```
-    pub fn write_string<S>(&self, value : S) -> Option<CollectionWriter>
-        where S: Into<String> {
-        let writer = OptionWriter::new(self.element).write_string("", 
-            &value.into());
-
-        if writer.is_none() {
-            return None
-        }
-
-        Some(CollectionWriter::new(Some(writer.unwrap().element.unwrap())))
+    pub fn write_string<S>(&self, value : S) -> Result<&CollectionWriter<'a>>
+        where S: AsRef<str> {
+        OptionWriter::new(self.element).write_string("", value)?;
+        Ok(self)
     }
 }
 
-impl OptionReader {
+impl<'a> OptionReader<'a> {
     
     // Constructor
-    fn new(elem : Option<*mut raw::config_setting_t>) -> OptionReader {
+    pub(crate) fn new(elem : Option<*mut raw::config_setting_t>)
+        -> OptionReader<'a> {
         OptionReader {
-            element : elem
+            element : elem,
+            tracker : None,
+            resolved : false,
+            children : ChildReaders::default(),
+            marker : PhantomData
         }
     }
 
-    /// Delete current config element.
-    /// 
-    /// # Example
-    /// ```
-    /// use librustconfig::config::Config;
-    /// 
-    /// let cfg = Config::new();
-    /// match cfg.create_section("group") {
-    ///     Some(group) => {
-    ///         /* ... */
-    ///         if !group.delete().is_ok() {
-    ///             /* ... */
-    ///         }
-    ///     },
-    ///     None => { /* ... */ }
-    /// }
-    pub fn delete(&self) -> Result<()> {
-        OptionWriter::new(self.element).delete()
-    }
-    
     /// Return true if element is section group.
     /// 
     /// # Example
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// if cfg.create_section("root").is_none() {
     ///     panic!("Can't create root section!");
     /// }
@@ -878,7 +1484,7 @@ impl OptionReader {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// let root = cfg.create_section("root");
     /// if root.is_none() {
     ///     panic!("Can't create root section!");
@@ -907,7 +1513,7 @@ impl OptionReader {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// let group = cfg.create_section("group");
     /// if group.is_none() {
     ///     panic!("Can't create group section!");
@@ -936,13 +1542,13 @@ impl OptionReader {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
-    /// let group = cfg.create_section("group");
+    /// let mut cfg = Config::new();
+    /// let mut group = cfg.create_section("group");
     /// if group.is_none() {
     ///     panic!("Can't create a group section!");
     /// }
     /// /* ... */
-    /// let section = group.unwrap().create_section("section");
+    /// let section = group.as_mut().unwrap().create_section("section");
     /// if section.is_none() {
     ///     panic!("Can't create section!");
     /// }
@@ -952,7 +1558,7 @@ impl OptionReader {
     ///     None => { /* ... */ }
     /// }
     /// ```
-    pub fn parent(&self) -> Option<OptionReader> {
+    pub fn parent(&self) -> Option<OptionReader<'_>> {
         if self.element.is_none() {
             return None
         }
@@ -962,23 +1568,25 @@ impl OptionReader {
         if result.is_null() {
             None
         } else {
-            Some(OptionReader::new(Some(result)))
+            Some(self.derive(Some(result)))
         }
     }
     
-    /// Return option value type.
+    /// Return option value type, None only if the option doesn't exist.
+    /// Type is read from the setting itself without calling into libconfig,
+    /// so checking it before every typed read is cheap.
     /// 
     /// # Example
     /// ```
     /// use librustconfig::config::{Config, OptionType};
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// let group = cfg.create_section("group");
     /// if group.is_none() {
     ///     panic!("Can't create group section!");
     /// }
     /// /* ... */
-    /// if group.unwrap().write_int32("value", 1345).is_none() {
+    /// if group.unwrap().write_int32("value", 1345).is_err() {
     ///     panic!("Can't write int32 value to config!");
     /// }
     /// /* ... */
@@ -987,7 +1595,10 @@ impl OptionReader {
     ///     OptionType::Int64Type => { /* ... */ },
     ///     OptionType::FloatType => { /* ... */ },
     ///     OptionType::StringType => { /* ... */ },
-    ///     OptionType::BooleanType => { /* ... */ }
+    ///     OptionType::BooleanType => { /* ... */ },
+    ///     OptionType::GroupType => { /* ... */ },
+    ///     OptionType::ArrayType | OptionType::ListType => { /* ... */ },
+    ///     OptionType::NoneType => { /* ... */ }
     /// }
     /// ```
     pub fn value_type(&self) -> Option<OptionType> {
@@ -1002,7 +1613,10 @@ impl OptionReader {
             raw::CONFIG_TYPE_FLOAT => { Some(OptionType::FloatType) },
             raw::CONFIG_TYPE_STRING => { Some(OptionType::StringType) },
             raw::CONFIG_TYPE_BOOL => { Some(OptionType::BooleanType) },
-            _ => { None }
+            raw::CONFIG_TYPE_GROUP => { Some(OptionType::GroupType) },
+            raw::CONFIG_TYPE_ARRAY => { Some(OptionType::ArrayType) },
+            raw::CONFIG_TYPE_LIST => { Some(OptionType::ListType) },
+            _ => { Some(OptionType::NoneType) }
         }
     }
     
@@ -1012,13 +1626,13 @@ impl OptionReader {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// let group = cfg.create_section("group");
     /// if group.is_none() {
     ///     panic!("Can't create group section!");
     /// }
     /// /* ... */
-    /// if group.unwrap().write_string("value", "test string").is_none() {
+    /// if group.unwrap().write_string("value", "test string").is_err() {
     ///     panic!("Can't write string value to config!");
     /// }
     /// /* ... */
@@ -1027,31 +1641,212 @@ impl OptionReader {
     ///     None => { /* ... */ }
     /// } 
     /// ``` 
-    pub fn value<S>(&self, path : S) -> Option<OptionReader>
-        where S: Into<String> {
+    pub fn value<S>(&self, path : S) -> Option<OptionReader<'_>>
+        where S: AsRef<str> {
         
         if self.element.is_none() {
             return None
         }
         
-        let option = unsafe { raw::config_setting_lookup(
-            self.element.unwrap(), CString::new(path.into())
-                .unwrap().as_ptr())
-        };
+        let option = key::with_c_str(path.as_ref(), |path| unsafe {
+            raw::config_setting_lookup(self.element.unwrap(), path.as_ptr())
+        }).unwrap_or(ptr::null_mut());
          
         if option.is_null() {
             None          
         } else {
-            Some(OptionReader::new(Some(option)))
+            Some(self.derive(Some(option)))
         }  
     }
     
-    pub fn as_array(&self) -> CollectionReaderIterator {
-        CollectionReaderIterator::new(self.element)
+    /// Return name of current option, None for collection elements.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { port = 80; };").is_ok() {
+    ///     let port = cfg.value("server.port").unwrap();
+    ///     assert_eq!(port.name(), Some("port"));
+    /// }
+    /// ```
+    pub fn name(&self) -> Option<&str> {
+        let name = raw::config_setting_name(self.element?);
+        if name.is_null() {
+            return None
+        }
+        unsafe { CStr::from_ptr(name) }.to_str().ok()
+    }
+
+    /// Return position of current option in its parent group or collection,
+    /// None for root.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("host = \"a\"; ports = [ 80, 443 ];").is_ok() {
+    ///     assert_eq!(cfg.value("ports").unwrap().index(), Some(1));
+    ///     assert_eq!(cfg.value("ports.[1]").unwrap().index(), Some(1));
+    ///     assert_eq!(cfg.value("").unwrap().index(), None);
+    /// }
+    /// ```
+    pub fn index(&self) -> Option<usize> {
+        let index = unsafe { raw::config_setting_index(self.element?) };
+        if index < 0 {
+            return None
+        }
+        Some(index as usize)
+    }
+
+    /// Return full path of current option, collection elements have `[N]`
+    /// segments. Root has empty path.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("servers = ( { port = 80; } );").is_ok() {
+    ///     let port = cfg.value("servers.[0].port").unwrap();
+    ///     assert_eq!(port.path(), "servers.[0].port");
+    ///     assert_eq!(port.parent().unwrap().path(), "servers.[0]");
+    /// }
+    /// ```
+    pub fn path(&self) -> String {
+        self.element.map(crate::path::setting_path).unwrap_or_default()
+    }
+
+    /// Iterate over direct children of group, array or list. Elements are
+    /// visited in document order, settings added later after loaded ones,
+    /// without allocation.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { host = \"a\"; port = 80; };")
+    ///     .is_ok() {
+    ///     assert_eq!(cfg.value("server").unwrap().children().count(), 2);
+    /// }
+    /// ```
+    pub fn children(&self) -> CollectionReaderIterator<'_> {
+        CollectionReaderIterator::new(self.element, self.tracker.clone(),
+            self.resolved)
+    }
+
+    /// Return number of children of group, array or list, None for scalar
+    /// or missing option.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("ports = [ 80, 443 ];").is_ok() {
+    ///     assert_eq!(cfg.value("ports").unwrap().len(), Some(2));
+    /// }
+    /// ```
+    pub fn len(&self) -> Option<usize> {
+        let element = self.element?;
+        if raw::config_setting_is_aggregate(element) != raw::CONFIG_TRUE {
+            return None;
+        }
+        Some(unsafe { raw::config_setting_length(element) } as usize)
+    }
+
+    /// Return true if group, array or list has no children, None for
+    /// scalar or missing option.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("ports = [ ];").is_ok() {
+    ///     assert_eq!(cfg.value("ports").unwrap().is_empty(), Some(true));
+    /// }
+    /// ```
+    pub fn is_empty(&self) -> Option<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Return reader of collection element or group member by index, None
+    /// if index is out of range.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("ports = [ 80, 443 ];").is_ok() {
+    ///     let ports = cfg.value("ports").unwrap();
+    ///     assert_eq!(ports.at(1).and_then(|port| port.as_int32()),
+    ///         Some(443));
+    /// }
+    /// ```
+    pub fn at(&self, index : usize) -> Option<OptionReader<'_>> {
+        self.get(index)
+    }
+
+    /// Iterate over members of group with their names. Names are borrowed
+    /// from config and are not copied. Iterator is empty if option is not a
+    /// group.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { host = \"a\"; port = 80; };")
+    ///     .is_ok() {
+    ///     let server = cfg.value("server").unwrap();
+    ///     for (name, option) in server.entries() {
+    ///         println!("{} = {:?}", name, option.value_type());
+    ///     }
+    /// }
+    /// ```
+    pub fn entries(&self) -> EntriesIterator<'_> {
+        let group = self.element.filter(|element| {
+            raw::config_setting_is_group(*element) == raw::CONFIG_TRUE
+        });
+        EntriesIterator {
+            elements : CollectionReaderIterator::new(group,
+                self.tracker.clone(), self.resolved)
+        }
+    }
+
+    /// Iterate over names of group members in document order. Iterator is
+    /// empty if option is not a group.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { host = \"a\"; port = 80; };")
+    ///     .is_ok() {
+    ///     let server = cfg.value("server").unwrap();
+    ///     assert_eq!(server.keys().collect::<Vec<_>>(), ["host", "port"]);
+    /// }
+    /// ```
+    pub fn keys(&self) -> impl Iterator<Item = &str> + '_ {
+        self.entries().map(|(name, _)| name)
+    }
+
+    pub fn as_array(&self) -> CollectionReaderIterator<'_> {
+        self.mark_read();
+        CollectionReaderIterator::new(self.element, self.tracker.clone(),
+            self.resolved)
     }
 
-    pub fn as_list(&self) -> CollectionReaderIterator {
-        CollectionReaderIterator::new(self.element)
+    pub fn as_list(&self) -> CollectionReaderIterator<'_> {
+        self.mark_read();
+        CollectionReaderIterator::new(self.element, self.tracker.clone(),
+            self.resolved)
     }
 
     /// Present option value as i32.
@@ -1060,13 +1855,13 @@ impl OptionReader {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// let group = cfg.create_section("group");
     /// if group.is_none() {
     ///     panic!("Can't create group section!");
     /// }
     /// /* ... */
-    /// if group.unwrap().write_int32("value", 131).is_none() {
+    /// if group.unwrap().write_int32("value", 131).is_err() {
     ///     panic!("Can't write int32 value!");
     /// }
     /// /* ... */
@@ -1079,6 +1874,7 @@ impl OptionReader {
         if self.element.is_none() {
             return None
         }
+        self.mark_read();
         
         let result = unsafe { 
             raw::config_setting_get_int(self.element.unwrap()) 
@@ -1092,13 +1888,13 @@ impl OptionReader {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// let group = cfg.create_section("group");
     /// if group.is_none() {
     ///     panic!("Can't create group section!");
     /// }
     /// /* ... */
-    /// if group.unwrap().write_int32("value", 143).is_none() {
+    /// if group.unwrap().write_int32("value", 143).is_err() {
     ///     panic!("Can't write int32 value!");
     /// }
     /// /* ... */
@@ -1117,13 +1913,13 @@ impl OptionReader {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// let group = cfg.create_section("group");
     /// if group.is_none() {
     ///     panic!("Can't create group section!");
     /// }
     /// /* ... */
-    /// if group.unwrap().write_int64("value", 120).is_none() {
+    /// if group.unwrap().write_int64("value", 120).is_err() {
     ///     panic!("Can't write int64 value!");
     /// }
     /// /* ... */
@@ -1136,6 +1932,7 @@ impl OptionReader {
         if self.element.is_none() {
             return None
         }
+        self.mark_read();
         
         let result = unsafe {
             raw::config_setting_get_int64(self.element.unwrap())
@@ -1149,13 +1946,13 @@ impl OptionReader {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// let group = cfg.create_section("group");
     /// if group.is_none() {
     ///     panic!("Can't create group section!");
     /// }
     /// /* ... */
-    /// if group.unwrap().write_int64("value", 145).is_none() {
+    /// if group.unwrap().write_int64("value", 145).is_err() {
     ///     panic!("Can't write int64 value!");
     /// }
     /// /* ... */
@@ -1174,13 +1971,13 @@ impl OptionReader {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// let group = cfg.create_section("group");
     /// if group.is_none() {
     ///     panic!("Can't create group section!");
     /// }
     /// /* ... */
-    /// if group.unwrap().write_float64("value", 1.00021).is_none() {
+    /// if group.unwrap().write_float64("value", 1.00021).is_err() {
     ///     panic!("Can't write float64 value!");
     /// }
     /// /* ... */
@@ -1193,6 +1990,7 @@ impl OptionReader {
         if self.element.is_none() {
             return None
         }
+        self.mark_read();
         
         let result = unsafe {
             raw::config_setting_get_float(self.element.unwrap())
@@ -1206,13 +2004,13 @@ impl OptionReader {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// let group = cfg.create_section("group");
     /// if group.is_none() {
     ///     panic!("Can't create group section!");
     /// }
     /// /* ... */
-    /// if group.unwrap().write_float64("value", 20.201).is_none() {
+    /// if group.unwrap().write_float64("value", 20.201).is_err() {
     ///     panic!("Can't write float64 value!");
     /// }
     /// /* ... */
@@ -1231,13 +2029,13 @@ impl OptionReader {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// let group = cfg.create_section("group");
     /// if group.is_none() {
     ///     panic!("Can't create group section!");
     /// }
     /// /* ... */
-    /// if group.unwrap().write_bool("value", true).is_none() {
+    /// if group.unwrap().write_bool("value", true).is_err() {
     ///     panic!("Can't write bool value!");
     /// }
     /// /* ... */
@@ -1250,6 +2048,7 @@ impl OptionReader {
         if self.element.is_none() {
             return None
         }
+        self.mark_read();
         
         let result = unsafe {
             raw::config_setting_get_bool(self.element.unwrap())
@@ -1263,13 +2062,13 @@ impl OptionReader {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// let group = cfg.create_section("group");
     /// if group.is_none() {
     ///     panic!("Can't create group section!");
     /// }
     /// /* ... */
-    /// if group.unwrap().write_bool("value", true).is_none() {
+    /// if group.unwrap().write_bool("value", true).is_err() {
     ///     panic!("Can't write value!");
     /// }
     /// /* ... */
@@ -1288,13 +2087,13 @@ impl OptionReader {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// let group = cfg.create_section("group");
     /// if group.is_none() {
     ///     panic!("Can't create group section!");
     /// }
     /// /* ... */
-    /// if group.unwrap().write_string("value", "string val").is_none() {
+    /// if group.unwrap().write_string("value", "string val").is_err() {
     ///     panic!("Can't write string value!");
     /// }
     /// /* ... */
@@ -1304,27 +2103,36 @@ impl OptionReader {
     /// }
     /// ```
     pub fn as_string(&self) -> Option<String> {
-        if self.element.is_none() {
-            return None
-        }
-        
-        let result = {
-            let str = unsafe {
-                raw::config_setting_get_string(self.element.unwrap())
-            };
+        self.as_str().map(|value| value.to_string())
+    }
 
-            if str.is_null() {
-                return None
-            } else { 
-                unsafe { CStr::from_ptr(raw::config_setting_get_string(
-                    self.element.unwrap())) }
-            }
-        };
+    /// Present current option value as string slice without copying.
+    /// Slice points into config and is valid until the setting is changed or
+    /// removed.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("name = \"server\";").is_ok() {
+    ///     let name = cfg.value("name").unwrap();
+    ///     assert_eq!(name.as_str(), Some("server"));
+    /// }
+    /// ```
+    pub fn as_str(&self) -> Option<&str> {
+        let element = self.element?;
+        self.mark_read();
 
-        if result.to_str().is_ok() {
-            Some(result.to_str().unwrap().to_string())
+        let str = unsafe { raw::config_setting_get_string(element) };
+        if str.is_null() {
+            return None
+        }
+        let value = unsafe { CStr::from_ptr(str) }.to_str().ok()?;
+        if self.resolved {
+            Some(crate::reference::unescape(value))
         } else {
-            None
+            Some(value)
         }
     }
     
@@ -1334,13 +2142,13 @@ impl OptionReader {
     /// ```
     /// use librustconfig::config::Config;
     /// 
-    /// let cfg = Config::new();
+    /// let mut cfg = Config::new();
     /// let group = cfg.create_section("group");
     /// if group.is_none() {
     ///     panic!("Can't create group section!");
     /// }
     /// /* ... */
-    /// if group.unwrap().write_string("value", "string val").is_none() {
+    /// if group.unwrap().write_string("value", "string val").is_err() {
     ///     panic!("Can't write seting value!");
     /// }
     /// /* ... */
@@ -1357,11 +2165,12 @@ impl OptionReader {
 
 }
 
-impl CollectionReaderIterator {
+impl<'a> CollectionReaderIterator<'a> {
 
     // Constructor.
-    fn new(elem : Option<*mut raw::config_setting_t>) 
-        -> CollectionReaderIterator {
+    fn new(elem : Option<*mut raw::config_setting_t>,
+        tracker : Option<Tracker>, resolved : bool)
+        -> CollectionReaderIterator<'a> {
         
         let collection_size = {
             match elem {
@@ -1374,17 +2183,20 @@ impl CollectionReaderIterator {
 
         CollectionReaderIterator {
             element : elem,
+            tracker,
+            resolved,
             pos : 0,
-            size : collection_size
+            size : collection_size,
+            marker : PhantomData
         }
     }
 
 }
 
-impl Iterator for CollectionReaderIterator {
-    type Item = OptionReader;
+impl<'a> Iterator for CollectionReaderIterator<'a> {
+    type Item = OptionReader<'a>;
 
-    fn next(&mut self) -> Option<OptionReader> {
+    fn next(&mut self) -> Option<OptionReader<'a>> {
         if (self.element.is_none()) || (self.pos >= self.size) {
             return None
         }
@@ -1398,7 +2210,115 @@ impl Iterator for CollectionReaderIterator {
         }
 
         self.pos += 1;
-        Some(OptionReader::new(Some(result)))
+        Some(OptionReader {
+            element : Some(result),
+            tracker : self.tracker.clone(),
+            resolved : self.resolved,
+            children : ChildReaders::default(),
+            marker : PhantomData
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = if self.element.is_none() {
+            0
+        } else {
+            (self.size - self.pos).max(0) as usize
+        };
+        (remaining, Some(remaining))
+    }
+
+}
+
+impl<'a> Iterator for EntriesIterator<'a> {
+    type Item = (&'a str, OptionReader<'a>);
+
+    fn next(&mut self) -> Option<(&'a str, OptionReader<'a>)> {
+        for option in self.elements.by_ref() {
+            let name = raw::config_setting_name(option.element?);
+            if name.is_null() {
+                continue;
+            }
+            if let Ok(name) = unsafe { CStr::from_ptr(name) }.to_str() {
+                return Some((name, option));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.elements.size_hint().1)
+    }
+}
+
+impl<'a> Iterator for NamedChildrenIterator<'a> {
+    type Item = (Option<String>, OptionReader<'a>);
+
+    fn next(&mut self) -> Option<(Option<String>, OptionReader<'a>)> {
+        let option = self.elements.next()?;
+        let name = raw::config_setting_name(option.element?);
+        let name = if name.is_null() {
+            None
+        } else {
+            Some(unsafe { CStr::from_ptr(name) }.to_string_lossy()
+                .into_owned())
+        };
+        Some((name, option))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.elements.size_hint()
+    }
+}
+
+/// Iterate over root settings with their names.
+///
+/// # Example
+/// ```
+/// use librustconfig::config::Config;
+///
+/// let mut cfg = Config::new();
+/// if cfg.load_from_string("host = \"a\"; port = 80;").is_ok() {
+///     for (name, option) in &cfg {
+///         println!("{:?} = {:?}", name, option.value_type());
+///     }
+/// }
+/// ```
+impl<'a> IntoIterator for &'a Config {
+    type Item = (Option<String>, OptionReader<'a>);
+    type IntoIter = NamedChildrenIterator<'a>;
+
+    fn into_iter(self) -> NamedChildrenIterator<'a> {
+        NamedChildrenIterator {
+            elements : CollectionReaderIterator::new(self.root_element,
+                self.tracker.clone(), self.resolve_references)
+        }
     }
+}
 
-}
\ No newline at end of file
+/// Iterate over children of group, array or list. Group members have
+/// names, collection elements have None.
+///
+/// # Example
+/// ```
+/// use librustconfig::config::Config;
+///
+/// let mut cfg = Config::new();
+/// if cfg.load_from_string("ports = [ 80, 443 ];").is_ok() {
+///     let ports = cfg.value("ports").unwrap();
+///     for (name, port) in &ports {
+///         assert!(name.is_none());
+///         println!("{:?}", port.as_int32());
+///     }
+/// }
+/// ```
+impl<'a> IntoIterator for &'a OptionReader<'_> {
+    type Item = (Option<String>, OptionReader<'a>);
+    type IntoIter = NamedChildrenIterator<'a>;
+
+    fn into_iter(self) -> NamedChildrenIterator<'a> {
+        NamedChildrenIterator {
+            elements : self.children()
+        }
+    }
+}
@@ -0,0 +1,343 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Deserialization of config settings into Rust types, `serde` feature.
+//!
+//! Groups are deserialized as maps and structs, arrays and lists as
+//! sequences and tuples. Enum unit variants are read from strings, other
+//! variants from groups with single member named after the variant.
+//! Missing `Option` fields are `None`.
+
+use crate::config::{CollectionReaderIterator, Config, OptionReader,
+    OptionType};
+use crate::path;
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer,
+    Visitor};
+use std::error;
+use std::fmt;
+
+/// Serialization or deserialization error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    /// Path of setting that failed, collection elements have `[N]`
+    /// segments.
+    pub path : String,
+    /// Error description.
+    pub message : String
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T>(message : T) -> Error where T: fmt::Display {
+        Error { path : String::new(), message : message.to_string() }
+    }
+}
+
+/// Deserializer of one setting.
+pub struct Deserializer<'a> {
+    option : OptionReader<'a>
+}
+
+impl<'a> Deserializer<'a> {
+
+    /// Create deserializer of setting.
+    pub fn new(option : OptionReader<'a>) -> Deserializer<'a> {
+        Deserializer { option }
+    }
+
+    // Create error for current setting.
+    fn error<S>(&self, message : S) -> Error where S: Into<String> {
+        Error {
+            path : self.option.element.map(path::setting_path)
+                .unwrap_or_default(),
+            message : message.into()
+        }
+    }
+
+    // Set path of error raised by visitor of current setting.
+    fn locate(&self, mut error : Error) -> Error {
+        if error.path.is_empty() {
+            error.path = self.option.element.map(path::setting_path)
+                .unwrap_or_default();
+        }
+        error
+    }
+
+    // Return string value of setting.
+    fn string(&self) -> Result<String, Error> {
+        self.option.as_string().ok_or_else(|| self.error("expected string"))
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor : V) -> Result<V::Value, Error>
+        where V: Visitor<'de> {
+        if self.option.element.is_none() {
+            return Err(self.error("setting does not exist"));
+        }
+        let result = match self.option.value_type() {
+            Some(OptionType::IntegerType) | Some(OptionType::Int64Type) => {
+                visitor.visit_i64(self.option.as_int64().unwrap_or_default())
+            },
+            Some(OptionType::FloatType) => {
+                visitor.visit_f64(self.option.as_float64()
+                    .unwrap_or_default())
+            },
+            Some(OptionType::BooleanType) => {
+                visitor.visit_bool(self.option.as_bool().unwrap_or_default())
+            },
+            Some(OptionType::StringType) => {
+                visitor.visit_string(self.string()?)
+            },
+            Some(OptionType::GroupType) => {
+                visitor.visit_map(GroupAccess {
+                    entries : self.option.entries()
+                        .map(|(name, option)| (name.to_string(), option))
+                        .collect::<Vec<_>>().into_iter(),
+                    value : None
+                })
+            },
+            Some(OptionType::ArrayType) | Some(OptionType::ListType) => {
+                visitor.visit_seq(CollectionAccess {
+                    elements : self.option.children()
+                })
+            },
+            Some(OptionType::NoneType) | None => { visitor.visit_unit() }
+        };
+        result.map_err(|error| self.locate(error))
+    }
+
+    fn deserialize_option<V>(self, visitor : V) -> Result<V::Value, Error>
+        where V: Visitor<'de> {
+        if self.option.element.is_none() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name : &'static str,
+        visitor : V) -> Result<V::Value, Error> where V: Visitor<'de> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(self, _name : &'static str,
+        _variants : &'static [&'static str], visitor : V)
+        -> Result<V::Value, Error> where V: Visitor<'de> {
+        if self.option.value_type() == Some(OptionType::StringType) {
+            let variant = self.string()?;
+            return visitor.visit_enum(variant.into_deserializer())
+                .map_err(|error : Error| self.locate(error));
+        }
+
+        let mut entries = self.option.entries();
+        let result = match (entries.next(), entries.next()) {
+            (Some((name, option)), None) => {
+                visitor.visit_enum(VariantAccess {
+                    name : name.to_string(),
+                    option
+                })
+            },
+            _ => {
+                return Err(self.error(
+                    "expected string or group with one member for enum"));
+            }
+        };
+        result.map_err(|error| self.locate(error))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str
+        string bytes byte_buf unit unit_struct seq tuple tuple_struct map
+        struct identifier ignored_any
+    }
+}
+
+/// Members of group.
+struct GroupAccess<'a> {
+    entries : std::vec::IntoIter<(String, OptionReader<'a>)>,
+    value : Option<OptionReader<'a>>
+}
+
+impl<'de> de::MapAccess<'de> for GroupAccess<'_> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed : K) -> Result<Option<K::Value>, Error>
+        where K: DeserializeSeed<'de> {
+        match self.entries.next() {
+            Some((name, option)) => {
+                self.value = Some(option);
+                seed.deserialize(name.into_deserializer()).map(Some)
+            },
+            None => { Ok(None) }
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed : V) -> Result<V::Value, Error>
+        where V: DeserializeSeed<'de> {
+        let option = self.value.take()
+            .ok_or_else(|| <Error as de::Error>::custom("value is missing"))?;
+        seed.deserialize(Deserializer::new(option))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// Elements of array or list.
+struct CollectionAccess<'a> {
+    elements : CollectionReaderIterator<'a>
+}
+
+impl<'de> de::SeqAccess<'de> for CollectionAccess<'_> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed : T)
+        -> Result<Option<T::Value>, Error> where T: DeserializeSeed<'de> {
+        match self.elements.next() {
+            Some(option) => {
+                seed.deserialize(Deserializer::new(option)).map(Some)
+            },
+            None => { Ok(None) }
+        }
+    }
+}
+
+/// Enum variant stored as group with single member.
+struct VariantAccess<'a> {
+    name : String,
+    option : OptionReader<'a>
+}
+
+impl<'a, 'de> de::EnumAccess<'de> for VariantAccess<'a> {
+    type Error = Error;
+    type Variant = Deserializer<'a>;
+
+    fn variant_seed<V>(self, seed : V)
+        -> Result<(V::Value, Deserializer<'a>), Error>
+        where V: DeserializeSeed<'de> {
+        let variant = seed.deserialize(self.name.into_deserializer())?;
+        Ok((variant, Deserializer::new(self.option)))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Deserializer<'_> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Err(self.error("unit variant must be written as string"))
+    }
+
+    fn newtype_variant_seed<T>(self, seed : T) -> Result<T::Value, Error>
+        where T: DeserializeSeed<'de> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, _len : usize, visitor : V)
+        -> Result<V::Value, Error> where V: Visitor<'de> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V>(self, _fields : &'static [&'static str],
+        visitor : V) -> Result<V::Value, Error> where V: Visitor<'de> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
+
+impl Config {
+
+    /// Deserialize root group into value, `serde` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Server {
+    ///     host : String,
+    ///     port : u16
+    /// }
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("host = \"localhost\"; port = 8080;").is_ok() {
+    ///     let server : Server = cfg.deserialize().unwrap();
+    ///     assert_eq!((server.host.as_str(), server.port),
+    ///         ("localhost", 8080));
+    /// }
+    /// ```
+    pub fn deserialize<T>(&self) -> Result<T, Error> where T: DeserializeOwned {
+        T::deserialize(Deserializer::new(self.reader(self.root_element)))
+    }
+}
+
+impl OptionReader<'_> {
+
+    /// Deserialize setting into value, `serde` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Server {
+    ///     host : String,
+    ///     ports : Vec<u16>
+    /// }
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string(
+    ///     "server : { host = \"localhost\"; ports = [ 80, 443 ]; };"
+    /// ).is_ok() {
+    ///     let server : Server = cfg.value("server").unwrap().deserialize()
+    ///         .unwrap();
+    ///     assert_eq!(server.ports, vec![80, 443]);
+    /// }
+    /// ```
+    pub fn deserialize<T>(&self) -> Result<T, Error> where T: DeserializeOwned {
+        T::deserialize(Deserializer::new(self.derive(self.element)))
+    }
+}
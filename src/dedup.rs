@@ -0,0 +1,184 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Duplicate string detection.
+//!
+//! Long string values repeated across config, typical for generated
+//! configs, are reported and can be factored out into references to their
+//! first occurrence, see `Config::resolve_references`.
+
+use crate::config::{Config, Errors, Result};
+use crate::path;
+use libconfig_sys as raw;
+
+use indexmap::IndexMap;
+use std::ffi::{CStr, CString};
+
+/// Prefix of reference string values.
+const REFERENCE_PREFIX : &str = "@";
+
+/// String value repeated in several settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateString {
+    /// Repeated value.
+    pub value : String,
+    /// Paths of settings holding the value in document order.
+    pub paths : Vec<String>
+}
+
+impl DuplicateString {
+
+    /// Return number of bytes saved by replacing all but the first
+    /// occurrence with references.
+    pub fn saved_bytes(&self) -> usize {
+        let reference = REFERENCE_PREFIX.len() + self.paths[0].len();
+        (self.paths.len() - 1) * self.value.len().saturating_sub(reference)
+    }
+}
+
+// Collect string settings of group or collection by value.
+fn collect_strings(option : *mut raw::config_setting_t, option_path : &str,
+    min_length : usize, strings : &mut IndexMap<String, Vec<String>>) {
+
+    let group = raw::config_setting_is_group(option) == raw::CONFIG_TRUE;
+    for index in 0..unsafe { raw::config_setting_length(option) } {
+        let child = unsafe {
+            raw::config_setting_get_elem(option, index as u32)
+        };
+        if child.is_null() {
+            continue;
+        }
+
+        let name = raw::config_setting_name(child);
+        let child_path = if group && !name.is_null() {
+            path::join(option_path, &unsafe { CStr::from_ptr(name) }
+                .to_string_lossy())
+        } else {
+            path::join(option_path, &format!("[{}]", index))
+        };
+
+        if raw::config_setting_type(child) as i16 == raw::CONFIG_TYPE_STRING {
+            let str = unsafe { raw::config_setting_get_string(child) };
+            if str.is_null() {
+                continue;
+            }
+            if let Ok(value) = unsafe { CStr::from_ptr(str) }.to_str() {
+                if value.len() >= min_length &&
+                    !value.starts_with(REFERENCE_PREFIX) {
+                    strings.entry(value.to_string()).or_default()
+                        .push(child_path);
+                }
+            }
+        } else if raw::config_setting_is_aggregate(child) == raw::CONFIG_TRUE {
+            collect_strings(child, &child_path, min_length, strings);
+        }
+    }
+}
+
+impl Config {
+
+    /// Return string values of at least `min_length` bytes held by more
+    /// than one setting, in order of their first occurrence. References are
+    /// not counted.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("a = \"long value\"; b = \"long value\";")
+    ///     .is_ok() {
+    ///     let duplicates = cfg.duplicate_strings(8);
+    ///     assert_eq!(duplicates[0].paths, vec!["a", "b"]);
+    /// }
+    /// ```
+    pub fn duplicate_strings(&self, min_length : usize)
+        -> Vec<DuplicateString> {
+        let mut strings = IndexMap::new();
+        if let Some(root) = self.root_element {
+            collect_strings(root, "", min_length.max(1), &mut strings);
+        }
+
+        strings.into_iter().filter(|(_, paths)| paths.len() > 1)
+            .map(|(value, paths)| DuplicateString { value, paths }).collect()
+    }
+
+    /// Replace repeated string values of at least `min_length` bytes by
+    /// references to their first occurrence. Only values which get shorter
+    /// are replaced. Returns replaced duplicates. References are followed on
+    /// reads when `resolve_references` is enabled.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("a = \"long value\"; b = \"long value\";")
+    ///     .is_ok() {
+    ///     assert!(cfg.factor_duplicates(8).is_ok());
+    ///     assert_eq!(cfg.value("b").unwrap().as_str(), Some("@a"));
+    ///     cfg.resolve_references(true);
+    ///     assert_eq!(cfg.value("b").unwrap().as_str(), Some("long value"));
+    /// }
+    /// ```
+    pub fn factor_duplicates(&mut self, min_length : usize)
+        -> Result<Vec<DuplicateString>> {
+        let _guard = self.write_guard()?;
+        let root = match self.root_element {
+            Some(root) => { root },
+            None => { return Ok(Vec::new()) }
+        };
+
+        let duplicates : Vec<DuplicateString> = self
+            .duplicate_strings(min_length).into_iter()
+            .filter(|duplicate| duplicate.saved_bytes() > 0).collect();
+        for duplicate in duplicates.iter() {
+            let reference = CString::new(format!("{}{}", REFERENCE_PREFIX,
+                duplicate.paths[0])).map_err(|_| Errors::InvalidString)?;
+
+            for option_path in duplicate.paths[1..].iter() {
+                let option_path = CString::new(option_path.as_str())
+                    .map_err(|_| Errors::InvalidString)?;
+                let result = unsafe {
+                    let option = raw::config_setting_lookup(root,
+                        option_path.as_ptr());
+                    if option.is_null() {
+                        return Err(Errors::ElementNotExists);
+                    }
+                    raw::config_setting_set_string(option, reference.as_ptr())
+                };
+                if result != raw::CONFIG_TRUE {
+                    return Err(Errors::WriteError);
+                }
+            }
+        }
+        Ok(duplicates)
+    }
+}
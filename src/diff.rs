@@ -0,0 +1,230 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Structural difference of configs.
+//!
+//! Settings are compared by path, collection elements by index, so moved
+//! settings are reported as removed and added. Changes can be replayed onto
+//! another config with `Config::apply_patch`.
+
+use crate::config::{Config, Errors, Result};
+use crate::path;
+use crate::value::{self, Value};
+
+use indexmap::IndexMap;
+
+/// Kind of setting change.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeKind {
+    /// Setting exists only in new config.
+    Added(Value),
+    /// Setting exists only in old config.
+    Removed(Value),
+    /// Setting value or type differs.
+    Changed {
+        old : Value,
+        new : Value
+    }
+}
+
+/// Changed setting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// Setting path, collection elements have `[N]` segments.
+    pub path : String,
+    /// How the setting changed.
+    pub kind : ChangeKind
+}
+
+// Compare group members by name.
+fn diff_groups(old : &IndexMap<String, Value>, new : &IndexMap<String, Value>,
+    parent : &str, changes : &mut Vec<Change>) {
+    for (name, old_value) in old.iter() {
+        let member_path = path::join(parent, name);
+        match new.get(name) {
+            Some(new_value) => {
+                diff_values(old_value, new_value, &member_path, changes);
+            },
+            None => {
+                changes.push(Change {
+                    path : member_path,
+                    kind : ChangeKind::Removed(old_value.clone())
+                });
+            }
+        }
+    }
+
+    for (name, new_value) in new.iter() {
+        if !old.contains_key(name) {
+            changes.push(Change {
+                path : path::join(parent, name),
+                kind : ChangeKind::Added(new_value.clone())
+            });
+        }
+    }
+}
+
+// Compare collection elements by index.
+fn diff_elements(old : &[Value], new : &[Value], parent : &str,
+    changes : &mut Vec<Change>) {
+    for index in 0..old.len().max(new.len()) {
+        let element_path = path::join(parent, &format!("[{}]", index));
+        match (old.get(index), new.get(index)) {
+            (Some(old_value), Some(new_value)) => {
+                diff_values(old_value, new_value, &element_path, changes);
+            },
+            (Some(old_value), None) => {
+                changes.push(Change {
+                    path : element_path,
+                    kind : ChangeKind::Removed(old_value.clone())
+                });
+            },
+            (None, Some(new_value)) => {
+                changes.push(Change {
+                    path : element_path,
+                    kind : ChangeKind::Added(new_value.clone())
+                });
+            },
+            (None, None) => {}
+        }
+    }
+}
+
+// Compare values of setting existing in both configs.
+fn diff_values(old : &Value, new : &Value, value_path : &str,
+    changes : &mut Vec<Change>) {
+    match (old, new) {
+        (Value::Group(old), Value::Group(new)) => {
+            diff_groups(old, new, value_path, changes);
+        },
+        (Value::Array(old), Value::Array(new)) |
+        (Value::List(old), Value::List(new)) => {
+            diff_elements(old, new, value_path, changes);
+        },
+        _ => {
+            if old != new {
+                changes.push(Change {
+                    path : value_path.to_string(),
+                    kind : ChangeKind::Changed {
+                        old : old.clone(),
+                        new : new.clone()
+                    }
+                });
+            }
+        }
+    }
+}
+
+impl Config {
+
+    /// Return settings added, removed or changed in other config compared
+    /// to this one. Changes of group members are listed in document order,
+    /// removed before added.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    /// use librustconfig::diff::ChangeKind;
+    /// use librustconfig::value::Value;
+    ///
+    /// let mut old = Config::new();
+    /// let mut new = Config::new();
+    /// if old.load_from_string("server : { port = 80; };").is_ok() &&
+    ///     new.load_from_string("server : { port = 8080; };").is_ok() {
+    ///     let changes = old.diff(&new);
+    ///     assert_eq!(changes[0].path, "server.port");
+    ///     assert_eq!(changes[0].kind, ChangeKind::Changed {
+    ///         old : Value::Int(80),
+    ///         new : Value::Int(8080)
+    ///     });
+    /// }
+    /// ```
+    pub fn diff(&self, other : &Config) -> Vec<Change> {
+        let root = |config : &Config| {
+            match config.root_element.and_then(value::read) {
+                Some(Value::Group(children)) => { children },
+                _ => { IndexMap::new() }
+            }
+        };
+
+        let mut changes = Vec::new();
+        diff_groups(&root(self), &root(other), "", &mut changes);
+        changes
+    }
+
+    /// Apply changes produced by `diff`. Added and changed settings are set
+    /// to new value, missing parent groups are created, removed settings
+    /// are deleted. Old values are not checked. Patch is applied only if all
+    /// changes can be applied, otherwise config is not changed.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut shipped = Config::new();
+    /// let mut current = Config::new();
+    /// let mut upgraded = Config::new();
+    /// if shipped.load_from_string("port = 80; debug = false;").is_ok() &&
+    ///     current.load_from_string("port = 8080;").is_ok() &&
+    ///     upgraded.load_from_string("port = 80; debug = false; workers = 4;")
+    ///         .is_ok() {
+    ///     assert!(upgraded.apply_patch(&shipped.diff(&current)).is_ok());
+    ///     assert_eq!(upgraded.value("port").unwrap().as_int32(), Some(8080));
+    ///     assert!(upgraded.value("debug").is_none());
+    ///     assert!(upgraded.value("workers").is_some());
+    /// }
+    /// ```
+    pub fn apply_patch(&mut self, changes : &[Change]) -> Result<()> {
+        self.clone().apply_changes(changes)?;
+        self.apply_changes(changes)
+    }
+
+    // Apply changes in order. Removed settings are deleted first, from last,
+    // so removed collection tail elements don't shift each other.
+    fn apply_changes(&mut self, changes : &[Change]) -> Result<()> {
+        for change in changes.iter().rev() {
+            if let ChangeKind::Removed(_) = change.kind {
+                self.writer(&change.path).ok_or(Errors::ElementNotExists)?
+                    .delete()?;
+            }
+        }
+
+        for change in changes.iter() {
+            match &change.kind {
+                ChangeKind::Added(value) |
+                ChangeKind::Changed { new : value, .. } => {
+                    self.set(&change.path, value.clone())?;
+                },
+                ChangeKind::Removed(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,223 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Values encoded in standard setting types.
+//!
+//! libconfig integers are at most 64 bits wide. Wider integers are stored
+//! in string settings with `i128:` prefix, so they survive save and load
+//! without precision loss. With `base64` feature binary data is stored in
+//! string settings as standard base64 with padding. Colors are strings in
+//! `#RRGGBB` or `#RRGGBBAA` hex notation.
+
+use crate::config::{CollectionWriter, OptionReader, OptionType, OptionWriter,
+    Result};
+
+#[cfg(feature = "base64")]
+use base64::Engine;
+use std::convert::TryFrom;
+
+/// Prefix of string settings holding integers out of int64 range.
+pub(crate) const I128_PREFIX : &str = "i128:";
+
+impl<'a> OptionWriter<'a> {
+
+    /// Add new i128 value to current group. Value in int64 range is written
+    /// as int64 setting, other values as string with `i128:` prefix.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if let Some(ids) = cfg.create_section("ids") {
+    ///     ids.write_i128("small", 42);
+    ///     ids.write_i128("large", i128::MAX);
+    /// }
+    /// assert_eq!(cfg.value("ids.large").unwrap().as_i128(), Some(i128::MAX));
+    /// ```
+    pub fn write_i128<S>(&self, name : S, value : i128)
+        -> Result<&OptionWriter<'a>> where S: AsRef<str> {
+        match i64::try_from(value) {
+            Ok(value) => { self.write_int64(name, value) },
+            Err(_) => {
+                self.write_string(name, format!("{}{}", I128_PREFIX, value))
+            }
+        }
+    }
+
+    /// Add new binary value to current group as base64 string.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// cfg.root().write_base64_bytes("key", &[0xde, 0xad, 0xbe, 0xef]);
+    /// assert_eq!(cfg.value("key").unwrap().as_str(), Some("3q2+7w=="));
+    /// ```
+    #[cfg(feature = "base64")]
+    pub fn write_base64_bytes<S>(&self, name : S, value : &[u8])
+        -> Result<&OptionWriter<'a>> where S: AsRef<str> {
+        self.write_string(name,
+            base64::engine::general_purpose::STANDARD.encode(value))
+    }
+}
+
+impl<'a> CollectionWriter<'a> {
+
+    /// Add new i128 value to current collection, encoded like
+    /// `OptionWriter::write_i128`. Array elements must have one type, so
+    /// values in and out of int64 range can be mixed only in lists.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if let Some(ids) = cfg.root().create_list("ids") {
+    ///     ids.write_i128(1).unwrap().write_i128(-(1 << 100));
+    /// }
+    /// ```
+    pub fn write_i128(&self, value : i128) -> Result<&CollectionWriter<'a>> {
+        match i64::try_from(value) {
+            Ok(value) => { self.write_int64(value) },
+            Err(_) => {
+                self.write_string(format!("{}{}", I128_PREFIX, value))
+            }
+        }
+    }
+
+    /// Add new binary value to current collection as base64 string.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if let Some(keys) = cfg.root().create_array("keys") {
+    ///     keys.write_base64_bytes(b"first").unwrap()
+    ///         .write_base64_bytes(b"second");
+    /// }
+    /// ```
+    #[cfg(feature = "base64")]
+    pub fn write_base64_bytes(&self, value : &[u8])
+        -> Result<&CollectionWriter<'a>> {
+        self.write_string(base64::engine::general_purpose::STANDARD
+            .encode(value))
+    }
+}
+
+impl OptionReader<'_> {
+
+    /// Present option value as i128. Integer settings and strings written
+    /// by `write_i128` are accepted, other settings give None.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("id = \"i128:100000000000000000000\";")
+    ///     .is_ok() {
+    ///     assert_eq!(cfg.value("id").unwrap().as_i128(),
+    ///         Some(100000000000000000000));
+    /// }
+    /// ```
+    pub fn as_i128(&self) -> Option<i128> {
+        match self.value_type()? {
+            OptionType::IntegerType | OptionType::Int64Type => {
+                self.as_int64().map(i128::from)
+            },
+            OptionType::StringType => {
+                self.as_str()?.strip_prefix(I128_PREFIX)?.parse().ok()
+            },
+            _ => { None }
+        }
+    }
+
+    /// Present string option value decoded from base64, None if option is
+    /// not a string or not valid base64.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("key = \"3q2+7w==\";").is_ok() {
+    ///     assert_eq!(cfg.value("key").unwrap().as_base64_bytes(),
+    ///         Some(vec![0xde, 0xad, 0xbe, 0xef]));
+    /// }
+    /// ```
+    #[cfg(feature = "base64")]
+    pub fn as_base64_bytes(&self) -> Option<Vec<u8>> {
+        if self.value_type()? != OptionType::StringType {
+            return None;
+        }
+        base64::engine::general_purpose::STANDARD.decode(self.as_str()?).ok()
+    }
+
+    /// Present string option value in `#RRGGBB` or `#RRGGBBAA` notation as
+    /// (r, g, b, a) tuple. Alpha is 255 when omitted. Other values give None.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("background = \"#1e90ff\";").is_ok() {
+    ///     assert_eq!(cfg.value("background").unwrap().as_color(),
+    ///         Some((0x1e, 0x90, 0xff, 0xff)));
+    /// }
+    /// ```
+    pub fn as_color(&self) -> Option<(u8, u8, u8, u8)> {
+        if self.value_type()? != OptionType::StringType {
+            return None;
+        }
+        parse_color(self.as_str()?)
+    }
+}
+
+/// Parse `#RRGGBB` or `#RRGGBBAA` color.
+fn parse_color(value : &str) -> Option<(u8, u8, u8, u8)> {
+    let digits = value.strip_prefix('#')?;
+    if !digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let component = |index : usize| {
+        u8::from_str_radix(&digits[index * 2..index * 2 + 2], 16).ok()
+    };
+    match digits.len() {
+        6 => { Some((component(0)?, component(1)?, component(2)?, 0xff)) },
+        8 => {
+            Some((component(0)?, component(1)?, component(2)?, component(3)?))
+        },
+        _ => { None }
+    }
+}
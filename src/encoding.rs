@@ -0,0 +1,147 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Text encoding detection for config files.
+//!
+//! libconfig parses UTF-8 only. Files saved by some editors start with byte
+//! order mark or are UTF-16 encoded, they are transcoded before parsing.
+//! Line ending of loaded text is detected, so it is kept on save.
+
+use crate::config::{Errors, LineEnding, Result};
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+/// Byte order mark character.
+pub(crate) const BYTE_ORDER_MARK : char = '\u{feff}';
+/// UTF-8 byte order mark.
+const UTF8_BOM : &[u8] = &[0xef, 0xbb, 0xbf];
+/// UTF-16 little endian byte order mark.
+const UTF16LE_BOM : &[u8] = &[0xff, 0xfe];
+/// UTF-16 big endian byte order mark.
+const UTF16BE_BOM : &[u8] = &[0xfe, 0xff];
+
+/// Detected file encoding.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Utf8,
+    Utf8Bom,
+    Utf16Le { bom : bool },
+    Utf16Be { bom : bool }
+}
+
+// Detect encoding from leading bytes. UTF-16 without byte order mark is
+// recognized by zero byte of first ASCII character.
+fn detect(head : &[u8]) -> Encoding {
+    if head.starts_with(UTF8_BOM) {
+        Encoding::Utf8Bom
+    } else if head.starts_with(UTF16LE_BOM) {
+        Encoding::Utf16Le { bom : true }
+    } else if head.starts_with(UTF16BE_BOM) {
+        Encoding::Utf16Be { bom : true }
+    } else if head.len() >= 2 && head[0] != 0 && head[1] == 0 {
+        Encoding::Utf16Le { bom : false }
+    } else if head.len() >= 2 && head[0] == 0 && head[1] != 0 {
+        Encoding::Utf16Be { bom : false }
+    } else {
+        Encoding::Utf8
+    }
+}
+
+// Decode UTF-16 code units, byte order mark is skipped.
+fn decode_utf16<F>(bytes : &[u8], bom : bool, unit : F) -> Result<String>
+    where F: Fn([u8; 2]) -> u16 {
+    let bytes = if bom { &bytes[2..] } else { bytes };
+    if bytes.len() % 2 != 0 {
+        return Err(Errors::ParseError);
+    }
+    let units = bytes.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+    char::decode_utf16(units).collect::<std::result::Result<String, _>>()
+        .map_err(|_| Errors::ParseError)
+}
+
+// Decode file content into UTF-8 text.
+pub(crate) fn decode(bytes : &[u8]) -> Result<String> {
+    let text = match detect(bytes) {
+        Encoding::Utf8 => { bytes },
+        Encoding::Utf8Bom => { &bytes[UTF8_BOM.len()..] },
+        Encoding::Utf16Le { bom } => {
+            return decode_utf16(bytes, bom, u16::from_le_bytes)
+        },
+        Encoding::Utf16Be { bom } => {
+            return decode_utf16(bytes, bom, u16::from_be_bytes)
+        }
+    };
+    String::from_utf8(text.to_vec()).map_err(|_| Errors::ParseError)
+}
+
+// Return true if text starting with head bytes must be decoded before
+// libconfig can read it.
+pub(crate) fn needs_transcoding(head : &[u8]) -> bool {
+    detect(head) != Encoding::Utf8
+}
+
+// Return decoded file content if file needs transcoding, None if libconfig
+// can read it as is.
+pub(crate) fn transcoded(file_name : &Path) -> Result<Option<String>> {
+    let mut file = File::open(file_name).map_err(|_| Errors::FileNotExists)?;
+    let mut bytes = Vec::new();
+    file.by_ref().take(UTF8_BOM.len() as u64).read_to_end(&mut bytes)
+        .map_err(|_| Errors::ParseError)?;
+    if !needs_transcoding(&bytes) {
+        return Ok(None);
+    }
+
+    file.read_to_end(&mut bytes).map_err(|_| Errors::ParseError)?;
+    decode(&bytes).map(Some)
+}
+
+// Detect line ending from first line break of text.
+pub(crate) fn line_ending(text : &[u8]) -> LineEnding {
+    match text.iter().position(|c| *c == b'\n') {
+        Some(index) if index > 0 && text[index - 1] == b'\r' => {
+            LineEnding::Crlf
+        },
+        _ => { LineEnding::Lf }
+    }
+}
+
+// Detect line ending of UTF-8 file, LF if file can't be read.
+pub(crate) fn file_line_ending(file_name : &Path) -> LineEnding {
+    let mut line = Vec::new();
+    match File::open(file_name) {
+        Ok(file) => {
+            let _ = BufReader::new(file).read_until(b'\n', &mut line);
+            line_ending(&line)
+        },
+        Err(_) => { LineEnding::Lf }
+    }
+}
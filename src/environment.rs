@@ -0,0 +1,155 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Environment variable overlay.
+//!
+//! Variable `PREFIX_SERVER__PORT` overrides setting `server.port`: the
+//! prefix and one underscore are stripped, double underscores separate path
+//! segments and names are lowercased. Values are parsed as the type of the
+//! existing setting, new settings get boolean, integer or float type if the
+//! value looks like one and string type otherwise.
+
+use crate::config::{Config, Errors, OptionType, Result};
+use crate::path;
+use crate::value::Value;
+
+use std::env;
+
+/// Separator of path segments in variable names.
+const SEGMENT_SEPARATOR : &str = "__";
+
+// Return setting path of variable, None if variable has another prefix or
+// doesn't name valid path.
+fn variable_path(prefix : &str, name : &str) -> Option<String> {
+    let rest = name.strip_prefix(prefix)?.strip_prefix('_')?;
+    let segments : Vec<String> = rest.split(SEGMENT_SEPARATOR)
+        .map(|segment| segment.to_lowercase()).collect();
+    if segments.iter().all(|segment| path::valid_name(segment)) {
+        Some(path::join_segments(&segments))
+    } else {
+        None
+    }
+}
+
+// Parse boolean, case insensitive like libconfig.
+fn parse_bool(text : &str) -> Option<bool> {
+    if text.eq_ignore_ascii_case("true") {
+        Some(true)
+    } else if text.eq_ignore_ascii_case("false") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+// Parse decimal or `0x` prefixed hexadecimal integer.
+fn parse_int(text : &str) -> Option<i64> {
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(digits) => { i64::from_str_radix(digits, 16).ok() },
+        None => { text.parse().ok() }
+    }
+}
+
+// Parse float written with digits, not `inf` or `nan`.
+fn parse_float(text : &str) -> Option<f64> {
+    if text.chars().any(|c| c.is_ascii_digit()) &&
+        text.chars().all(|c| c.is_ascii_digit() || "+-.eE".contains(c)) {
+        text.parse().ok()
+    } else {
+        None
+    }
+}
+
+// Parse variable value as setting type, guess type if setting is new.
+fn parse(text : &str, value_type : Option<OptionType>) -> Option<Value> {
+    match value_type {
+        Some(OptionType::IntegerType) | Some(OptionType::Int64Type) => {
+            parse_int(text).map(Value::Int)
+        },
+        Some(OptionType::FloatType) => { parse_float(text).map(Value::Float) },
+        Some(OptionType::BooleanType) => { parse_bool(text).map(Value::Bool) },
+        Some(OptionType::StringType) => { Some(Value::from(text)) },
+        Some(OptionType::GroupType) | Some(OptionType::ArrayType) |
+        Some(OptionType::ListType) => { None },
+        Some(OptionType::NoneType) | None => {
+            Some(parse_bool(text).map(Value::Bool)
+                .or_else(|| parse_int(text).map(Value::Int))
+                .or_else(|| parse_float(text).map(Value::Float))
+                .unwrap_or_else(|| Value::from(text)))
+        }
+    }
+}
+
+impl Config {
+
+    /// Override settings by environment variables starting with prefix and
+    /// underscore, e.g. `MYAPP_SERVER__PORT=8080` sets `server.port`.
+    /// Missing settings and groups are created. Returns TypeMismatch and
+    /// changes nothing if a value can't be parsed as the type of existing
+    /// setting or the variable names a group or collection.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server = { port = 80; };").is_ok() {
+    ///     std::env::set_var("MYAPP_SERVER__PORT", "8080");
+    ///     assert!(cfg.apply_env("MYAPP").is_ok());
+    ///     assert_eq!(cfg.value("server.port").unwrap().as_int32(),
+    ///         Some(8080));
+    /// }
+    /// ```
+    pub fn apply_env(&mut self, prefix : &str) -> Result<()> {
+        let mut variables : Vec<(String, String)> = env::vars_os()
+            .filter_map(|(name, value)| {
+                Some((name.into_string().ok()?, value.into_string().ok()?))
+            }).collect();
+        // Parents before children, same order on every run.
+        variables.sort();
+
+        let mut overrides = Vec::new();
+        for (name, text) in variables.iter() {
+            let path = match variable_path(prefix, name) {
+                Some(path) => { path },
+                None => { continue }
+            };
+            let value_type = self.value(&path)
+                .and_then(|option| option.value_type());
+            let value = parse(text, value_type).ok_or(Errors::TypeMismatch)?;
+            overrides.push((path, value));
+        }
+
+        for (path, value) in overrides {
+            self.set(path, value)?;
+        }
+        Ok(())
+    }
+}
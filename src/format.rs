@@ -0,0 +1,509 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! libconfig text output of owned values and config settings.
+
+use crate::config::{Config, Errors, FormatOptions, LineEnding, Result};
+use crate::value::Value;
+use libconfig_sys as raw;
+
+use std::fmt::{self, Write};
+use std::mem::{self, MaybeUninit};
+use std::io;
+
+/// Size of text copied from libconfig writer at once.
+const CHUNK_SIZE : usize = 64 * 1024;
+
+/// Text output of formatter.
+pub(crate) trait Output : Write {
+    fn push(&mut self, c : char);
+    fn push_str(&mut self, text : &str);
+}
+
+impl Output for String {
+    fn push(&mut self, c : char) {
+        String::push(self, c);
+    }
+
+    fn push_str(&mut self, text : &str) {
+        String::push_str(self, text);
+    }
+}
+
+/// Stream libconfig writes config text to, anonymous temporary file created
+/// by `tmpfile`. File is removed when stream is closed.
+struct TempStream(*mut libc::FILE);
+
+impl Drop for TempStream {
+    fn drop(&mut self) {
+        unsafe { libc::fclose(self.0); }
+    }
+}
+
+/// Indentation of nested settings.
+pub(crate) const INDENT : usize = 2;
+
+/// Largest indentation libconfig supports.
+const MAX_TAB_WIDTH : u16 = 15;
+
+// Write indentation for nesting depth.
+pub(crate) fn indent<W : Output>(out : &mut W, depth : usize) {
+    for _ in 0..depth * INDENT {
+        out.push(' ');
+    }
+}
+
+// Write string literal.
+pub(crate) fn write_string<W : Output>(out : &mut W, text : &str) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => { out.push_str("\\\""); },
+            '\\' => { out.push_str("\\\\"); },
+            '\n' => { out.push_str("\\n"); },
+            '\r' => { out.push_str("\\r"); },
+            '\t' => { out.push_str("\\t"); },
+            '\x0c' => { out.push_str("\\f"); },
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\x{:02X}", c as u32);
+            },
+            c => { out.push(c); }
+        }
+    }
+    out.push('"');
+}
+
+// Write float literal, it always has decimal point or exponent. NaN and
+// infinity have no libconfig syntax and return error.
+pub(crate) fn write_float<W : Output>(out : &mut W, value : f64)
+    -> fmt::Result {
+    if !value.is_finite() {
+        return Err(fmt::Error);
+    }
+    write!(out, "{:?}", value)
+}
+
+// Write value, nested groups start at depth.
+pub(crate) fn write_value<W : Output>(out : &mut W, value : &Value,
+    depth : usize) -> fmt::Result {
+    match value {
+        Value::Int(val) => {
+            if *val >= i32::MIN as i64 && *val <= i32::MAX as i64 {
+                write!(out, "{}", val)?;
+            } else {
+                write!(out, "{}L", val)?;
+            }
+        },
+        Value::Float(val) => { write_float(out, *val)?; },
+        Value::Bool(val) => { write!(out, "{}", val)?; },
+        Value::Str(val) => { write_string(out, val); },
+        Value::Array(elements) | Value::List(elements) => {
+            let (open, close) = match value {
+                Value::Array(_) => { ('[', ']') },
+                _ => { ('(', ')') }
+            };
+
+            out.push(open);
+            for (index, element) in elements.iter().enumerate() {
+                out.push_str(if index == 0 { " " } else { ", " });
+                write_value(out, element, depth)?;
+            }
+            if !elements.is_empty() {
+                out.push(' ');
+            }
+            out.push(close);
+        },
+        Value::Group(children) => {
+            out.push_str("{\n");
+            for (name, child) in children.iter() {
+                write_setting(out, name, child, depth + 1)?;
+            }
+            indent(out, depth);
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+// Write named setting line.
+pub(crate) fn write_setting<W : Output>(out : &mut W, name : &str,
+    value : &Value, depth : usize) -> fmt::Result {
+    indent(out, depth);
+    out.push_str(name);
+    match value {
+        Value::Group(_) => {
+            out.push_str(" :\n");
+            indent(out, depth);
+        },
+        _ => { out.push_str(" = "); }
+    }
+    write_value(out, value, depth)?;
+    out.push_str(";\n");
+    Ok(())
+}
+
+// Return true if setting or any nested element is NaN or infinity, libconfig
+// writes them as text it can't read back.
+fn has_non_finite(option : *mut raw::config_setting_t) -> bool {
+    if raw::config_setting_type(option) as i16 == raw::CONFIG_TYPE_FLOAT {
+        return !unsafe { raw::config_setting_get_float(option) }.is_finite();
+    }
+
+    (0..unsafe { raw::config_setting_length(option) }).any(|index| {
+        let child = unsafe {
+            raw::config_setting_get_elem(option, index as u32)
+        };
+        !child.is_null() && has_non_finite(child)
+    })
+}
+
+// Write comment line.
+pub(crate) fn write_comment<W : Output>(out : &mut W, text : &str,
+    depth : usize) {
+    for line in text.lines() {
+        indent(out, depth);
+        if line.is_empty() {
+            out.push_str("#\n");
+        } else {
+            let _ = writeln!(out, "# {}", line);
+        }
+    }
+}
+
+// Write text of config settings with libconfig writer, pass it to write in
+// chunks with line ending.
+fn write_text<F>(config : &raw::config_t, line_ending : LineEnding,
+    mut write : F) -> Result<()>
+    where F: FnMut(&[u8]) -> Result<()> {
+    let stream = TempStream(unsafe { libc::tmpfile() });
+    if stream.0.is_null() {
+        return Err(Errors::SaveError);
+    }
+    unsafe {
+        raw::config_write(config, stream.0);
+        if libc::fflush(stream.0) != 0 || libc::ferror(stream.0) != 0 {
+            return Err(Errors::SaveError);
+        }
+        libc::rewind(stream.0);
+    }
+
+    let mut chunk = vec![0u8; CHUNK_SIZE];
+    let mut crlf = Vec::new();
+    loop {
+        let length = unsafe {
+            libc::fread(chunk.as_mut_ptr() as *mut libc::c_void, 1,
+                chunk.len(), stream.0)
+        };
+        if length == 0 {
+            break;
+        }
+        match line_ending {
+            LineEnding::Lf => { write(&chunk[..length])?; },
+            // Line breaks inside strings are escaped, so every `\n` is a
+            // line end.
+            LineEnding::Crlf => {
+                crlf.clear();
+                for byte in chunk[..length].iter() {
+                    if *byte == b'\n' {
+                        crlf.push(b'\r');
+                    }
+                    crlf.push(*byte);
+                }
+                write(&crlf)?;
+            }
+        }
+    }
+
+    if unsafe { libc::ferror(stream.0) } != 0 {
+        Err(Errors::SaveError)
+    } else {
+        Ok(())
+    }
+}
+
+// Return float precision libconfig uses for new config.
+fn default_float_precision() -> u16 {
+    let mut config = MaybeUninit::<raw::config_t>::uninit();
+    unsafe {
+        raw::config_init(config.as_mut_ptr());
+        let precision = raw::config_get_float_precision(config.as_ptr());
+        raw::config_destroy(config.as_mut_ptr());
+        precision
+    }
+}
+
+impl FormatOptions {
+
+    /// Constructor.
+    /// Create default style.
+    pub fn new() -> FormatOptions {
+        FormatOptions::default()
+    }
+
+    /// Set number of spaces per nesting level, at most 15. Default is 2.
+    pub fn tab_width(mut self, tab_width : u16) -> FormatOptions {
+        self.tab_width = tab_width.min(MAX_TAB_WIDTH);
+        self
+    }
+
+    /// Write floats with at most this many digits after decimal point. By
+    /// default libconfig precision is used.
+    pub fn float_precision(mut self, digits : u16) -> FormatOptions {
+        self.float_precision = Some(digits);
+        self
+    }
+
+    /// End settings with semicolon. Default is true.
+    pub fn semicolon_separators(mut self, enabled : bool) -> FormatOptions {
+        self.semicolon_separators = enabled;
+        self
+    }
+
+    /// Write `name : { ... }` instead of `name = { ... }` for groups.
+    /// Default is true.
+    pub fn colon_assignment_for_groups(mut self, enabled : bool)
+        -> FormatOptions {
+        self.colon_assignment_for_groups = enabled;
+        self
+    }
+
+    /// Write `name : value` instead of `name = value` for other settings.
+    /// Default is false.
+    pub fn colon_assignment_for_non_groups(mut self, enabled : bool)
+        -> FormatOptions {
+        self.colon_assignment_for_non_groups = enabled;
+        self
+    }
+
+    /// Write opening brace of group on its own line. Default is true.
+    pub fn open_brace_on_separate_line(mut self, enabled : bool)
+        -> FormatOptions {
+        self.open_brace_on_separate_line = enabled;
+        self
+    }
+}
+
+impl Default for FormatOptions {
+    fn default() -> FormatOptions {
+        FormatOptions {
+            tab_width : INDENT as u16,
+            float_precision : None,
+            semicolon_separators : true,
+            colon_assignment_for_groups : true,
+            colon_assignment_for_non_groups : false,
+            open_brace_on_separate_line : true
+        }
+    }
+}
+
+impl Config {
+
+    /// Write current config in libconfig format into string. Text is
+    /// written by libconfig, like in `save_to_file`. Returns SaveError if
+    /// config has NaN or infinity values or text is not valid UTF-8.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { port = 80; };").is_ok() {
+    ///     assert_eq!(cfg.save_to_string().unwrap(),
+    ///         "server : \n{\n  port = 80;\n};\n");
+    /// }
+    /// ```
+    pub fn save_to_string(&self) -> Result<String> {
+        let mut out = String::new();
+        self.save_to_string_into(&mut out)?;
+        Ok(out)
+    }
+
+    /// Write current config in libconfig format into buffer, replacing its
+    /// content. Buffer keeps its capacity, so it can be reused for repeated
+    /// saves without reallocation. Buffer is left empty on error.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// let mut buffer = String::new();
+    /// if cfg.load_from_string("port = 80;").is_ok() {
+    ///     assert!(cfg.save_to_string_into(&mut buffer).is_ok());
+    ///     assert_eq!(buffer, "port = 80;\n");
+    /// }
+    /// ```
+    pub fn save_to_string_into(&self, out : &mut String) -> Result<()> {
+        let mut bytes = mem::take(out).into_bytes();
+        bytes.clear();
+        let result = self.write_text(|text| {
+            bytes.extend_from_slice(text);
+            Ok(())
+        });
+
+        match String::from_utf8(bytes) {
+            Ok(text) if result.is_ok() => {
+                *out = text;
+                Ok(())
+            },
+            Ok(text) => {
+                *out = text;
+                out.clear();
+                result
+            },
+            Err(error) => {
+                let mut bytes = error.into_bytes();
+                bytes.clear();
+                *out = String::from_utf8(bytes).unwrap_or_default();
+                Err(Errors::SaveError)
+            }
+        }
+    }
+
+    /// Write current config in libconfig format into stream. Text is copied
+    /// from libconfig writer in chunks, so whole document is never kept in
+    /// memory.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("port = 80;").is_ok() {
+    ///     let mut out = Vec::new();
+    ///     assert!(cfg.save_to_writer(&mut out).is_ok());
+    ///     assert_eq!(out, b"port = 80;\n");
+    /// }
+    /// ```
+    pub fn save_to_writer<S>(&self, mut stream : S) -> Result<()>
+        where S: io::Write {
+        self.write_text(|text| {
+            stream.write_all(text).map_err(|_| Errors::SaveError)
+        })?;
+        stream.flush().map_err(|_| Errors::SaveError)
+    }
+
+    // Write config text with libconfig writer, empty config has no text.
+    fn write_text<F>(&self, write : F) -> Result<()>
+        where F: FnMut(&[u8]) -> Result<()> {
+        self.check_savable()?;
+        match self.root_element {
+            Some(_) => { write_text(&self.config, self.line_ending, write) },
+            None => { Ok(()) }
+        }
+    }
+
+    // Return SaveError if config has NaN or infinity values.
+    pub(crate) fn check_savable(&self) -> Result<()> {
+        match self.root_element {
+            Some(root) if has_non_finite(root) => { Err(Errors::SaveError) },
+            _ => { Ok(()) }
+        }
+    }
+
+    /// Return line ending used on save. It is detected from first line of
+    /// loaded text, LF for new config.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::{Config, LineEnding};
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("port = 80;\r\nhost = \"a\";\r\n").is_ok() {
+    ///     assert_eq!(cfg.line_ending(), LineEnding::Crlf);
+    /// }
+    /// ```
+    pub fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// Set line ending used on save.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::{Config, LineEnding};
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("port = 80;").is_ok() {
+    ///     cfg.set_line_ending(LineEnding::Crlf);
+    ///     assert_eq!(cfg.save_to_string().unwrap(), "port = 80;\r\n");
+    /// }
+    /// ```
+    pub fn set_line_ending(&mut self, line_ending : LineEnding) {
+        self.line_ending = line_ending;
+    }
+
+    /// Return style of saved config text.
+    pub fn format_options(&self) -> FormatOptions {
+        self.format_options
+    }
+
+    /// Set style of saved config text, used by all save methods.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::{Config, FormatOptions};
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("port = 80;").is_ok() {
+    ///     cfg.set_format_options(FormatOptions::new()
+    ///         .colon_assignment_for_non_groups(true));
+    ///     assert_eq!(cfg.save_to_string().unwrap(), "port : 80;\n");
+    /// }
+    /// ```
+    pub fn set_format_options(&mut self, options : FormatOptions) {
+        let mut flags = unsafe { raw::config_get_options(&self.config) };
+        for (flag, enabled) in [
+            (raw::CONFIG_OPTION_SEMICOLON_SEPARATORS,
+                options.semicolon_separators),
+            (raw::CONFIG_OPTION_COLON_ASSIGNMENT_FOR_GROUPS,
+                options.colon_assignment_for_groups),
+            (raw::CONFIG_OPTION_COLON_ASSIGNMENT_FOR_NON_GROUPS,
+                options.colon_assignment_for_non_groups),
+            (raw::CONFIG_OPTION_OPEN_BRACE_ON_SEPARATE_LINE,
+                options.open_brace_on_separate_line)
+        ].iter() {
+            if *enabled {
+                flags |= flag;
+            } else {
+                flags &= !flag;
+            }
+        }
+
+        unsafe { raw::config_set_options(&mut self.config, flags); }
+        raw::config_set_tab_width(&mut self.config, options.tab_width);
+        let precision = options.float_precision
+            .unwrap_or_else(default_float_precision);
+        unsafe {
+            raw::config_set_float_precision(&mut self.config, precision);
+        }
+        self.format_options = options;
+    }
+}
@@ -0,0 +1,174 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Mutation guard of finalized configs.
+//!
+//! Config frozen by `Config::finalize` refuses all writes. Write attempt is
+//! reported on stderr, debug builds panic. All writes pass the guard.
+
+use crate::cache::{self, Generation};
+use crate::config::{Config, Errors, Result};
+use crate::path;
+use libconfig_sys as raw;
+
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Root settings of frozen configs.
+static FROZEN_ROOTS : Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+/// Number of frozen configs, checked before taking the lock.
+static FROZEN_COUNT : AtomicUsize = AtomicUsize::new(0);
+
+// Return root setting of setting tree.
+fn root(option : *mut raw::config_setting_t) -> *mut raw::config_setting_t {
+    let mut current = option;
+    while raw::config_setting_is_root(current) != raw::CONFIG_TRUE {
+        current = raw::config_setting_parent(current);
+    }
+    current
+}
+
+// Return true if setting tree with root is frozen.
+fn is_frozen_root(root : *mut raw::config_setting_t) -> bool {
+    FROZEN_COUNT.load(Ordering::Acquire) != 0 && FROZEN_ROOTS.lock()
+        .map(|roots| roots.contains(&(root as usize))).unwrap_or(false)
+}
+
+// Report write attempt to frozen config.
+fn report(option : *mut raw::config_setting_t) {
+    let option_path = path::setting_path(option);
+    let message = if option_path.is_empty() {
+        "librustconfig: write to frozen config".to_string()
+    } else {
+        format!("librustconfig: write to frozen config at \"{}\"",
+            option_path)
+    };
+    if cfg!(debug_assertions) {
+        panic!("{}", message);
+    }
+    eprintln!("{}", message);
+}
+
+/// Modification of whole config in progress. Lookup cache is invalidated
+/// again when modification ends, so settings looked up during modification
+/// are not kept.
+pub(crate) struct WriteGuard {
+    generation : Generation
+}
+
+impl Drop for WriteGuard {
+    fn drop(&mut self) {
+        cache::invalidate(&self.generation);
+    }
+}
+
+// Return true if setting can be modified, report write attempt otherwise.
+pub(crate) fn writable(option : *mut raw::config_setting_t) -> bool {
+    if FROZEN_COUNT.load(Ordering::Acquire) == 0 ||
+        !is_frozen_root(root(option)) {
+        return true;
+    }
+    report(option);
+    false
+}
+
+impl Config {
+
+    /// Freeze config after initialization. All following writes are refused
+    /// and reported on stderr, debug builds panic on write attempt.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("port = 8080;").is_ok() {
+    ///     cfg.finalize();
+    ///     assert!(cfg.is_frozen());
+    /// }
+    /// ```
+    pub fn finalize(&mut self) {
+        let root = match self.root_element {
+            Some(root) => { root as usize },
+            None => { return }
+        };
+
+        if let Ok(mut roots) = FROZEN_ROOTS.lock() {
+            if !roots.contains(&root) {
+                roots.push(root);
+                FROZEN_COUNT.fetch_add(1, Ordering::AcqRel);
+            }
+        }
+    }
+
+    /// Return true if config was frozen by `finalize`.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let cfg = Config::new();
+    /// assert!(!cfg.is_frozen());
+    /// ```
+    pub fn is_frozen(&self) -> bool {
+        match self.root_element {
+            Some(root) => { is_frozen_root(root) },
+            None => { false }
+        }
+    }
+
+    // Start modification of whole config, return error if config is frozen.
+    pub(crate) fn write_guard(&mut self) -> Result<WriteGuard> {
+        cache::invalidate(&self.generation);
+        match self.root_element {
+            Some(root) if !writable(root) => { Err(Errors::ConfigFrozen) },
+            _ => {
+                Ok(WriteGuard { generation : Rc::clone(&self.generation) })
+            }
+        }
+    }
+
+    // Forget frozen state, config is destroyed.
+    pub(crate) fn thaw(&mut self) {
+        let root = match self.root_element {
+            Some(root) => { root as usize },
+            None => { return }
+        };
+
+        if let Ok(mut roots) = FROZEN_ROOTS.lock() {
+            if let Some(index) = roots.iter().position(|r| *r == root) {
+                roots.swap_remove(index);
+                FROZEN_COUNT.fetch_sub(1, Ordering::AcqRel);
+            }
+        }
+    }
+}
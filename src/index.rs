@@ -0,0 +1,232 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Index operators of config and readers.
+//!
+//! `cfg["server"]["port"]` never panics: missing settings give empty
+//! reader, so lookups chain like `serde_json::Value` and every accessor of
+//! the result returns `None`. Use `Config::value` and `OptionReader::get`
+//! to check every step instead.
+//!
+//! Readers returned by config are kept in its lookup cache until config is
+//! written. Readers of children are kept in parent reader, which can't be
+//! alive while config is written.
+
+use crate::config::{Config, OptionReader};
+use crate::track::Tracker;
+use libconfig_sys as raw;
+
+use std::cell::{OnceCell, RefCell};
+use std::collections::HashMap;
+use std::ops::Index;
+
+/// Readers by setting, empty reader is stored under null setting.
+pub(crate) type Readers = HashMap<usize, Box<OptionReader<'static>>>;
+
+/// Readers of children returned by index operators of reader, created on
+/// first use.
+#[derive(Default)]
+pub(crate) struct ChildReaders {
+    readers : OnceCell<Box<RefCell<Readers>>>
+}
+
+// Return stored reader of element, create it if it not exists. Readers are
+// 'static, so callers return them with lifetime of their owner.
+pub(crate) fn stored_reader(readers : &mut Readers,
+    element : Option<*mut raw::config_setting_t>, tracker : &Option<Tracker>,
+    resolved : bool) -> *const OptionReader<'static> {
+    let key = element.map_or(0, |element| element as usize);
+    &**readers.entry(key).or_insert_with(|| {
+        let mut reader = OptionReader::new(element);
+        reader.tracker = tracker.clone();
+        reader.resolved = resolved;
+        Box::new(reader)
+    })
+}
+
+impl ChildReaders {
+
+    // Return reader of child element sharing read tracker of parent.
+    fn reader(&self, element : Option<*mut raw::config_setting_t>,
+        tracker : &Option<Tracker>, resolved : bool)
+        -> &OptionReader<'static> {
+        let mut readers = self.readers.get_or_init(Box::default)
+            .borrow_mut();
+        let reader = stored_reader(&mut readers, element, tracker,
+            resolved);
+        // Boxed reader doesn't move and lives as long as parent.
+        unsafe { &*reader }
+    }
+}
+
+/// Group member path or collection element index.
+pub trait ReaderIndex {
+    /// Return reader of child setting.
+    fn child<'a>(&self, reader : &'a OptionReader<'_>)
+        -> Option<OptionReader<'a>>;
+}
+
+impl ReaderIndex for str {
+    fn child<'a>(&self, reader : &'a OptionReader<'_>)
+        -> Option<OptionReader<'a>> {
+        reader.value(self)
+    }
+}
+
+impl ReaderIndex for String {
+    fn child<'a>(&self, reader : &'a OptionReader<'_>)
+        -> Option<OptionReader<'a>> {
+        reader.value(self)
+    }
+}
+
+impl ReaderIndex for usize {
+    fn child<'a>(&self, reader : &'a OptionReader<'_>)
+        -> Option<OptionReader<'a>> {
+        let element = reader.element?;
+        if raw::config_setting_is_aggregate(element) != raw::CONFIG_TRUE {
+            return None;
+        }
+
+        let child = unsafe {
+            raw::config_setting_get_elem(element, *self as u32)
+        };
+        if child.is_null() {
+            None
+        } else {
+            Some(reader.derive(Some(child)))
+        }
+    }
+}
+
+impl<T> ReaderIndex for &T where T: ReaderIndex + ?Sized {
+    fn child<'a>(&self, reader : &'a OptionReader<'_>)
+        -> Option<OptionReader<'a>> {
+        (**self).child(reader)
+    }
+}
+
+impl OptionReader<'_> {
+
+    /// Return reader of group member or collection element, None if it not
+    /// exists.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("servers = ( { port = 80; } );").is_ok() {
+    ///     let servers = cfg.value("servers").unwrap();
+    ///     let port = servers.get(0).and_then(|server| {
+    ///         server.get("port").and_then(|port| port.as_int32())
+    ///     });
+    ///     assert_eq!(port, Some(80));
+    /// }
+    /// ```
+    pub fn get<I>(&self, index : I) -> Option<OptionReader<'_>>
+        where I: ReaderIndex {
+        index.child(self)
+    }
+
+    /// Return true if reader points to existing setting, false for empty
+    /// reader returned by index operators.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { port = 80; };").is_ok() {
+    ///     assert!(cfg["server"]["port"].exists());
+    ///     assert!(!cfg["server"]["host"].exists());
+    /// }
+    /// ```
+    pub fn exists(&self) -> bool {
+        self.element.is_some()
+    }
+}
+
+// Output can't name the config borrow, so readers are 'static. It's still
+// sound: returned reference borrows config, and everything reader returns
+// borrows the reader itself.
+impl<'a> Index<&'a str> for Config {
+    type Output = OptionReader<'static>;
+
+    /// Return reader of setting at path, or empty reader if it not exists.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { port = 80; };").is_ok() {
+    ///     assert_eq!(cfg["server"]["port"].as_int32(), Some(80));
+    ///     assert_eq!(cfg["server"]["host"]["name"].as_int32(), None);
+    /// }
+    /// ```
+    fn index(&self, path : &'a str) -> &OptionReader<'static> {
+        let element = self.value(path).and_then(|option| option.element);
+        self.stored_reader(element)
+    }
+}
+
+impl<'a, 'b> Index<&'b str> for OptionReader<'a> {
+    type Output = OptionReader<'a>;
+
+    /// Return reader of setting at path relative to this setting, or empty
+    /// reader if it not exists.
+    fn index(&self, path : &'b str) -> &OptionReader<'a> {
+        let element = self.get(path).and_then(|option| option.element);
+        self.children.reader(element, &self.tracker, self.resolved)
+    }
+}
+
+impl<'a> Index<usize> for OptionReader<'a> {
+    type Output = OptionReader<'a>;
+
+    /// Return reader of collection element, or empty reader if it not
+    /// exists.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("ports = [ 80, 443 ];").is_ok() {
+    ///     assert_eq!(cfg["ports"][1].as_int32(), Some(443));
+    ///     assert!(!cfg["ports"][2].exists());
+    /// }
+    /// ```
+    fn index(&self, index : usize) -> &OptionReader<'a> {
+        let element = self.get(index).and_then(|option| option.element);
+        self.children.reader(element, &self.tracker, self.resolved)
+    }
+}
@@ -0,0 +1,128 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Integer notation of saved settings.
+//!
+//! Hexadecimal integers keep their notation when config is loaded and saved
+//! again. Format of integer settings and integer arrays can be changed, so
+//! bitmask settings are written as `0xFF` instead of `255`.
+
+use crate::config::{Errors, IntFormat, OptionReader, OptionWriter, Result};
+use crate::freeze;
+use libconfig_sys as raw;
+
+use std::os::raw::c_short;
+
+// Return true if setting is integer.
+fn is_integer(option : *mut raw::config_setting_t) -> bool {
+    matches!(raw::config_setting_type(option) as i16,
+        raw::CONFIG_TYPE_INT | raw::CONFIG_TYPE_INT64)
+}
+
+impl OptionReader<'_> {
+
+    /// Return notation of integer setting, None if setting is not integer.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::{Config, IntFormat};
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("mask = 0xFF; port = 80;").is_ok() {
+    ///     assert_eq!(cfg.value("mask").unwrap().format(),
+    ///         Some(IntFormat::Hex));
+    ///     assert_eq!(cfg.value("port").unwrap().format(),
+    ///         Some(IntFormat::Default));
+    /// }
+    /// ```
+    pub fn format(&self) -> Option<IntFormat> {
+        let option = self.element?;
+        if !is_integer(option) {
+            return None;
+        }
+
+        match unsafe { raw::config_setting_get_format(option) } as i32 {
+            raw::CONFIG_FORMAT_HEX => { Some(IntFormat::Hex) },
+            _ => { Some(IntFormat::Default) }
+        }
+    }
+}
+
+impl OptionWriter<'_> {
+
+    /// Set notation of integer setting or of all elements of integer array.
+    /// Returns TypeMismatch for other settings.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::{Config, IntFormat};
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("mask = 255;").is_ok() {
+    ///     assert!(cfg.writer("mask").unwrap().set_format(IntFormat::Hex)
+    ///         .is_ok());
+    ///     assert_eq!(cfg.save_to_string().unwrap(), "mask = 0xFF;\n");
+    /// }
+    /// ```
+    pub fn set_format(&self, format : IntFormat) -> Result<()> {
+        let option = self.element.ok_or(Errors::ElementNotExists)?;
+        let elements : Vec<_> = if is_integer(option) {
+            vec![option]
+        } else if raw::config_setting_is_array(option) == raw::CONFIG_TRUE {
+            (0..unsafe { raw::config_setting_length(option) })
+                .map(|index| unsafe {
+                    raw::config_setting_get_elem(option, index as u32)
+                })
+                .collect()
+        } else {
+            return Err(Errors::TypeMismatch);
+        };
+        if !elements.iter().all(|element| !element.is_null() &&
+            is_integer(*element)) {
+            return Err(Errors::TypeMismatch);
+        }
+        if !freeze::writable(option) {
+            return Err(Errors::ConfigFrozen);
+        }
+
+        let format = match format {
+            IntFormat::Default => { raw::CONFIG_FORMAT_DEFAULT },
+            IntFormat::Hex => { raw::CONFIG_FORMAT_HEX }
+        };
+        for element in elements {
+            if unsafe {
+                raw::config_setting_set_format(element, format as c_short)
+            } != raw::CONFIG_TRUE {
+                return Err(Errors::WriteError);
+            }
+        }
+        Ok(())
+    }
+}
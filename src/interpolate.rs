@@ -0,0 +1,228 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! `${var}` interpolation within string values.
+//!
+//! Interpolation is an opt-in pass run after config is loaded. Every
+//! `${name}` inside string values is replaced by the value of setting found
+//! by path `name`, or by environment variable `name` if there is no such
+//! setting. `$${` is written as literal `${`, so the pass runs only once
+//! per loaded config: another pass would expand unescaped literals.
+
+use crate::config::{Config, Errors, Result};
+use libconfig_sys as raw;
+
+use std::collections::HashMap;
+use std::env;
+use std::ffi::{CStr, CString};
+
+/// Interpolation pass state.
+struct Interpolator {
+    root : *mut raw::config_setting_t,
+    expanded : HashMap<*mut raw::config_setting_t, String>,
+    stack : Vec<*mut raw::config_setting_t>
+}
+
+impl Interpolator {
+
+    // Return expanded value of string setting.
+    fn expand(&mut self, option : *mut raw::config_setting_t)
+        -> Result<String> {
+
+        if let Some(value) = self.expanded.get(&option) {
+            return Ok(value.clone());
+        }
+
+        let text = unsafe {
+            let str = raw::config_setting_get_string(option);
+            if str.is_null() {
+                return Ok(String::new());
+            }
+            CStr::from_ptr(str).to_str().map_err(|_| Errors::InvalidString)?
+                .to_string()
+        };
+
+        self.stack.push(option);
+        let result = self.expand_text(&text);
+        self.stack.pop();
+
+        let value = result?;
+        self.expanded.insert(option, value.clone());
+        Ok(value)
+    }
+
+    // Replace all variables in text.
+    fn expand_text(&mut self, text : &str) -> Result<String> {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+
+        while let Some(pos) = rest.find('$') {
+            result.push_str(&rest[..pos]);
+            rest = &rest[pos..];
+
+            if rest.starts_with("$${") {
+                result.push_str("${");
+                rest = &rest[3..];
+            } else if rest.starts_with("${") {
+                let end = rest.find('}').ok_or(Errors::UnresolvedVariable)?;
+                result.push_str(&self.resolve(&rest[2..end])?);
+                rest = &rest[end + 1..];
+            } else {
+                result.push('$');
+                rest = &rest[1..];
+            }
+        }
+
+        result.push_str(rest);
+        Ok(result)
+    }
+
+    // Return value of variable.
+    fn resolve(&mut self, name : &str) -> Result<String> {
+        let path = CString::new(name).map_err(|_| Errors::UnresolvedVariable)?;
+        let option = unsafe {
+            raw::config_setting_lookup(self.root, path.as_ptr())
+        };
+
+        if option.is_null() {
+            return env::var(name).map_err(|_| Errors::UnresolvedVariable);
+        }
+
+        if self.stack.contains(&option) {
+            return Err(Errors::RecursiveVariable);
+        }
+
+        unsafe {
+            match raw::config_setting_type(option) as i16 {
+                raw::CONFIG_TYPE_STRING => { self.expand(option) },
+                raw::CONFIG_TYPE_INT => {
+                    Ok(raw::config_setting_get_int(option).to_string())
+                },
+                raw::CONFIG_TYPE_INT64 => {
+                    Ok(raw::config_setting_get_int64(option).to_string())
+                },
+                raw::CONFIG_TYPE_FLOAT => {
+                    Ok(raw::config_setting_get_float(option).to_string())
+                },
+                raw::CONFIG_TYPE_BOOL => {
+                    Ok((raw::config_setting_get_bool(option) ==
+                        raw::CONFIG_TRUE).to_string())
+                },
+                _ => { Err(Errors::UnresolvedVariable) }
+            }
+        }
+    }
+}
+
+// Collect all string settings under option.
+fn collect_strings(option : *mut raw::config_setting_t,
+    result : &mut Vec<*mut raw::config_setting_t>) {
+
+    if raw::config_setting_type(option) as i16 == raw::CONFIG_TYPE_STRING {
+        result.push(option);
+        return;
+    }
+
+    let length = unsafe { raw::config_setting_length(option) };
+    for index in 0..length {
+        let child = unsafe {
+            raw::config_setting_get_elem(option, index as u32)
+        };
+        if !child.is_null() {
+            collect_strings(child, result);
+        }
+    }
+}
+
+impl Config {
+
+    /// Expand `${name}` variables inside all string values.
+    ///
+    /// Variable name is looked up as setting path first and as environment
+    /// variable next. Referenced string settings are expanded recursively,
+    /// reference cycle returns `Errors::RecursiveVariable`. Unknown variables
+    /// return `Errors::UnresolvedVariable`, string that is not valid UTF-8
+    /// returns `Errors::InvalidString`, in all cases config stays unchanged.
+    /// Write `$${` to get literal `${`.
+    ///
+    /// Interpolation is one-shot: literal `${` is stored unescaped, so
+    /// following calls return false and do nothing until config is loaded
+    /// again, values written after interpolation are not expanded. Return
+    /// true if strings were expanded.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string(
+    ///     "paths : {
+    ///         base = \"/opt/app\";
+    ///         logs = \"${paths.base}/logs\";
+    ///     };"
+    /// ).is_ok() && cfg.interpolate().is_ok() {
+    ///     assert_eq!(cfg.value("paths.logs").unwrap().as_string().unwrap(),
+    ///         "/opt/app/logs");
+    ///     assert_eq!(cfg.interpolate(), Ok(false));
+    /// }
+    /// ```
+    pub fn interpolate(&mut self) -> Result<bool> {
+        if self.interpolated {
+            return Ok(false);
+        }
+        let _guard = self.write_guard()?;
+        let root = match self.root_element {
+            Some(root) => { root },
+            None => { return Err(Errors::ElementNotExists) }
+        };
+
+        let mut strings = Vec::new();
+        collect_strings(root, &mut strings);
+
+        let mut interpolator = Interpolator {
+            root,
+            expanded : HashMap::new(),
+            stack : Vec::new()
+        };
+        for option in strings.iter() {
+            interpolator.expand(*option)?;
+        }
+
+        for option in strings.iter() {
+            let value = CString::new(interpolator.expanded[option].as_str())
+                .map_err(|_| Errors::UnresolvedVariable)?;
+            unsafe {
+                raw::config_setting_set_string(*option, value.as_ptr());
+            }
+        }
+        self.interpolated = true;
+        Ok(true)
+    }
+}
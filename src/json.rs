@@ -0,0 +1,126 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! JSON output of owned values.
+
+use crate::value::Value;
+
+use std::fmt::Write;
+
+/// Indentation of nested JSON elements.
+const INDENT : usize = 2;
+
+// Write JSON string literal.
+pub(crate) fn write_string(out : &mut String, text : &str) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => { out.push_str("\\\""); },
+            '\\' => { out.push_str("\\\\"); },
+            '\n' => { out.push_str("\\n"); },
+            '\r' => { out.push_str("\\r"); },
+            '\t' => { out.push_str("\\t"); },
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            },
+            c => { out.push(c); }
+        }
+    }
+    out.push('"');
+}
+
+// Write value as pretty printed JSON.
+fn write_value(out : &mut String, value : &Value, depth : usize) {
+    match value {
+        Value::Int(val) => { let _ = write!(out, "{}", val); },
+        Value::Float(val) => {
+            if val.is_finite() {
+                let _ = write!(out, "{}", val);
+            } else {
+                out.push_str("null");
+            }
+        },
+        Value::Bool(val) => { let _ = write!(out, "{}", val); },
+        Value::Str(val) => { write_string(out, val); },
+        Value::Array(elements) | Value::List(elements) => {
+            if elements.is_empty() {
+                out.push_str("[]");
+                return;
+            }
+
+            out.push_str("[\n");
+            for (index, element) in elements.iter().enumerate() {
+                if index > 0 {
+                    out.push_str(",\n");
+                }
+                indent(out, depth + 1);
+                write_value(out, element, depth + 1);
+            }
+            out.push('\n');
+            indent(out, depth);
+            out.push(']');
+        },
+        Value::Group(children) => {
+            if children.is_empty() {
+                out.push_str("{}");
+                return;
+            }
+
+            out.push_str("{\n");
+            for (index, (name, child)) in children.iter().enumerate() {
+                if index > 0 {
+                    out.push_str(",\n");
+                }
+                indent(out, depth + 1);
+                write_string(out, name);
+                out.push_str(": ");
+                write_value(out, child, depth + 1);
+            }
+            out.push('\n');
+            indent(out, depth);
+            out.push('}');
+        }
+    }
+}
+
+// Write indentation for nesting depth.
+fn indent(out : &mut String, depth : usize) {
+    for _ in 0..depth * INDENT {
+        out.push(' ');
+    }
+}
+
+// Return value as pretty printed JSON document.
+pub(crate) fn to_string_pretty(value : &Value) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value, 0);
+    out.push('\n');
+    out
+}
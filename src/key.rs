@@ -0,0 +1,138 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Interned setting paths.
+//!
+//! Paths and names passed to lookups and writers are converted to C strings
+//! once and kept in a per thread interner, so repeated use of the same keys
+//! doesn't allocate. `Key` is a handle of interned path, which can be created
+//! ahead of time and used for lookups directly.
+
+use crate::config::{Config, OptionReader};
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::rc::Rc;
+
+/// Maximal number of interned strings, interner is cleared when it is full.
+const INTERNER_CAPACITY : usize = 4096;
+
+thread_local! {
+    static INTERNER : RefCell<HashMap<Rc<str>, Rc<CStr>>> =
+        RefCell::new(HashMap::new());
+}
+
+// Return interned C string of name, None if name contains NUL byte.
+pub(crate) fn intern(name : &str) -> Option<(Rc<str>, Rc<CStr>)> {
+    INTERNER.with(|interner| {
+        let mut interner = interner.borrow_mut();
+        if let Some((name, c_name)) = interner.get_key_value(name) {
+            return Some((name.clone(), c_name.clone()));
+        }
+
+        let c_name : Rc<CStr> = CString::new(name).ok()?.into();
+        if interner.len() >= INTERNER_CAPACITY {
+            interner.clear();
+        }
+        let name : Rc<str> = name.into();
+        interner.insert(name.clone(), c_name.clone());
+        Some((name, c_name))
+    })
+}
+
+// Call f with interned C string of name, None if name contains NUL byte.
+pub(crate) fn with_c_str<R, F>(name : &str, f : F) -> Option<R>
+    where F: FnOnce(&CStr) -> R {
+    intern(name).map(|(_, c_name)| f(&c_name))
+}
+
+/// Pre-created setting path. Cloning is cheap, keys with the same path share
+/// the same string.
+///
+/// # Example
+/// ```
+/// use librustconfig::config::Config;
+/// use librustconfig::key::Key;
+///
+/// let port = Key::new("server.port");
+/// let mut cfg = Config::new();
+/// if cfg.load_from_string("server : { port = 80; };").is_ok() {
+///     assert_eq!(cfg.value_key(&port).unwrap().as_int32(), Some(80));
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Key {
+    path : Rc<str>,
+    // None if path contains NUL byte, such key never matches.
+    c_path : Option<Rc<CStr>>
+}
+
+impl Key {
+
+    /// Create key of dotted setting path.
+    pub fn new<S>(path : S) -> Key
+        where S: AsRef<str> {
+        match intern(path.as_ref()) {
+            Some((path, c_path)) => { Key { path, c_path : Some(c_path) } },
+            None => { Key { path : path.as_ref().into(), c_path : None } }
+        }
+    }
+
+    /// Return dotted setting path.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl Config {
+
+    /// Read value at path of pre-created key.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    /// use librustconfig::key::Key;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("name = \"server\";").is_ok() {
+    ///     let name = Key::new("name");
+    ///     assert_eq!(cfg.value_key(&name).unwrap().as_str(),
+    ///         Some("server"));
+    /// }
+    /// ```
+    pub fn value_key(&self, key : &Key) -> Option<OptionReader<'_>> {
+        key.c_path.as_ref()?;
+        if self.resolve_references {
+            return self.value_resolved(key.path());
+        }
+        self.lookup(key.path()).map(|option| self.reader(Some(option)))
+    }
+}
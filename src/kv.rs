@@ -0,0 +1,624 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Key/value store backends.
+//!
+//! Service discovery stores such as etcd and Consul keep configuration as a
+//! flat list of `prefix/section/option` keys. This module rebuilds a config
+//! tree from such keys. Network backends are available with the `kv` feature.
+
+use crate::config::{Config, Errors, Result};
+use libconfig_sys as raw;
+
+use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::mem;
+
+#[cfg(feature = "kv")]
+use std::time::Duration;
+
+/// Separator between key segments in a flattened key.
+const KEY_SEPARATOR : char = '/';
+
+/// First delay before backend error is retried while watching.
+#[cfg(feature = "kv")]
+const RETRY_DELAY : Duration = Duration::from_secs(1);
+
+/// Maximal delay between retries of backend errors while watching.
+#[cfg(feature = "kv")]
+const MAX_RETRY_DELAY : Duration = Duration::from_secs(60);
+
+/// Values stored under a key prefix.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct KvSnapshot {
+    /// Backend specific modification index of the data.
+    pub index : u64,
+    /// Full keys with their values.
+    pub entries : Vec<(String, String)>
+}
+
+/// Key/value store that can be used as configuration source.
+pub trait KvBackend {
+    /// Read all keys stored under prefix.
+    fn fetch(&self, prefix : &str) -> Result<KvSnapshot>;
+
+    /// Block until keys under prefix may have changed after index and read
+    /// them again.
+    fn wait(&self, prefix : &str, index : u64) -> Result<KvSnapshot>;
+}
+
+/// Key segment, numbers are list indexes.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum Segment {
+    Index(u64),
+    Name(String)
+}
+
+/// Intermediate tree rebuilt from flattened keys.
+enum Node {
+    Leaf(String),
+    Branch(BTreeMap<Segment, Node>)
+}
+
+impl Node {
+
+    // Insert value into tree by key segments.
+    fn insert(&mut self, segments : &[&str], value : &str) -> Result<()> {
+        let children = match self {
+            Node::Branch(children) => { children },
+            Node::Leaf(_) => { return Err(Errors::ParseError) }
+        };
+
+        let segment = match segments[0].parse::<u64>() {
+            Ok(index) => { Segment::Index(index) },
+            Err(_) => { Segment::Name(segments[0].to_string()) }
+        };
+
+        if segments.len() == 1 {
+            if children.contains_key(&segment) {
+                return Err(Errors::ParseError);
+            }
+            children.insert(segment, Node::Leaf(value.to_string()));
+            Ok(())
+        } else {
+            children.entry(segment)
+                .or_insert_with(|| Node::Branch(BTreeMap::new()))
+                .insert(&segments[1..], value)
+        }
+    }
+}
+
+impl Config {
+
+    /// Replace current config with the tree rebuilt from flattened keys.
+    ///
+    /// Keys outside of prefix are ignored, the rest of the key is split by
+    /// `/` into section names. Numeric segments become list elements. Values
+    /// that look like booleans, integers or floats are stored with that type,
+    /// everything else is stored as string. Config is not changed if keys
+    /// can't be loaded.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// let pairs = vec![
+    ///     ("app/server/port", "8080"),
+    ///     ("app/server/hosts/0", "alpha"),
+    ///     ("app/server/hosts/1", "beta")
+    /// ];
+    /// if cfg.load_from_kv_pairs("app/", pairs).is_ok() {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn load_from_kv_pairs<I, K, V>(&mut self, prefix : &str, pairs : I)
+        -> Result<()> where I: IntoIterator<Item = (K, V)>, K: AsRef<str>,
+        V: AsRef<str> {
+        let _guard = self.write_guard()?;
+
+        let mut tree = Node::Branch(BTreeMap::new());
+        for (key, value) in pairs {
+            let key = key.as_ref();
+            if !key.starts_with(prefix) || key.ends_with(KEY_SEPARATOR) {
+                continue;
+            }
+
+            let segments : Vec<&str> = key[prefix.len()..]
+                .split(KEY_SEPARATOR)
+                .filter(|s| !s.is_empty())
+                .collect();
+            if segments.is_empty() {
+                continue;
+            }
+            tree.insert(&segments, value.as_ref())?;
+        }
+
+        let root = raw::config_root_setting(&self.config);
+        if root.is_null() {
+            return Err(Errors::ParseError);
+        }
+
+        // Build settings in separate config with the same settings and swap
+        // it in, so current settings are kept if the tree can't be written.
+        let mut loaded = self.copy_settings();
+        let include_dir = raw::config_get_include_dir(&self.config);
+        if !include_dir.is_null() {
+            unsafe {
+                raw::config_set_include_dir(&mut loaded.config, include_dir);
+            }
+        }
+        let loaded_root = loaded.root_element.ok_or(Errors::ParseError)?;
+        match tree {
+            Node::Branch(children) => {
+                for (segment, node) in children.iter() {
+                    match segment {
+                        Segment::Name(name) => {
+                            write_node(loaded_root, Some(name), node)?;
+                        },
+                        Segment::Index(_) => {
+                            return Err(Errors::ParseError)
+                        }
+                    }
+                }
+            },
+            Node::Leaf(_) => { return Err(Errors::ParseError) }
+        }
+
+        self.clear_reads();
+        self.origins.borrow_mut().clear();
+        self.interpolated = false;
+        mem::swap(&mut self.config, &mut loaded.config);
+        loaded.root_element = Some(root);
+        self.root_element = Some(loaded_root);
+        Ok(())
+    }
+
+    /// Replace current config with keys read from key/value store.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use librustconfig::config::Config;
+    /// use librustconfig::kv::Consul;
+    ///
+    /// let mut cfg = Config::new();
+    /// let consul = Consul::new("http://127.0.0.1:8500");
+    /// if cfg.load_from_kv(&consul, "myapp/").is_ok() {
+    ///     // ...
+    /// }
+    /// ```
+    #[cfg(feature = "kv")]
+    pub fn load_from_kv<B>(&mut self, backend : &B, prefix : &str)
+        -> Result<()> where B: KvBackend {
+        let snapshot = backend.fetch(prefix)?;
+        self.load_from_kv_pairs(prefix, snapshot.entries)
+    }
+}
+
+// Create config setting for tree node.
+fn write_node(parent : *mut raw::config_setting_t, name : Option<&str>,
+    node : &Node) -> Result<()> {
+
+    let name = match name {
+        Some(n) => {
+            Some(CString::new(n).map_err(|_| Errors::InvalidString)?)
+        },
+        None => { None }
+    };
+    let name_ptr = match &name {
+        Some(n) => { n.as_ptr() },
+        None => { std::ptr::null() }
+    };
+
+    match node {
+        Node::Leaf(value) => { write_scalar(parent, name_ptr, value) },
+        Node::Branch(children) => {
+            let is_list = children.keys()
+                .all(|s| matches!(s, Segment::Index(_)));
+            let value_type = if is_list {
+                raw::CONFIG_TYPE_LIST
+            } else {
+                raw::CONFIG_TYPE_GROUP
+            };
+
+            let option = unsafe {
+                raw::config_setting_add(parent, name_ptr, value_type as i32)
+            };
+            if option.is_null() {
+                return Err(Errors::ParseError);
+            }
+
+            for (segment, child) in children.iter() {
+                match segment {
+                    Segment::Index(_) => { write_node(option, None, child)? },
+                    Segment::Name(n) => {
+                        if is_list {
+                            return Err(Errors::ParseError);
+                        }
+                        write_node(option, Some(n), child)?
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+// Create scalar setting with type guessed from value text.
+fn write_scalar(parent : *mut raw::config_setting_t, name : *const i8,
+    value : &str) -> Result<()> {
+
+    let is_number = !value.is_empty() && value.chars()
+        .all(|c| c.is_ascii_digit() || "+-.eE".contains(c));
+
+    let result = unsafe {
+        if value.eq_ignore_ascii_case("true") ||
+            value.eq_ignore_ascii_case("false") {
+            let option = raw::config_setting_add(parent, name,
+                raw::CONFIG_TYPE_BOOL as i32);
+            if option.is_null() {
+                return Err(Errors::ParseError);
+            }
+            let val = if value.eq_ignore_ascii_case("true") {
+                raw::CONFIG_TRUE
+            } else {
+                raw::CONFIG_FALSE
+            };
+            raw::config_setting_set_bool(option, val)
+        } else if let (true, Ok(val)) = (is_number, value.parse::<i64>()) {
+            if val >= i32::MIN as i64 && val <= i32::MAX as i64 {
+                let option = raw::config_setting_add(parent, name,
+                    raw::CONFIG_TYPE_INT as i32);
+                if option.is_null() {
+                    return Err(Errors::ParseError);
+                }
+                raw::config_setting_set_int(option, val as i32)
+            } else {
+                let option = raw::config_setting_add(parent, name,
+                    raw::CONFIG_TYPE_INT64 as i32);
+                if option.is_null() {
+                    return Err(Errors::ParseError);
+                }
+                raw::config_setting_set_int64(option, val)
+            }
+        } else if let (true, Ok(val)) = (is_number, value.parse::<f64>()) {
+            let option = raw::config_setting_add(parent, name,
+                raw::CONFIG_TYPE_FLOAT as i32);
+            if option.is_null() {
+                return Err(Errors::ParseError);
+            }
+            raw::config_setting_set_float(option, val)
+        } else {
+            let option = raw::config_setting_add(parent, name,
+                raw::CONFIG_TYPE_STRING as i32);
+            if option.is_null() {
+                return Err(Errors::ParseError);
+            }
+            let val = CString::new(value)
+                .map_err(|_| Errors::InvalidString)?;
+            raw::config_setting_set_string(option, val.as_ptr())
+        }
+    };
+
+    if result == raw::CONFIG_TRUE {
+        Ok(())
+    } else {
+        Err(Errors::ParseError)
+    }
+}
+
+/// Watch keys under prefix and call callback with the rebuilt config every
+/// time they change. Watching stops when callback returns false or changed
+/// keys can't be loaded. Backend errors while waiting for changes are
+/// retried, delay between retries doubles up to a minute.
+///
+/// # Example
+/// ```no_run
+/// use librustconfig::kv::{self, Etcd};
+///
+/// let etcd = Etcd::new("http://127.0.0.1:2379");
+/// let _ = kv::watch(&etcd, "myapp/", |cfg| {
+///     /* ... */
+///     true
+/// });
+/// ```
+#[cfg(feature = "kv")]
+pub fn watch<B, F>(backend : &B, prefix : &str, mut callback : F) -> Result<()>
+    where B: KvBackend, F: FnMut(Config) -> bool {
+
+    let mut snapshot = backend.fetch(prefix)?;
+    let mut delay = RETRY_DELAY;
+    loop {
+        let next = match backend.wait(prefix, snapshot.index) {
+            Ok(next) => {
+                delay = RETRY_DELAY;
+                next
+            },
+            Err(Errors::BackendError) => {
+                std::thread::sleep(delay);
+                delay = std::cmp::min(delay * 2, MAX_RETRY_DELAY);
+                continue;
+            },
+            Err(error) => { return Err(error) }
+        };
+        if next.entries != snapshot.entries {
+            let mut cfg = Config::new();
+            cfg.load_from_kv_pairs(prefix, next.entries.iter()
+                .map(|(k, v)| (k.as_str(), v.as_str())))?;
+
+            if !callback(cfg) {
+                return Ok(());
+            }
+        }
+        snapshot = next;
+    }
+}
+
+// Decode base64 encoded value returned by backend.
+#[cfg(feature = "kv")]
+fn decode_base64(value : &str) -> Result<String> {
+    use base64::Engine;
+
+    let bytes = base64::engine::general_purpose::STANDARD.decode(value)
+        .map_err(|_| Errors::BackendError)?;
+    String::from_utf8(bytes).map_err(|_| Errors::BackendError)
+}
+
+/// Consul key/value store backend.
+#[cfg(feature = "kv")]
+pub struct Consul {
+    address : String,
+    token : Option<String>,
+    wait : Duration
+}
+
+#[cfg(feature = "kv")]
+impl Consul {
+
+    /// Constructor.
+    /// Create backend for Consul agent address.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::kv::Consul;
+    ///
+    /// let consul = Consul::new("http://127.0.0.1:8500");
+    /// ```
+    pub fn new<S>(address : S) -> Consul where S: Into<String> {
+        Consul {
+            address : address.into().trim_end_matches('/').to_string(),
+            token : None,
+            wait : Duration::from_secs(300)
+        }
+    }
+
+    /// Set ACL token sent with every request.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::kv::Consul;
+    ///
+    /// let consul = Consul::new("http://127.0.0.1:8500").token("secret");
+    /// ```
+    pub fn token<S>(mut self, token : S) -> Consul where S: Into<String> {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Set maximum time blocking query waits for changes.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::kv::Consul;
+    /// use std::time::Duration;
+    ///
+    /// let consul = Consul::new("http://127.0.0.1:8500")
+    ///     .wait_time(Duration::from_secs(60));
+    /// ```
+    pub fn wait_time(mut self, wait : Duration) -> Consul {
+        self.wait = wait;
+        self
+    }
+
+    // Run recursive key read, optionally as blocking query.
+    fn read(&self, prefix : &str, index : Option<u64>) -> Result<KvSnapshot> {
+        let mut url = format!("{}/v1/kv/{}?recurse=true", self.address,
+            prefix.trim_start_matches(KEY_SEPARATOR));
+        if let Some(index) = index {
+            url.push_str(&format!("&index={}&wait={}s", index,
+                self.wait.as_secs()));
+        }
+
+        let mut request = ureq::get(&url)
+            .timeout(self.wait + Duration::from_secs(30));
+        if let Some(token) = &self.token {
+            request = request.set("X-Consul-Token", token);
+        }
+
+        let response = match request.call() {
+            Ok(response) => { response },
+            Err(ureq::Error::Status(404, response)) => {
+                return Ok(KvSnapshot {
+                    index : consul_index(&response),
+                    entries : Vec::new()
+                });
+            },
+            Err(_) => { return Err(Errors::BackendError) }
+        };
+
+        let index = consul_index(&response);
+        let body : serde_json::Value = response.into_json()
+            .map_err(|_| Errors::BackendError)?;
+
+        let mut entries = Vec::new();
+        for item in body.as_array().ok_or(Errors::BackendError)? {
+            let key = item["Key"].as_str().ok_or(Errors::BackendError)?;
+            let value = match item["Value"].as_str() {
+                Some(value) => { decode_base64(value)? },
+                None => { continue }
+            };
+            entries.push((format!("{}{}",
+                if prefix.starts_with(KEY_SEPARATOR) { "/" } else { "" },
+                key), value));
+        }
+
+        Ok(KvSnapshot {
+            index,
+            entries
+        })
+    }
+}
+
+// Read modification index of Consul response.
+#[cfg(feature = "kv")]
+fn consul_index(response : &ureq::Response) -> u64 {
+    response.header("X-Consul-Index")
+        .and_then(|index| index.parse().ok())
+        .unwrap_or(0)
+}
+
+#[cfg(feature = "kv")]
+impl KvBackend for Consul {
+    fn fetch(&self, prefix : &str) -> Result<KvSnapshot> {
+        self.read(prefix, None)
+    }
+
+    fn wait(&self, prefix : &str, index : u64) -> Result<KvSnapshot> {
+        self.read(prefix, Some(index))
+    }
+}
+
+/// etcd v3 key/value store backend, uses JSON gRPC gateway.
+#[cfg(feature = "kv")]
+pub struct Etcd {
+    address : String,
+    poll : Duration
+}
+
+#[cfg(feature = "kv")]
+impl Etcd {
+
+    /// Constructor.
+    /// Create backend for etcd endpoint address.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::kv::Etcd;
+    ///
+    /// let etcd = Etcd::new("http://127.0.0.1:2379");
+    /// ```
+    pub fn new<S>(address : S) -> Etcd where S: Into<String> {
+        Etcd {
+            address : address.into().trim_end_matches('/').to_string(),
+            poll : Duration::from_secs(5)
+        }
+    }
+
+    /// Set interval between reads while watching for changes. JSON gateway
+    /// has no blocking reads, so waiting backend polls until revision of
+    /// the store passes the last read one.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::kv::Etcd;
+    /// use std::time::Duration;
+    ///
+    /// let etcd = Etcd::new("http://127.0.0.1:2379")
+    ///     .poll_interval(Duration::from_secs(1));
+    /// ```
+    pub fn poll_interval(mut self, poll : Duration) -> Etcd {
+        self.poll = poll;
+        self
+    }
+}
+
+#[cfg(feature = "kv")]
+impl KvBackend for Etcd {
+    fn fetch(&self, prefix : &str) -> Result<KvSnapshot> {
+        use base64::Engine;
+        let engine = &base64::engine::general_purpose::STANDARD;
+
+        // Range end is the prefix with last byte incremented.
+        let mut range_end = prefix.as_bytes().to_vec();
+        while let Some(last) = range_end.pop() {
+            if last < 0xff {
+                range_end.push(last + 1);
+                break;
+            }
+        }
+        if range_end.is_empty() {
+            range_end.push(0);
+        }
+
+        let body = serde_json::json!({
+            "key" : engine.encode(prefix),
+            "range_end" : engine.encode(&range_end)
+        });
+
+        let response : serde_json::Value = ureq::post(&format!("{}/v3/kv/range",
+            self.address))
+            .send_json(body)
+            .map_err(|_| Errors::BackendError)?
+            .into_json()
+            .map_err(|_| Errors::BackendError)?;
+
+        // Store revision changes on every write, including deletes, which
+        // don't show up in revisions of remaining keys.
+        let index = response["header"]["revision"].as_str()
+            .and_then(|r| r.parse().ok())
+            .unwrap_or(0);
+        let mut entries = Vec::new();
+        if let Some(kvs) = response["kvs"].as_array() {
+            for item in kvs {
+                let key = decode_base64(item["key"].as_str()
+                    .ok_or(Errors::BackendError)?)?;
+                let value = decode_base64(item["value"].as_str()
+                    .unwrap_or(""))?;
+                entries.push((key, value));
+            }
+        }
+
+        Ok(KvSnapshot {
+            index,
+            entries
+        })
+    }
+
+    fn wait(&self, prefix : &str, index : u64) -> Result<KvSnapshot> {
+        loop {
+            std::thread::sleep(self.poll);
+            let snapshot = self.fetch(prefix)?;
+            // Without revision every read may be a change.
+            if snapshot.index != index || snapshot.index == 0 {
+                return Ok(snapshot);
+            }
+        }
+    }
+}
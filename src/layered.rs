@@ -0,0 +1,231 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Layered configuration.
+//!
+//! LayeredConfig stacks configs, typically built-in defaults, system file,
+//! user file and runtime overrides, and resolves lookups from the top layer
+//! down. Like `Config::load_layers`, groups are merged member by member and
+//! any other setting of upper layer hides the same setting below. Layers
+//! stay separate, so each of them can be reloaded or modified on its own.
+
+use crate::config::{Config, OptionReader, Result};
+use crate::source::ConfigSource;
+use crate::value::Value;
+
+use std::path::Path;
+
+/// Named config layer.
+struct Layer {
+    name : String,
+    config : Config
+}
+
+/// Stack of configs resolved top-down.
+///
+/// # Example
+/// ```
+/// use librustconfig::config::Config;
+/// use librustconfig::layered::LayeredConfig;
+/// use librustconfig::source::ConfigSource;
+/// use std::path::Path;
+///
+/// let mut cfg = LayeredConfig::new();
+/// if cfg.push_string("defaults", "port = 80; host = \"localhost\";")
+///     .is_ok() {
+///     cfg.push_optional_file("user", Path::new("user.cfg")).unwrap();
+///     cfg.push("overrides", Config::new());
+///     cfg.layer_mut("overrides").unwrap().set("port", 8080).unwrap();
+///
+///     assert_eq!(cfg.get_int32("port"), Some(8080));
+///     assert_eq!(cfg.layer_of("port"), Some("overrides"));
+/// }
+/// ```
+#[derive(Default)]
+pub struct LayeredConfig {
+    // Bottom layer first.
+    layers : Vec<Layer>
+}
+
+// Merge upper value over lower one.
+fn merge_over(lower : &mut Value, upper : Value) {
+    match (lower, upper) {
+        (Value::Group(lower), Value::Group(upper)) => {
+            for (name, value) in upper {
+                match lower.get_mut(&name) {
+                    Some(member) => { merge_over(member, value) },
+                    None => { lower.insert(name, value); }
+                }
+            }
+        },
+        (lower, upper) => { *lower = upper }
+    }
+}
+
+impl LayeredConfig {
+
+    /// Constructor.
+    /// Create config without layers.
+    pub fn new() -> LayeredConfig {
+        LayeredConfig { layers : Vec::new() }
+    }
+
+    /// Put config on top of the stack.
+    pub fn push<S>(&mut self, name : S, config : Config)
+        where S: Into<String> {
+        self.layers.push(Layer { name : name.into(), config });
+    }
+
+    /// Parse string, usually built-in defaults, and put it on top of the
+    /// stack.
+    pub fn push_string<S>(&mut self, name : S, config_string : &str)
+        -> Result<()> where S: Into<String> {
+        let mut config = Config::new();
+        config.load_from_string(config_string)?;
+        self.push(name, config);
+        Ok(())
+    }
+
+    /// Load file and put it on top of the stack.
+    pub fn push_file<S>(&mut self, name : S, file_name : &Path) -> Result<()>
+        where S: Into<String> {
+        let mut config = Config::new();
+        config.load_from_file(file_name)?;
+        self.push(name, config);
+        Ok(())
+    }
+
+    /// Load file and put it on top of the stack. Missing file is pushed as
+    /// empty layer, so it can be filled later.
+    pub fn push_optional_file<S>(&mut self, name : S, file_name : &Path)
+        -> Result<()> where S: Into<String> {
+        if !file_name.exists() {
+            self.push(name, Config::new());
+            return Ok(());
+        }
+        self.push_file(name, file_name)
+    }
+
+    /// Return config of layer with name.
+    pub fn layer(&self, name : &str) -> Option<&Config> {
+        self.layers.iter().rev().find(|layer| layer.name == name)
+            .map(|layer| &layer.config)
+    }
+
+    /// Return mutable config of layer with name, e.g. to reload it.
+    pub fn layer_mut(&mut self, name : &str) -> Option<&mut Config> {
+        self.layers.iter_mut().rev().find(|layer| layer.name == name)
+            .map(|layer| &mut layer.config)
+    }
+
+    /// Return names of layers, bottom layer first.
+    pub fn layer_names(&self) -> impl Iterator<Item = &str> {
+        self.layers.iter().map(|layer| layer.name.as_str())
+    }
+
+    /// Return name of the top layer containing setting.
+    pub fn layer_of(&self, path : &str) -> Option<&str> {
+        self.layers.iter().rev()
+            .find(|layer| layer.config.value(path).is_some())
+            .map(|layer| layer.name.as_str())
+    }
+
+    // Return the top layer containing setting.
+    fn top(&self, path : &str) -> Option<&Config> {
+        self.layers.iter().rev().map(|layer| &layer.config)
+            .find(|config| config.value(path).is_some())
+    }
+
+    /// Return setting of the top layer containing it. Group members defined
+    /// only in lower layers aren't visible through it, use `get_value` to
+    /// read merged group.
+    pub fn value(&self, path : &str) -> Option<OptionReader<'_>> {
+        self.layers.iter().rev().find_map(|layer| layer.config.value(path))
+    }
+
+    /// Merge all layers into one config.
+    pub fn flatten(&self) -> Result<Config> {
+        let mut config = Config::new();
+        if let Some(Value::Group(members)) = self.get_value("") {
+            for (name, value) in members {
+                config.set(name, value)?;
+            }
+        }
+        Ok(config)
+    }
+}
+
+impl ConfigSource for LayeredConfig {
+
+    fn get_value(&self, path : &str) -> Option<Value> {
+        // Values hiding each other, top first. Only groups let lower layers
+        // through.
+        let mut values = Vec::new();
+        for layer in self.layers.iter().rev() {
+            if let Some(value) = layer.config.get_value(path) {
+                let is_group = matches!(value, Value::Group(_));
+                values.push(value);
+                if !is_group {
+                    break;
+                }
+            }
+        }
+
+        let mut merged = values.pop()?;
+        while let Some(value) = values.pop() {
+            merge_over(&mut merged, value);
+        }
+        Some(merged)
+    }
+
+    fn contains(&self, path : &str) -> bool {
+        self.value(path).is_some()
+    }
+
+    fn get_int32(&self, path : &str) -> Option<i32> {
+        self.top(path)?.get_int32(path)
+    }
+
+    fn get_int64(&self, path : &str) -> Option<i64> {
+        self.top(path)?.get_int64(path)
+    }
+
+    fn get_float64(&self, path : &str) -> Option<f64> {
+        self.top(path)?.get_float64(path)
+    }
+
+    fn get_bool(&self, path : &str) -> Option<bool> {
+        self.top(path)?.get_bool(path)
+    }
+
+    fn get_string(&self, path : &str) -> Option<String> {
+        self.top(path)?.get_string(path)
+    }
+}
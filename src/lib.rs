@@ -28,7 +28,59 @@
 /*                                                                            */
 /******************************************************************************/
 
+mod append;
+mod array;
+mod atomic;
+pub mod build;
+mod bulk;
+mod cache;
+mod clone;
+mod compare;
 pub mod config;
+#[cfg(feature = "serde")]
+pub mod de;
+pub mod dedup;
+pub mod diff;
+mod encoded;
+mod encoding;
+mod environment;
+mod format;
+mod freeze;
+pub mod index;
+mod int_format;
+mod interpolate;
+mod json;
+pub mod key;
+pub mod kv;
+pub mod layered;
+mod merge;
+#[cfg(all(feature = "mmap", unix))]
+mod mmap;
+mod order;
+pub mod parsed;
+mod path;
+pub mod query;
+mod reference;
+mod reload;
+#[cfg(feature = "watch")]
+pub mod reloadable;
+pub mod schema;
+#[cfg(feature = "serde")]
+pub mod ser;
+pub mod shared;
+pub mod snapshot;
+pub mod source;
+pub mod stats;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+pub mod testing;
+#[cfg(feature = "toml")]
+pub mod toml_format;
+mod track;
+pub mod typed;
+mod update;
+pub mod value;
+pub mod watch;
 
 #[cfg(test)]
 mod test;
\ No newline at end of file
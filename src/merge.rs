@@ -0,0 +1,423 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Merging of config fragments.
+//!
+//! Groups are merged member by member, any other setting of later fragment
+//! replaces setting with the same name. Source file and layer of every
+//! merged setting are recorded and available through `Config::origin`.
+//! With `parallel` feature fragments are parsed in parallel and merged in
+//! order afterwards.
+
+use crate::config::{Config, Errors, MergeStrategy, Origin, Result};
+use crate::path;
+use crate::value;
+use libconfig_sys as raw;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Merged fragment.
+struct Layer<'a> {
+    index : usize,
+    file : &'a Path
+}
+
+// Return origin of setting read from layer.
+fn setting_origin(option : *mut raw::config_setting_t, layer : &Layer)
+    -> Origin {
+    let file = raw::config_setting_source_file(option);
+    Origin {
+        file : if file.is_null() {
+            layer.file.to_path_buf()
+        } else {
+            crate::config::path_from_c(unsafe { CStr::from_ptr(file) })
+        },
+        layer : layer.index,
+        line : raw::config_setting_source_line(option)
+    }
+}
+
+// Record origins of setting and all its children.
+fn record_origins(origins : &mut HashMap<String, Origin>,
+    option : *mut raw::config_setting_t, option_path : &str, layer : &Layer) {
+
+    origins.insert(option_path.to_string(), setting_origin(option, layer));
+
+    let group = raw::config_setting_is_group(option) == raw::CONFIG_TRUE;
+    for index in 0..unsafe { raw::config_setting_length(option) } {
+        let child = unsafe {
+            raw::config_setting_get_elem(option, index as u32)
+        };
+        if child.is_null() {
+            continue;
+        }
+
+        let name = raw::config_setting_name(child);
+        let segment = if group && !name.is_null() {
+            unsafe { CStr::from_ptr(name) }.to_string_lossy().into_owned()
+        } else {
+            format!("[{}]", index)
+        };
+        record_origins(origins, child, &path::join(option_path, &segment),
+            layer);
+    }
+}
+
+// Forget origins of setting and all its children.
+pub(crate) fn forget_origins(origins : &mut HashMap<String, Origin>,
+    option_path : &str) {
+    if origins.is_empty() {
+        return;
+    }
+
+    let prefix = format!("{}{}", option_path, path::PATH_SEPARATOR);
+    origins.retain(|key, _| key != option_path && !key.starts_with(&prefix));
+}
+
+// Merge all members of source group into target group.
+fn merge_group(source : *mut raw::config_setting_t,
+    target : *mut raw::config_setting_t, target_path : &str, layer : &Layer,
+    origins : &mut HashMap<String, Origin>) -> Result<()> {
+
+    for index in 0..unsafe { raw::config_setting_length(source) } {
+        let child = unsafe {
+            raw::config_setting_get_elem(source, index as u32)
+        };
+        if child.is_null() {
+            return Err(Errors::WriteError);
+        }
+
+        let name_ptr = raw::config_setting_name(child);
+        if name_ptr.is_null() {
+            return Err(Errors::WriteError);
+        }
+        let name = unsafe { CStr::from_ptr(name_ptr) }.to_str()
+            .map_err(|_| Errors::WriteError)?;
+        let child_path = path::join(target_path, name);
+        let existing = unsafe {
+            raw::config_setting_get_member(target, name_ptr)
+        };
+
+        if !existing.is_null() {
+            if raw::config_setting_is_group(child) == raw::CONFIG_TRUE &&
+                raw::config_setting_is_group(existing) == raw::CONFIG_TRUE {
+                merge_group(child, existing, &child_path, layer, origins)?;
+                continue;
+            }
+            if unsafe { raw::config_setting_remove(target, name_ptr) } !=
+                raw::CONFIG_TRUE {
+                return Err(Errors::WriteError);
+            }
+            forget_origins(origins, &child_path);
+        }
+
+        value::copy(child, target, name)?;
+        record_origins(origins, child, &child_path, layer);
+    }
+    Ok(())
+}
+
+// Return true if elements of source collection can be appended to target.
+fn can_append(source : *mut raw::config_setting_t,
+    target : *mut raw::config_setting_t) -> bool {
+    let source_type = raw::config_setting_type(source);
+    if source_type != raw::config_setting_type(target) {
+        return false;
+    }
+    match source_type as i16 {
+        raw::CONFIG_TYPE_LIST => { true },
+        raw::CONFIG_TYPE_ARRAY => {
+            let first = |array| unsafe {
+                raw::config_setting_get_elem(array, 0)
+            };
+            let (source, target) = (first(source), first(target));
+            source.is_null() || target.is_null() ||
+                raw::config_setting_type(source) ==
+                raw::config_setting_type(target)
+        },
+        _ => { false }
+    }
+}
+
+// Merge all members of source group into target group using strategy.
+fn merge_with(source : *mut raw::config_setting_t,
+    target : *mut raw::config_setting_t, target_path : &str,
+    strategy : MergeStrategy, origins : &mut HashMap<String, Origin>)
+    -> Result<()> {
+
+    for index in 0..unsafe { raw::config_setting_length(source) } {
+        let child = unsafe {
+            raw::config_setting_get_elem(source, index as u32)
+        };
+        if child.is_null() {
+            return Err(Errors::WriteError);
+        }
+
+        let name_ptr = raw::config_setting_name(child);
+        if name_ptr.is_null() {
+            return Err(Errors::WriteError);
+        }
+        let name = unsafe { CStr::from_ptr(name_ptr) }.to_str()
+            .map_err(|_| Errors::WriteError)?;
+        let child_path = path::join(target_path, name);
+        let existing = unsafe {
+            raw::config_setting_get_member(target, name_ptr)
+        };
+
+        if !existing.is_null() {
+            if raw::config_setting_is_group(child) == raw::CONFIG_TRUE &&
+                raw::config_setting_is_group(existing) == raw::CONFIG_TRUE {
+                merge_with(child, existing, &child_path, strategy, origins)?;
+                continue;
+            }
+            match strategy {
+                MergeStrategy::KeepExisting => { continue },
+                MergeStrategy::AppendArrays if can_append(child, existing) => {
+                    value::copy_children(child, existing)?;
+                    continue;
+                },
+                _ => {}
+            }
+            if unsafe { raw::config_setting_remove(target, name_ptr) } !=
+                raw::CONFIG_TRUE {
+                return Err(Errors::WriteError);
+            }
+        }
+
+        forget_origins(origins, &child_path);
+        value::copy(child, target, name)?;
+    }
+    Ok(())
+}
+
+// Parse fragment files in order.
+#[cfg(not(feature = "parallel"))]
+fn parse_layers(files : &[&Path]) -> Result<Vec<Config>> {
+    files.iter().map(|file_name| {
+        let mut fragment = Config::new();
+        fragment.load_from_file(file_name).map(|_| fragment)
+    }).collect()
+}
+
+/// Fragment parsed on worker thread.
+#[cfg(feature = "parallel")]
+struct Fragment(Config);
+
+// Fragment config is created and parsed by single worker thread and is not
+// shared with any other config, so it can be handed over to merging thread.
+#[cfg(feature = "parallel")]
+unsafe impl Send for Fragment {}
+
+// Parse fragment files in parallel, each into its own config.
+#[cfg(feature = "parallel")]
+fn parse_layers(files : &[&Path]) -> Result<Vec<Config>> {
+    let fragments : Result<Vec<Fragment>> = files.par_iter()
+        .map(|file_name| {
+            let mut fragment = Config::new();
+            fragment.load_from_file(file_name).map(|_| Fragment(fragment))
+        }).collect();
+    Ok(fragments?.into_iter().map(|fragment| fragment.0).collect())
+}
+
+// Return true if file name matches pattern with `*` and `?` wildcards.
+fn glob_match(pattern : &[char], name : &[char]) -> bool {
+    match pattern.split_first() {
+        None => { name.is_empty() },
+        Some(('*', rest)) => {
+            (0..=name.len()).any(|skip| glob_match(rest, &name[skip..]))
+        },
+        Some((expected, rest)) => {
+            match name.split_first() {
+                Some((c, tail)) => {
+                    (*expected == '?' || expected == c) &&
+                        glob_match(rest, tail)
+                },
+                None => { false }
+            }
+        }
+    }
+}
+
+impl Config {
+
+    /// Load files in order and merge them into one config, replacing current
+    /// one. Groups are merged, other settings of later files replace earlier
+    /// ones. Current config is unchanged if any file can't be parsed. With
+    /// `parallel` feature files are parsed in parallel before merging.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    /// use std::path::Path;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_layers(&[Path::new("defaults.cfg"), Path::new("site.cfg")])
+    ///     .is_ok() {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn load_layers<P>(&mut self, files : &[P]) -> Result<()>
+        where P: AsRef<Path> {
+        let _guard = self.write_guard()?;
+
+        let files : Vec<&Path> = files.iter().map(|f| f.as_ref()).collect();
+        let fragments = parse_layers(&files)?;
+
+        let merged = Config::new();
+        let merged_root = merged.root_element.ok_or(Errors::WriteError)?;
+        let mut origins = HashMap::new();
+        for (index, (file_name, fragment)) in files.iter()
+            .zip(fragments.iter()).enumerate() {
+            let layer = Layer {
+                index,
+                file : file_name
+            };
+            merge_group(fragment.root_element.ok_or(Errors::ParseError)?,
+                merged_root, "", &layer, &mut origins)?;
+        }
+
+        let root = raw::config_root_setting(&self.config);
+        if root.is_null() {
+            return Err(Errors::WriteError);
+        }
+
+        self.clear_reads();
+        self.clear_settings();
+        self.root_element = Some(root);
+        *self.origins.borrow_mut() = origins;
+        self.interpolated = false;
+        value::copy_children(merged_root, root)
+    }
+
+    /// Copy settings of other config into this one. Groups are merged
+    /// member by member, settings present in both configs are resolved by
+    /// strategy. Config may be partially merged if an error occurs.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::{Config, MergeStrategy};
+    ///
+    /// let mut site = Config::new();
+    /// let mut local = Config::new();
+    /// if site.load_from_string("port = 80; hosts = [ \"a\" ];").is_ok() &&
+    ///     local.load_from_string("port = 8080; hosts = [ \"b\" ];").is_ok() {
+    ///     assert!(site.merge(&local, MergeStrategy::AppendArrays).is_ok());
+    ///     assert_eq!(site.value("port").unwrap().as_int32(), Some(8080));
+    ///     assert_eq!(site.value("hosts").unwrap().len(), Some(2));
+    /// }
+    /// ```
+    pub fn merge(&mut self, other : &Config, strategy : MergeStrategy)
+        -> Result<()> {
+        let _guard = self.write_guard()?;
+        let target = self.root_element.ok_or(Errors::WriteError)?;
+        let source = other.root_element.ok_or(Errors::ElementNotExists)?;
+        merge_with(source, target, "", strategy,
+            &mut self.origins.borrow_mut())
+    }
+
+    /// Load all files in directory matching pattern in lexical order and
+    /// merge them into one config like `load_layers`. Pattern supports `*`
+    /// and `?` wildcards.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    /// use std::path::Path;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_dir(Path::new("conf.d"), "*.cfg").is_ok() {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn load_dir(&mut self, dir : &Path, pattern : &str) -> Result<()> {
+        let pattern : Vec<char> = pattern.chars().collect();
+        let mut files = Vec::new();
+        for entry in fs::read_dir(dir).map_err(|_| Errors::FileNotExists)? {
+            let entry = entry.map_err(|_| Errors::FileNotExists)?;
+            let name = match entry.file_name().into_string() {
+                Ok(name) => { name },
+                Err(_) => { continue }
+            };
+
+            let name_chars : Vec<char> = name.chars().collect();
+            if entry.path().is_file() && glob_match(&pattern, &name_chars) {
+                files.push((name, entry.path()));
+            }
+        }
+        files.sort();
+
+        let files : Vec<PathBuf> = files.into_iter()
+            .map(|(_, file_name)| file_name).collect();
+        self.load_layers(&files)
+    }
+
+    /// Return source file, layer and line the setting at path was read from.
+    /// Settings merged by `load_layers` or `load_dir` report their layer,
+    /// settings of config loaded from single file report layer 0. Returns
+    /// None for settings not read from file.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    /// use std::path::Path;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_dir(Path::new("conf.d"), "*.cfg").is_ok() {
+    ///     if let Some(origin) = cfg.origin("server.port") {
+    ///         println!("set in {:?} line {}", origin.file, origin.line);
+    ///     }
+    /// }
+    /// ```
+    pub fn origin<S>(&self, path : S) -> Option<Origin>
+        where S: AsRef<str> {
+        let option = self.value(path)?.element?;
+        if let Some(origin) = self.origins.borrow()
+            .get(&path::setting_path(option)) {
+            return Some(origin.clone());
+        }
+
+        let file = raw::config_setting_source_file(option);
+        if file.is_null() {
+            return None;
+        }
+        Some(Origin {
+            file : crate::config::path_from_c(unsafe {
+                CStr::from_ptr(file)
+            }),
+            layer : 0,
+            line : raw::config_setting_source_line(option)
+        })
+    }
+}
@@ -0,0 +1,97 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Memory mapped config loading.
+//!
+//! Config file is mapped into memory and parsed from the mapping directly,
+//! without copying file content to heap first. Mapping is read as a stream
+//! opened by `fmemopen`, so the module is only built on Unix.
+
+use crate::config::{Config, Errors, Result};
+use crate::encoding;
+use libconfig_sys as raw;
+
+use memmap2::Mmap;
+use std::fs::File;
+use std::path::Path;
+
+impl Config {
+
+    /// Load config file through memory mapping and parse it. Useful for
+    /// very large files, content is never copied to heap. Files with byte
+    /// order mark and UTF-16 files are transcoded like in `load_from_file`,
+    /// which copies them. Relative `@include` paths are resolved against
+    /// `include_dir` and settings don't record their source file. Available
+    /// on Unix only.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    /// use std::path::Path;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_file_mmap(Path::new("large.cfg")).is_ok() {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn load_from_file_mmap(&mut self, file_name : &Path) -> Result<()> {
+        let file = File::open(file_name).map_err(|_| Errors::FileNotExists)?;
+        let length = file.metadata().map_err(|_| Errors::FileNotExists)?
+            .len();
+        if length == 0 {
+            return self.load_from_string("");
+        }
+
+        // File is only read through the mapping while it's parsed below.
+        let map = unsafe { Mmap::map(&file) }
+            .map_err(|_| Errors::FileNotExists)?;
+        if encoding::needs_transcoding(&map) {
+            let result = self.load_from_string(encoding::decode(&map)?);
+            self.set_error_file(file_name);
+            return result;
+        }
+
+        let _guard = self.write_guard()?;
+        let result = unsafe {
+            let stream = libc::fmemopen(map.as_ptr() as *mut libc::c_void,
+                map.len(), b"r\0".as_ptr() as *const libc::c_char);
+            if stream.is_null() {
+                return Err(Errors::FileNotExists);
+            }
+            let result = raw::config_read(&mut self.config, stream);
+            libc::fclose(stream);
+            result
+        };
+
+        let result = self.parsed(result, encoding::line_ending(&map));
+        self.set_error_file(file_name);
+        result
+    }
+}
@@ -0,0 +1,92 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Order of settings.
+//!
+//! Members of groups and elements of collections are kept in insertion
+//! order: loaded settings in document order, new settings after existing
+//! ones. Iteration, `save_to_string` and `save_to_file` follow this order,
+//! so settings can be positioned explicitly with `move_child_to`.
+
+use crate::config::{Errors, OptionReader, OptionWriter, Result};
+use crate::freeze;
+use crate::index::ReaderIndex;
+use libconfig_sys as raw;
+
+use std::slice;
+
+impl<'a> OptionWriter<'a> {
+
+    /// Move direct child, given by name or index, to new position in
+    /// current group, array or list. Settings between old and new position
+    /// are shifted by one.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { host = \"a\"; port = 80; };")
+    ///     .is_ok() {
+    ///     assert!(cfg.root().create_section("log").is_some());
+    ///     assert!(cfg.root().move_child_to("log", 0).is_ok());
+    ///     assert!(cfg.save_to_string().unwrap().starts_with("log :"));
+    /// }
+    /// ```
+    pub fn move_child_to<I>(&self, child : I, new_index : usize) -> Result<()>
+        where I: ReaderIndex {
+        let element = self.element.ok_or(Errors::ElementNotExists)?;
+        let child = child.child(&OptionReader::new(Some(element)))
+            .and_then(|child| child.element)
+            .filter(|child| raw::config_setting_parent(*child) == element)
+            .ok_or(Errors::ElementNotExists)?;
+        if !freeze::writable(element) {
+            return Err(Errors::ConfigFrozen);
+        }
+
+        let length = unsafe { raw::config_setting_length(element) } as usize;
+        if new_index >= length {
+            return Err(Errors::OutOfRange);
+        }
+        let index = unsafe { raw::config_setting_index(child) } as usize;
+
+        // Aggregate setting keeps its children in list of element pointers.
+        let elements = unsafe {
+            slice::from_raw_parts_mut((*(*element).value.list).elements,
+                length)
+        };
+        if index < new_index {
+            elements[index..=new_index].rotate_left(1);
+        } else {
+            elements[new_index..=index].rotate_right(1);
+        }
+        Ok(())
+    }
+}
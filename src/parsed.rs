@@ -0,0 +1,149 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Accessors parsing string settings into types of other crates.
+//!
+//! Each accessor is behind the feature of the same name as the crate it
+//! parses into. Errors carry the full setting path, so they can be reported
+//! to the user as is.
+
+use crate::config::OptionReader;
+#[cfg(any(feature = "regex", feature = "semver"))]
+use crate::config::OptionType;
+#[cfg(any(feature = "regex", feature = "semver"))]
+use crate::path;
+
+use std::error;
+use std::fmt;
+
+/// Setting value could not be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidValue {
+    /// Setting path, collection elements have `[N]` segments.
+    pub path : String,
+    /// Why value was rejected.
+    pub message : String
+}
+
+impl fmt::Display for InvalidValue {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl error::Error for InvalidValue {}
+
+impl OptionReader<'_> {
+
+    /// Compile string option value into regular expression, `regex` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("routes : { api = \"^/api/v[0-9]+/\"; };")
+    ///     .is_ok() {
+    ///     let route = cfg.value("routes.api").unwrap().as_regex().unwrap();
+    ///     assert!(route.is_match("/api/v2/users"));
+    /// }
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn as_regex(&self) -> Result<regex::Regex, InvalidValue> {
+        let pattern = self.expect_str()?;
+        regex::Regex::new(pattern).map_err(|error| {
+            self.invalid(format!("invalid regular expression: {}", error))
+        })
+    }
+
+    /// Parse string option value as semantic version, `semver` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("plugin : { version = \"1.4.2\"; };").is_ok() {
+    ///     let version = cfg.value("plugin.version").unwrap().as_version()
+    ///         .unwrap();
+    ///     assert_eq!(version.minor, 4);
+    /// }
+    /// ```
+    #[cfg(feature = "semver")]
+    pub fn as_version(&self) -> Result<semver::Version, InvalidValue> {
+        semver::Version::parse(self.expect_str()?).map_err(|error| {
+            self.invalid(format!("invalid version: {}", error))
+        })
+    }
+
+    /// Parse string option value as semantic version requirement, `semver`
+    /// feature.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("plugin : { requires = \">=1.2, <2\"; };")
+    ///     .is_ok() {
+    ///     let requires = cfg.value("plugin.requires").unwrap()
+    ///         .as_version_req().unwrap();
+    ///     assert!(requires.matches(&semver::Version::new(1, 5, 0)));
+    /// }
+    /// ```
+    #[cfg(feature = "semver")]
+    pub fn as_version_req(&self)
+        -> Result<semver::VersionReq, InvalidValue> {
+        semver::VersionReq::parse(self.expect_str()?).map_err(|error| {
+            self.invalid(format!("invalid version requirement: {}", error))
+        })
+    }
+
+    // Return string value or error naming the setting.
+    #[cfg(any(feature = "regex", feature = "semver"))]
+    fn expect_str(&self) -> Result<&str, InvalidValue> {
+        if self.element.is_none() {
+            return Err(self.invalid("setting does not exist"));
+        }
+        if self.value_type() != Some(OptionType::StringType) {
+            return Err(self.invalid("expected string setting"));
+        }
+        self.as_str().ok_or_else(|| self.invalid("invalid UTF-8 string"))
+    }
+
+    // Create error for current setting.
+    #[cfg(any(feature = "regex", feature = "semver"))]
+    fn invalid<S>(&self, message : S) -> InvalidValue where S: Into<String> {
+        InvalidValue {
+            path : self.element.map(path::setting_path).unwrap_or_default(),
+            message : message.into()
+        }
+    }
+}
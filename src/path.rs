@@ -0,0 +1,189 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Setting path helpers.
+
+use crate::config::{Errors, Result};
+use crate::key;
+use libconfig_sys as raw;
+
+use smallvec::SmallVec;
+use std::ffi::CStr;
+
+/// Separator between path segments.
+pub(crate) const PATH_SEPARATOR : char = '.';
+
+/// Path segments, kept on stack for usual path depths.
+pub(crate) type Segments<'a> = SmallVec<[&'a str; 8]>;
+
+// Split dotted path into segments.
+pub(crate) fn split(path : &str) -> Segments<'_> {
+    path.split(PATH_SEPARATOR).filter(|s| !s.is_empty()).collect()
+}
+
+// Join parent path and child name.
+pub(crate) fn join(parent : &str, name : &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else if name.is_empty() {
+        parent.to_string()
+    } else {
+        format!("{}{}{}", parent, PATH_SEPARATOR, name)
+    }
+}
+
+// Return collection element index if segment has `[N]` form.
+pub(crate) fn index(segment : &str) -> Option<u32> {
+    segment.strip_prefix('[')?.strip_suffix(']')?.parse().ok()
+}
+
+// Return true if name is valid libconfig setting name.
+pub(crate) fn valid_name(name : &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '*' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || "*_-".contains(c))
+        },
+        _ => { false }
+    }
+}
+
+// Join path segments into dotted path.
+pub(crate) fn join_segments(segments : &[String]) -> String {
+    segments.join(&PATH_SEPARATOR.to_string())
+}
+
+// Return full path of setting, collection elements have `[N]` segments.
+pub(crate) fn setting_path(option : *mut raw::config_setting_t) -> String {
+    let mut segments = Vec::new();
+    let mut current = option;
+
+    while raw::config_setting_is_root(current) != raw::CONFIG_TRUE {
+        let name = raw::config_setting_name(current);
+        if name.is_null() {
+            segments.push(format!("[{}]", unsafe {
+                raw::config_setting_index(current)
+            }));
+        } else {
+            segments.push(unsafe { CStr::from_ptr(name) }.to_string_lossy()
+                .into_owned());
+        }
+        current = raw::config_setting_parent(current);
+    }
+
+    segments.reverse();
+    join_segments(&segments)
+}
+
+// Split JSON Pointer into unescaped reference tokens.
+pub(crate) fn pointer_tokens(pointer : &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+
+    let tokens = pointer.strip_prefix('/')?.split('/').map(|token| {
+        token.replace("~1", "/").replace("~0", "~")
+    }).collect();
+    Some(tokens)
+}
+
+// Parse JSON Pointer array index, leading zeros are not allowed.
+pub(crate) fn pointer_index(token : &str) -> Option<u32> {
+    if token.is_empty() || !token.bytes().all(|c| c.is_ascii_digit()) ||
+        (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+// Find direct child of group by name or of collection by `[N]` segment.
+pub(crate) fn child(parent : *mut raw::config_setting_t, segment : &str)
+    -> Option<*mut raw::config_setting_t> {
+
+    let option = match index(segment) {
+        Some(index) => {
+            if raw::config_setting_is_aggregate(parent) != raw::CONFIG_TRUE {
+                return None;
+            }
+            unsafe { raw::config_setting_get_elem(parent, index) }
+        },
+        None => {
+            if raw::config_setting_is_group(parent) != raw::CONFIG_TRUE {
+                return None;
+            }
+            key::with_c_str(segment, |name| unsafe {
+                raw::config_setting_get_member(parent, name.as_ptr())
+            })?
+        }
+    };
+
+    if option.is_null() {
+        None
+    } else {
+        Some(option)
+    }
+}
+
+// Find parent group of path creating missing groups on the way, return the
+// parent and the last path segment.
+pub(crate) fn create_parents(root : *mut raw::config_setting_t,
+    path : &str) -> Result<(*mut raw::config_setting_t, &str)> {
+
+    let segments = split(path);
+    let (name, groups) = segments.split_last()
+        .ok_or(Errors::ElementNotExists)?;
+
+    let mut parent = root;
+    for segment in groups.iter() {
+        parent = key::with_c_str(segment, |segment_name| {
+            let option = unsafe {
+                raw::config_setting_get_member(parent,
+                    segment_name.as_ptr())
+            };
+
+            if option.is_null() {
+                let option = unsafe {
+                    raw::config_setting_add(parent, segment_name.as_ptr(),
+                        raw::CONFIG_TYPE_GROUP as i32)
+                };
+                if option.is_null() {
+                    return Err(Errors::WriteError);
+                }
+                Ok(option)
+            } else if raw::config_setting_is_group(option) !=
+                raw::CONFIG_TRUE {
+                Err(Errors::WriteError)
+            } else {
+                Ok(option)
+            }
+        }).ok_or(Errors::WriteError)??;
+    }
+    Ok((parent, name))
+}
@@ -0,0 +1,206 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Wildcard path queries.
+//!
+//! Query is a dotted path where `*` segment matches any single setting and
+//! `**` segment matches any number of nested levels, including none.
+//! Collection elements are matched by `[N]` segments and are reported with
+//! such paths, e.g. `servers.[0].port`.
+
+use crate::config::{Config, OptionReader};
+use crate::path;
+use libconfig_sys as raw;
+
+use std::ffi::CStr;
+
+/// Segment matching any single setting.
+const ANY_SETTING : &str = "*";
+
+/// Segment matching any number of nested levels.
+const ANY_LEVELS : &str = "**";
+
+/// Setting matched by query.
+pub struct QueryMatch<'a> {
+    /// Setting path.
+    pub path : String,
+    /// Segments matched by wildcards, in pattern order. Levels matched by
+    /// `**` are joined into one dotted path, which is empty if no level was
+    /// matched.
+    pub captures : Vec<String>,
+    /// Setting reader.
+    pub option : OptionReader<'a>
+}
+
+// Match setting path segments against query segments, collecting segments
+// matched by wildcards.
+fn capture(pattern : &[&str], segments : &[String], captures : &mut Vec<String>)
+    -> bool {
+    match pattern.split_first() {
+        None => { segments.is_empty() },
+        Some((&ANY_LEVELS, rest)) => {
+            for skip in 0..=segments.len() {
+                captures.push(path::join_segments(&segments[..skip]));
+                if capture(rest, &segments[skip..], captures) {
+                    return true;
+                }
+                captures.pop();
+            }
+            false
+        },
+        Some((expected, rest)) => {
+            let (name, tail) = match segments.split_first() {
+                Some(split) => { split },
+                None => { return false }
+            };
+
+            if *expected == ANY_SETTING {
+                captures.push(name.clone());
+                if capture(rest, tail, captures) {
+                    return true;
+                }
+                captures.pop();
+                false
+            } else {
+                expected == name && capture(rest, tail, captures)
+            }
+        }
+    }
+}
+
+// Walk all option descendants in document order and collect matching ones.
+fn walk<'a>(config : &'a Config, option : *mut raw::config_setting_t,
+    pattern : &[&str], segments : &mut Vec<String>,
+    found : &mut Vec<QueryMatch<'a>>) {
+    for (name, child) in children(option) {
+        segments.push(name);
+        let mut captures = Vec::new();
+        if capture(pattern, segments, &mut captures) {
+            found.push(QueryMatch {
+                path : path::join_segments(segments),
+                captures,
+                option : config.reader(Some(child))
+            });
+        }
+        walk(config, child, pattern, segments, found);
+        segments.pop();
+    }
+}
+
+// Return direct children of group or collection with their path segments.
+fn children(option : *mut raw::config_setting_t)
+    -> Vec<(String, *mut raw::config_setting_t)> {
+
+    if raw::config_setting_is_aggregate(option) != raw::CONFIG_TRUE {
+        return Vec::new();
+    }
+
+    let group = raw::config_setting_is_group(option) == raw::CONFIG_TRUE;
+    let length = unsafe { raw::config_setting_length(option) };
+    let mut children = Vec::with_capacity(length as usize);
+
+    for index in 0..length {
+        let child = unsafe {
+            raw::config_setting_get_elem(option, index as u32)
+        };
+        if child.is_null() {
+            continue;
+        }
+
+        let name = if group {
+            let name = raw::config_setting_name(child);
+            if name.is_null() {
+                continue;
+            }
+            match unsafe { CStr::from_ptr(name) }.to_str() {
+                Ok(name) => { name.to_string() },
+                Err(_) => { continue }
+            }
+        } else {
+            format!("[{}]", index)
+        };
+        children.push((name, child));
+    }
+    children
+}
+
+impl Config {
+
+    /// Find all settings matching wildcard path. `*` segment matches any
+    /// single setting, `**` matches any number of nested levels. Returns
+    /// paths and readers of matched settings in document order.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string(
+    ///     "servers : { a : { port = 80; }; b : { port = 81; }; };").is_ok() {
+    ///     for (path, option) in cfg.query("servers.*.port") {
+    ///         println!("{} = {:?}", path, option.as_int32());
+    ///     }
+    /// }
+    /// ```
+    pub fn query<S>(&self, pattern : S) -> Vec<(String, OptionReader<'_>)>
+        where S: AsRef<str> {
+        self.query_matches(pattern).into_iter()
+            .map(|found| (found.path, found.option)).collect()
+    }
+
+    /// Find all settings matching wildcard path like `query`, returning also
+    /// segments matched by wildcards.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string(
+    ///     "databases : { main : { pool_size = 8; }; };").is_ok() {
+    ///     for found in cfg.query_matches("databases.*.pool_size") {
+    ///         assert_eq!(found.captures, vec!["main"]);
+    ///     }
+    /// }
+    /// ```
+    pub fn query_matches<S>(&self, pattern : S) -> Vec<QueryMatch<'_>>
+        where S: AsRef<str> {
+        let root = match self.root_element {
+            Some(root) => { root },
+            None => { return Vec::new() }
+        };
+
+        let pattern = pattern.as_ref();
+        let mut found = Vec::new();
+        walk(self, root, &path::split(pattern), &mut Vec::new(),
+            &mut found);
+        found
+    }
+}
@@ -0,0 +1,183 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Cross-setting references.
+//!
+//! String value starting with `@` followed by a path, e.g. `"@paths.base"`,
+//! is a reference to another setting. Strings starting with `@@` are not
+//! references, when references are resolved they are read with `@@`
+//! unescaped to `@`.
+
+use crate::cache;
+use crate::config::{Config, OptionReader};
+use crate::path;
+use libconfig_sys as raw;
+
+use std::ffi::{CStr, CString};
+
+/// Prefix of reference string values.
+const REFERENCE_PREFIX : &str = "@";
+
+/// Prefix of escaped string values starting with literal `@`.
+const ESCAPED_PREFIX : &str = "@@";
+
+// Return reference target path if option is a reference.
+fn reference_path(option : *mut raw::config_setting_t) -> Option<String> {
+    let str = unsafe { raw::config_setting_get_string(option) };
+    if str.is_null() {
+        return None;
+    }
+
+    let value = unsafe { CStr::from_ptr(str) }.to_str().ok()?;
+    if value.starts_with(REFERENCE_PREFIX) &&
+        !value[REFERENCE_PREFIX.len()..].starts_with(REFERENCE_PREFIX) {
+        Some(value[REFERENCE_PREFIX.len()..].to_string())
+    } else {
+        None
+    }
+}
+
+// Return string value with escaped `@@` prefix replaced by `@`.
+pub(crate) fn unescape(value : &str) -> &str {
+    if value.starts_with(ESCAPED_PREFIX) {
+        &value[REFERENCE_PREFIX.len()..]
+    } else {
+        value
+    }
+}
+
+// Follow references chain, return None on dangling or cyclic reference.
+pub(crate) fn resolve(option : *mut raw::config_setting_t)
+    -> Option<*mut raw::config_setting_t> {
+
+    let mut root = option;
+    while !raw::config_setting_parent(root).is_null() {
+        root = raw::config_setting_parent(root);
+    }
+
+    let mut current = option;
+    let mut visited = vec![option];
+    while let Some(path) = reference_path(current) {
+        let path = CString::new(path).ok()?;
+        current = unsafe { raw::config_setting_lookup(root, path.as_ptr()) };
+
+        if current.is_null() || visited.contains(&current) {
+            return None;
+        }
+        visited.push(current);
+    }
+    Some(current)
+}
+
+impl Config {
+
+    /// Enable or disable following references in `value()` lookups.
+    ///
+    /// When enabled every path segment that is a reference is replaced by
+    /// the setting it refers to, so reading `"@paths.base"` returns the
+    /// value of `paths.base`. Strings starting with `@@` are read with `@@`
+    /// unescaped to `@`. Disabled by default.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string(
+    ///     "paths : { base = \"/opt/app\"; };
+    ///     logs : { dir = \"@paths.base\"; };"
+    /// ).is_ok() {
+    ///     cfg.resolve_references(true);
+    ///     assert_eq!(cfg.value("logs.dir").unwrap().as_string().unwrap(),
+    ///         "/opt/app");
+    /// }
+    /// ```
+    pub fn resolve_references(&mut self, enable : bool) {
+        cache::invalidate(&self.generation);
+        self.resolve_references = enable;
+    }
+
+    // Follow references of option if resolution is enabled.
+    pub(crate) fn follow(&self, option : *mut raw::config_setting_t)
+        -> Option<*mut raw::config_setting_t> {
+        if self.resolve_references {
+            resolve(option)
+        } else {
+            Some(option)
+        }
+    }
+
+    // Read value from path following references in every segment.
+    pub(crate) fn value_resolved(&self, path : &str)
+        -> Option<OptionReader<'_>> {
+        self.value_segments(&path::split(path))
+    }
+}
+
+impl OptionReader<'_> {
+
+    /// Return true if element is a reference to another setting.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("a = 1; b = \"@a\";").is_ok() {
+    ///     assert!(cfg.value("b").unwrap().is_reference().unwrap());
+    /// }
+    /// ```
+    pub fn is_reference(&self) -> Option<bool> {
+        Some(reference_path(self.element?).is_some())
+    }
+
+    /// Return setting the reference points to, following chained
+    /// references. Elements that are not references are returned as is.
+    /// Strings of returned setting are read with `@@` unescaped to `@`.
+    /// Return None if target not exists or references form a cycle.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("a = 1; b = \"@a\";").is_ok() {
+    ///     match cfg.value("b").unwrap().resolve() {
+    ///         Some(val) => { /* ... */ },
+    ///         None => { /* ... */ }
+    ///     }
+    /// }
+    /// ```
+    pub fn resolve(&self) -> Option<OptionReader<'_>> {
+        let mut reader = self.derive(Some(resolve(self.element?)?));
+        reader.resolved = true;
+        Some(reader)
+    }
+}
@@ -0,0 +1,261 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Incremental reloading.
+//!
+//! New config is parsed separately and compared with the current setting
+//! tree. Only changed settings are modified in place, unchanged settings
+//! keep their identity together with recorded reads. Settings whose type
+//! changed are replaced, new group members are appended to their group.
+
+use crate::config::{Config, Errors, Result};
+use crate::path;
+use crate::value;
+use libconfig_sys as raw;
+
+use std::ffi::CStr;
+use std::path::Path;
+
+/// Changes applied to setting tree.
+struct Changes {
+    // Paths of added, removed and modified settings.
+    paths : Vec<String>,
+    // Settings removed from tree.
+    removed : Vec<*mut raw::config_setting_t>
+}
+
+// Collect setting and all its descendants.
+fn collect_subtree(option : *mut raw::config_setting_t,
+    settings : &mut Vec<*mut raw::config_setting_t>) {
+    settings.push(option);
+    for index in 0..unsafe { raw::config_setting_length(option) } {
+        let child = unsafe {
+            raw::config_setting_get_elem(option, index as u32)
+        };
+        if !child.is_null() {
+            collect_subtree(child, settings);
+        }
+    }
+}
+
+// Return name of group member.
+fn member_name<'a>(option : *mut raw::config_setting_t) -> Result<&'a str> {
+    let name = raw::config_setting_name(option);
+    if name.is_null() {
+        return Err(Errors::WriteError);
+    }
+    unsafe { CStr::from_ptr(name) }.to_str().map_err(|_| Errors::WriteError)
+}
+
+// Update target group to match source group.
+fn sync_group(source : *mut raw::config_setting_t,
+    target : *mut raw::config_setting_t, target_path : &str,
+    changes : &mut Changes) -> Result<()> {
+
+    let mut index = 0;
+    while index < unsafe { raw::config_setting_length(target) } {
+        let child = unsafe {
+            raw::config_setting_get_elem(target, index as u32)
+        };
+        let name = raw::config_setting_name(child);
+        if unsafe { raw::config_setting_get_member(source, name) }.is_null() {
+            changes.paths.push(path::join(target_path, member_name(child)?));
+            collect_subtree(child, &mut changes.removed);
+            if unsafe { raw::config_setting_remove_elem(target,
+                index as u32) } != raw::CONFIG_TRUE {
+                return Err(Errors::WriteError);
+            }
+        } else {
+            index += 1;
+        }
+    }
+
+    for index in 0..unsafe { raw::config_setting_length(source) } {
+        let child = unsafe {
+            raw::config_setting_get_elem(source, index as u32)
+        };
+        let name = member_name(child)?;
+        let child_path = path::join(target_path, name);
+        let existing = unsafe {
+            raw::config_setting_get_member(target,
+                raw::config_setting_name(child))
+        };
+
+        if existing.is_null() {
+            value::copy(child, target, name)?;
+            changes.paths.push(child_path);
+        } else if !sync_setting(child, existing, &child_path, changes)? {
+            collect_subtree(existing, &mut changes.removed);
+            if unsafe { raw::config_setting_remove(target,
+                raw::config_setting_name(child)) } != raw::CONFIG_TRUE {
+                return Err(Errors::WriteError);
+            }
+            value::copy(child, target, name)?;
+            changes.paths.push(child_path);
+        }
+    }
+    Ok(())
+}
+
+// Update target setting to match source setting in place. Returns false if
+// target must be replaced.
+fn sync_setting(source : *mut raw::config_setting_t,
+    target : *mut raw::config_setting_t, target_path : &str,
+    changes : &mut Changes) -> Result<bool> {
+
+    let source_type = raw::config_setting_type(source);
+    if source_type != raw::config_setting_type(target) {
+        return Ok(false);
+    }
+
+    match source_type as i16 {
+        raw::CONFIG_TYPE_GROUP => {
+            sync_group(source, target, target_path, changes)?;
+            Ok(true)
+        },
+        raw::CONFIG_TYPE_ARRAY | raw::CONFIG_TYPE_LIST => {
+            let length = unsafe { raw::config_setting_length(source) };
+            if length != unsafe { raw::config_setting_length(target) } {
+                return Ok(false);
+            }
+
+            for index in 0..length {
+                let (source_element, target_element) = unsafe {(
+                    raw::config_setting_get_elem(source, index as u32),
+                    raw::config_setting_get_elem(target, index as u32)
+                )};
+                let element_path = path::join(target_path,
+                    &format!("[{}]", index));
+                if !sync_setting(source_element, target_element,
+                    &element_path, changes)? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        },
+        _ => {
+            let format = unsafe { raw::config_setting_get_format(source) };
+            let source_value = value::read(source);
+            if source_value == value::read(target) && format ==
+                unsafe { raw::config_setting_get_format(target) } {
+                return Ok(true);
+            }
+
+            let assigned = match source_value {
+                Some(source_value) => {
+                    value::assign(target, &source_value)?
+                },
+                None => { false }
+            };
+            if assigned {
+                unsafe { raw::config_setting_set_format(target, format) };
+                changes.paths.push(target_path.to_string());
+            }
+            Ok(assigned)
+        }
+    }
+}
+
+impl Config {
+
+    /// Load config file and apply only differences to current settings.
+    /// Unchanged settings are kept as they are, including recorded reads.
+    /// Returns paths of added, removed and modified settings. Current config
+    /// is unchanged if the file can't be parsed.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    /// use std::path::Path;
+    ///
+    /// let mut cfg = Config::new();
+    /// if let Ok(changed) = cfg.reload_from_file(Path::new("app.cfg")) {
+    ///     for path in changed {
+    ///         println!("{} changed", path);
+    ///     }
+    /// }
+    /// ```
+    pub fn reload_from_file(&mut self, file_name : &Path)
+        -> Result<Vec<String>> {
+        let mut source = Config::new();
+        source.load_from_file(file_name)?;
+        self.reload_from(&source)
+    }
+
+    /// Parse config from string and apply only differences to current
+    /// settings like `reload_from_file`.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("a = 1; b = 2;").is_ok() {
+    ///     let changed = cfg.reload_from_string("a = 1; b = 3; c = 4;");
+    ///     assert_eq!(changed, Ok(vec!["b".to_string(), "c".to_string()]));
+    /// }
+    /// ```
+    pub fn reload_from_string<S>(&mut self, config_string : S)
+        -> Result<Vec<String>> where S: Into<String> {
+        let mut source = Config::new();
+        source.load_from_string(config_string)?;
+        self.reload_from(&source)
+    }
+
+    // Apply differences between source config and current settings.
+    fn reload_from(&mut self, source : &Config) -> Result<Vec<String>> {
+        let _guard = self.write_guard()?;
+        let source_root = source.root_element.ok_or(Errors::ParseError)?;
+        let root = raw::config_root_setting(&self.config);
+        if root.is_null() {
+            return Err(Errors::WriteError);
+        }
+        self.root_element = Some(root);
+        self.interpolated = false;
+
+        let mut changes = Changes {
+            paths : Vec::new(),
+            removed : Vec::new()
+        };
+        sync_group(source_root, root, "", &mut changes)?;
+
+        if let Some(tracker) = &self.tracker {
+            let mut tracker = tracker.borrow_mut();
+            for option in changes.removed.iter() {
+                tracker.remove(option);
+            }
+        }
+        let mut origins = self.origins.borrow_mut();
+        for changed in changes.paths.iter() {
+            crate::merge::forget_origins(&mut origins, changed);
+        }
+        Ok(changes.paths)
+    }
+}
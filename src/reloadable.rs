@@ -0,0 +1,208 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Hot reloading.
+//!
+//! ReloadableConfig keeps config loaded from file and reparses it every time
+//! the file changes. New config is parsed separately and swapped in only when
+//! it is valid, so readers see either the old or the new tree. Subscribers
+//! are notified after the swap.
+
+use crate::config::{Config, Errors, Result};
+use crate::shared::{ReadGuard, SharedConfig};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Callback called with reloaded config.
+type Subscriber = Box<dyn FnMut(&Config) + Send>;
+
+type Subscribers = Arc<Mutex<Vec<Subscriber>>>;
+
+// Time without file events after which writing is considered finished.
+const SETTLE_TIME : Duration = Duration::from_millis(50);
+
+/// Config reloaded every time its file changes.
+pub struct ReloadableConfig {
+    file_name : PathBuf,
+    config : SharedConfig,
+    subscribers : Subscribers,
+    // Stops watching and the reloading thread when dropped.
+    _watcher : RecommendedWatcher
+}
+
+// Parse file and swap it in place of shared config. Subscribers are called
+// while the new config is locked.
+fn reload(file_name : &Path, config : &SharedConfig,
+    subscribers : &Subscribers) -> Result<()> {
+    let mut reloaded = Config::new();
+    reloaded.load_from_file(file_name)?;
+    config.replace(reloaded);
+
+    let config = config.read();
+    let mut subscribers = subscribers.lock()
+        .unwrap_or_else(|error| error.into_inner());
+    for subscriber in subscribers.iter_mut() {
+        subscriber(&config);
+    }
+    Ok(())
+}
+
+// Return true if event changes file contents. Editors often save by renaming
+// new file over the old one, so creation counts as well.
+fn is_change(event : &Event, file_name : &Path) -> bool {
+    matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+        && event.paths.iter().any(|path| path.file_name()
+            == file_name.file_name())
+}
+
+// Reload config on every change until watcher is dropped. Events coming
+// in quick succession are collapsed so partially written file isn't read.
+fn listen(events : Receiver<notify::Result<Event>>, file_name : PathBuf,
+    config : SharedConfig, subscribers : Subscribers) {
+    while let Ok(event) = events.recv() {
+        match event {
+            Ok(event) if is_change(&event, &file_name) => {},
+            _ => { continue }
+        }
+        loop {
+            match events.recv_timeout(SETTLE_TIME) {
+                Ok(_) => {},
+                Err(RecvTimeoutError::Timeout) => { break },
+                Err(RecvTimeoutError::Disconnected) => { return }
+            }
+        }
+        // Invalid file keeps previous config active.
+        let _ = reload(&file_name, &config, &subscribers);
+    }
+}
+
+impl ReloadableConfig {
+
+    /// Constructor.
+    /// Load config file and start watching it.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use librustconfig::reloadable::ReloadableConfig;
+    /// use std::path::Path;
+    ///
+    /// if let Ok(cfg) = ReloadableConfig::new(Path::new("app.cfg")) {
+    ///     /* ... */
+    /// }
+    /// ```
+    pub fn new(file_name : &Path) -> Result<ReloadableConfig> {
+        let mut config = Config::new();
+        config.load_from_file(file_name)?;
+        let config = SharedConfig::new(config);
+        let subscribers : Subscribers = Arc::new(Mutex::new(Vec::new()));
+
+        // Watch the directory, file replaced by rename would be lost
+        // otherwise.
+        let directory = match file_name.parent() {
+            Some(directory) if directory != Path::new("") => { directory },
+            _ => { Path::new(".") }
+        };
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)
+            .map_err(|_| Errors::FileNotExists)?;
+        watcher.watch(directory, RecursiveMode::NonRecursive)
+            .map_err(|_| Errors::FileNotExists)?;
+
+        let file_name = file_name.to_path_buf();
+        {
+            let file_name = file_name.clone();
+            let config = config.clone();
+            let subscribers = subscribers.clone();
+            thread::spawn(move || {
+                listen(events, file_name, config, subscribers)
+            });
+        }
+
+        Ok(ReloadableConfig {
+            file_name,
+            config,
+            subscribers,
+            _watcher : watcher
+        })
+    }
+
+    /// Lock current config for reading. Reloading waits until the guard is
+    /// dropped.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use librustconfig::reloadable::ReloadableConfig;
+    /// use std::path::Path;
+    ///
+    /// let cfg = ReloadableConfig::new(Path::new("app.cfg")).unwrap();
+    /// let port = cfg.read().int32("server.port");
+    /// ```
+    pub fn read(&self) -> ReadGuard<'_> {
+        self.config.read()
+    }
+
+    /// Return shared config which follows reloads, for passing to other
+    /// threads.
+    pub fn shared(&self) -> SharedConfig {
+        self.config.clone()
+    }
+
+    /// Call callback with the new config after every reload. Callback runs
+    /// on the reloading thread while the config is locked, so it must use
+    /// the passed config instead of locking it again.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use librustconfig::reloadable::ReloadableConfig;
+    /// use std::path::Path;
+    ///
+    /// let cfg = ReloadableConfig::new(Path::new("app.cfg")).unwrap();
+    /// cfg.subscribe(|cfg| {
+    ///     println!("log level is {:?}", cfg.string("log.level"));
+    /// });
+    /// ```
+    pub fn subscribe<F>(&self, callback : F)
+        where F: FnMut(&Config) + Send + 'static {
+        self.subscribers.lock().unwrap_or_else(|error| error.into_inner())
+            .push(Box::new(callback));
+    }
+
+    /// Reload config file now and notify subscribers. If the file can't be
+    /// parsed previously loaded config stays active.
+    pub fn reload(&self) -> Result<()> {
+        reload(&self.file_name, &self.config, &self.subscribers)
+    }
+}
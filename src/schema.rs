@@ -0,0 +1,1278 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Declarative configuration schema.
+//!
+//! Schema lists expected settings with their types, whether they are
+//! required, default values and allowed values. Config can be checked
+//! against schema with `Config::validate`.
+
+use crate::config::{Config, Errors, OptionType, OptionWriter, Result};
+use crate::format;
+use crate::json;
+use crate::path;
+use crate::value::{self, Value};
+use libconfig_sys as raw;
+
+use indexmap::IndexMap;
+use std::collections::HashSet;
+use std::ffi::CStr;
+use std::fs;
+use std::path as fs_path;
+
+/// Maximal edit distance of suggested setting name.
+const MAX_SUGGESTION_DISTANCE : usize = 2;
+
+/// JSON Schema dialect of exported schemas.
+const JSON_SCHEMA_DIALECT : &str =
+    "https://json-schema.org/draft/2020-12/schema";
+
+/// Declared setting.
+#[derive(Debug, Clone)]
+pub(crate) struct Field {
+    pub(crate) path : String,
+    pub(crate) value_type : OptionType,
+    pub(crate) required : bool,
+    pub(crate) default : Option<Value>,
+    pub(crate) min : Option<f64>,
+    pub(crate) max : Option<f64>,
+    pub(crate) allowed : Option<Vec<String>>,
+    pub(crate) description : Option<String>,
+    #[cfg(feature = "regex")]
+    pub(crate) pattern : Option<regex::Regex>
+}
+
+/// How string values of numeric and boolean settings are treated.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Coercion {
+    /// String value is a type error.
+    #[default]
+    Strict,
+    /// Convertible string value is accepted and reported as warning.
+    Warn,
+    /// Convertible string value is accepted silently.
+    Allow
+}
+
+/// Configuration schema.
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    pub(crate) fields : Vec<Field>,
+    pub(crate) coercion : Coercion,
+    pub(crate) deny_unknown : bool
+}
+
+/// Schema validation error kinds.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationErrorKind {
+    /// Required setting not exists.
+    Missing,
+    /// Setting has unexpected type.
+    WrongType {
+        expected : OptionType,
+        found : Option<OptionType>
+    },
+    /// Numeric value is out of allowed range.
+    OutOfRange,
+    /// String value doesn't match pattern.
+    PatternMismatch,
+    /// String value is not one of allowed values.
+    NotAllowed,
+    /// Value was accepted after conversion from another type. This is a
+    /// warning, not an error.
+    Coerced {
+        from : OptionType
+    },
+    /// Setting is not declared in schema. Contains the most similar declared
+    /// path, if any.
+    Unknown {
+        suggestion : Option<String>
+    }
+}
+
+/// Schema validation error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    /// Setting path.
+    pub path : String,
+    /// What is wrong with the setting.
+    pub kind : ValidationErrorKind
+}
+
+impl ValidationError {
+
+    /// Return true if problem is a warning and config is still usable.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::{Config, OptionType};
+    /// use librustconfig::schema::{Coercion, Schema};
+    ///
+    /// let schema = Schema::new()
+    ///     .coercion(Coercion::Warn)
+    ///     .require("port", OptionType::IntegerType);
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("port = \"8080\";").is_ok() {
+    ///     let errors = cfg.validate(&schema);
+    ///     assert!(errors.iter().all(|error| error.is_warning()));
+    /// }
+    /// ```
+    pub fn is_warning(&self) -> bool {
+        matches!(self.kind, ValidationErrorKind::Coerced { .. })
+    }
+}
+
+impl Schema {
+
+    /// Constructor.
+    /// Create new empty schema.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::schema::Schema;
+    ///
+    /// let schema = Schema::new();
+    /// ```
+    pub fn new() -> Schema {
+        Schema {
+            fields : Vec::new(),
+            coercion : Coercion::Strict,
+            deny_unknown : false
+        }
+    }
+
+    /// Set how string values of numeric and boolean settings are treated,
+    /// default is `Coercion::Strict`.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::schema::{Coercion, Schema};
+    ///
+    /// let schema = Schema::new().coercion(Coercion::Warn);
+    /// ```
+    pub fn coercion(mut self, coercion : Coercion) -> Schema {
+        self.coercion = coercion;
+        self
+    }
+
+    /// Report settings not declared in schema as `Unknown` errors of
+    /// `Config::validate`, default is false.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::{Config, OptionType};
+    /// use librustconfig::schema::Schema;
+    ///
+    /// let schema = Schema::new()
+    ///     .deny_unknown(true)
+    ///     .require("port", OptionType::IntegerType);
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("port = 80; prot = 81;").is_ok() {
+    ///     assert_eq!(cfg.validate(&schema)[0].path, "prot");
+    /// }
+    /// ```
+    pub fn deny_unknown(mut self, deny : bool) -> Schema {
+        self.deny_unknown = deny;
+        self
+    }
+
+    // Add new field declaration.
+    fn field<S>(mut self, path : S, value_type : OptionType, required : bool)
+        -> Schema where S: Into<String> {
+        self.fields.push(Field {
+            path : path.into(),
+            value_type,
+            required,
+            default : None,
+            min : None,
+            max : None,
+            allowed : None,
+            description : None,
+            #[cfg(feature = "regex")]
+            pattern : None
+        });
+        self
+    }
+
+    // Modify last declared field.
+    fn last<F>(mut self, modify : F) -> Schema where F: FnOnce(&mut Field) {
+        if let Some(field) = self.fields.last_mut() {
+            modify(field);
+        }
+        self
+    }
+
+    /// Declare required setting.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::OptionType;
+    /// use librustconfig::schema::Schema;
+    ///
+    /// let schema = Schema::new()
+    ///     .require("server.port", OptionType::IntegerType);
+    /// ```
+    pub fn require<S>(self, path : S, value_type : OptionType) -> Schema
+        where S: Into<String> {
+        self.field(path, value_type, true)
+    }
+
+    /// Declare optional setting.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::OptionType;
+    /// use librustconfig::schema::Schema;
+    ///
+    /// let schema = Schema::new()
+    ///     .optional("debug", OptionType::BooleanType);
+    /// ```
+    pub fn optional<S>(self, path : S, value_type : OptionType) -> Schema
+        where S: Into<String> {
+        self.field(path, value_type, false)
+    }
+
+    /// Set default value of last declared setting.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::OptionType;
+    /// use librustconfig::schema::Schema;
+    ///
+    /// let schema = Schema::new()
+    ///     .optional("server.host", OptionType::StringType)
+    ///         .default_value("localhost");
+    /// ```
+    pub fn default_value<V>(self, value : V) -> Schema where V: Into<Value> {
+        let value = value.into();
+        self.last(|field| field.default = Some(value))
+    }
+
+    /// Set allowed range of last declared numeric setting, bounds are
+    /// inclusive.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::OptionType;
+    /// use librustconfig::schema::Schema;
+    ///
+    /// let schema = Schema::new()
+    ///     .require("server.port", OptionType::IntegerType)
+    ///         .range(1.0, 65535.0);
+    /// ```
+    pub fn range(self, min : f64, max : f64) -> Schema {
+        self.last(|field| {
+            field.min = Some(min);
+            field.max = Some(max);
+        })
+    }
+
+    /// Set minimal allowed value of last declared numeric setting.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::OptionType;
+    /// use librustconfig::schema::Schema;
+    ///
+    /// let schema = Schema::new()
+    ///     .optional("workers", OptionType::IntegerType).min(1.0);
+    /// ```
+    pub fn min(self, min : f64) -> Schema {
+        self.last(|field| field.min = Some(min))
+    }
+
+    /// Set maximal allowed value of last declared numeric setting.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::OptionType;
+    /// use librustconfig::schema::Schema;
+    ///
+    /// let schema = Schema::new()
+    ///     .optional("ratio", OptionType::FloatType).max(1.0);
+    /// ```
+    pub fn max(self, max : f64) -> Schema {
+        self.last(|field| field.max = Some(max))
+    }
+
+    /// Set list of values last declared string setting is allowed to have.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::OptionType;
+    /// use librustconfig::schema::Schema;
+    ///
+    /// let schema = Schema::new()
+    ///     .optional("log.level", OptionType::StringType)
+    ///         .one_of(&["debug", "info", "warning", "error"]);
+    /// ```
+    pub fn one_of<S>(self, values : &[S]) -> Schema where S: AsRef<str> {
+        let values = values.iter().map(|v| v.as_ref().to_string()).collect();
+        self.last(|field| field.allowed = Some(values))
+    }
+
+    /// Set regular expression last declared string setting must match.
+    ///
+    /// # Panics
+    /// Panics if pattern is not a valid regular expression.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::OptionType;
+    /// use librustconfig::schema::Schema;
+    ///
+    /// let schema = Schema::new()
+    ///     .require("server.host", OptionType::StringType)
+    ///         .pattern("^[a-z0-9.-]+$");
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn pattern(self, pattern : &str) -> Schema {
+        let regex = regex::Regex::new(pattern)
+            .expect("invalid schema pattern");
+        self.last(|field| field.pattern = Some(regex))
+    }
+
+    /// Set human readable description of last declared setting.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::OptionType;
+    /// use librustconfig::schema::Schema;
+    ///
+    /// let schema = Schema::new()
+    ///     .require("server.port", OptionType::IntegerType)
+    ///         .description("TCP port to listen on.");
+    /// ```
+    pub fn description<S>(self, text : S) -> Schema where S: Into<String> {
+        let text = text.into();
+        self.last(|field| field.description = Some(text))
+    }
+
+    /// Read schema from config file. See `Schema::from_config` for file
+    /// layout.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::schema::Schema;
+    /// use std::path::Path;
+    ///
+    /// if let Ok(schema) = Schema::load_from_file(Path::new("schema.cfg")) {
+    ///     /* ... */
+    /// }
+    /// ```
+    pub fn load_from_file(file_name : &fs_path::Path) -> Result<Schema> {
+        let mut config = Config::new();
+        config.load_from_file(file_name)?;
+        Schema::from_config(&config)
+    }
+
+    /// Read schema declared in config. Group with string `type` member
+    /// declares setting, other groups are sections. Declaration members are
+    /// `type` (`int`, `int64`, `float`, `bool` or `string`), `required`,
+    /// `default`, `min`, `max`, `one_of`, `pattern` and `description`.
+    /// Optional root `coercion` string is `strict`, `warn` or `allow`,
+    /// optional root `deny_unknown` bool enables `Schema::deny_unknown`.
+    /// Unknown members are a `ParseError`, so is `pattern` without the
+    /// `regex` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    /// use librustconfig::schema::Schema;
+    ///
+    /// let mut schema_cfg = Config::new();
+    /// if schema_cfg.load_from_string("server : {
+    ///         port : { type = \"int\"; required = true; min = 1; };
+    ///     };").is_ok() {
+    ///     let schema = Schema::from_config(&schema_cfg).unwrap();
+    ///
+    ///     let mut cfg = Config::new();
+    ///     if cfg.load_from_string("server : { port = 0; };").is_ok() {
+    ///         assert_eq!(cfg.validate(&schema).len(), 1);
+    ///     }
+    /// }
+    /// ```
+    pub fn from_config(config : &Config) -> Result<Schema> {
+        let mut declarations = match config.root_element
+            .and_then(value::read) {
+            Some(Value::Group(children)) => { children },
+            _ => { return Err(Errors::ParseError) }
+        };
+
+        let mut schema = Schema::new();
+        if let Some(Value::Str(name)) = declarations.get("coercion") {
+            schema.coercion = match name.as_str() {
+                "strict" => { Coercion::Strict },
+                "warn" => { Coercion::Warn },
+                "allow" => { Coercion::Allow },
+                _ => { return Err(Errors::ParseError) }
+            };
+            declarations.shift_remove("coercion");
+        }
+        match declarations.shift_remove("deny_unknown") {
+            Some(Value::Bool(deny)) => { schema.deny_unknown = deny; },
+            Some(_) => { return Err(Errors::ParseError) },
+            None => {}
+        }
+
+        read_declarations(&mut schema, &declarations, "")?;
+        Ok(schema)
+    }
+
+    /// Export schema as JSON Schema document, so external tools can
+    /// validate and autocomplete the same settings.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::OptionType;
+    /// use librustconfig::schema::Schema;
+    ///
+    /// let schema = Schema::new()
+    ///     .require("server.port", OptionType::IntegerType)
+    ///         .range(1.0, 65535.0);
+    ///
+    /// let json = schema.to_json_schema();
+    /// assert!(json.contains("\"port\""));
+    /// ```
+    pub fn to_json_schema(&self) -> String {
+        let mut root = Value::Group(IndexMap::new());
+        object_schema(&mut root);
+
+        for field in self.fields.iter() {
+            let segments = path::split(&field.path);
+            let (name, groups) = match segments.split_last() {
+                Some(split) => { split },
+                None => { continue }
+            };
+
+            let mut parent = &mut root;
+            for group in groups.iter() {
+                if field.required {
+                    add_required(parent, group);
+                }
+                parent = child_schema(parent, group);
+                if !is_object_schema(parent) {
+                    object_schema(parent);
+                }
+            }
+
+            if field.required {
+                add_required(parent, name);
+            }
+            if let Value::Group(properties) = property_map(parent) {
+                properties.insert(name.to_string(),
+                    field.json_schema(self.coercion));
+            }
+        }
+
+        if let Value::Group(root) = &mut root {
+            root.insert("$schema".to_string(),
+                Value::Str(JSON_SCHEMA_DIALECT.to_string()));
+            root.move_index(root.len() - 1, 0);
+        }
+        json::to_string_pretty(&root)
+    }
+
+    /// Write config file with text returned by `render_example`.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::OptionType;
+    /// use librustconfig::schema::Schema;
+    /// use std::fs;
+    /// use std::path::Path;
+    ///
+    /// let schema = Schema::new()
+    ///     .require("server.port", OptionType::IntegerType)
+    ///         .description("TCP port to listen on.")
+    ///         .default_value(8080);
+    ///
+    /// if schema.write_default_config(Path::new("default.cfg")).is_ok() {
+    ///     fs::remove_file(Path::new("default.cfg"));
+    /// }
+    /// ```
+    pub fn write_default_config(&self, file_name : &fs_path::Path)
+        -> Result<()> {
+        match fs::write(file_name, self.render_example()) {
+            Ok(_) => { Ok(()) },
+            Err(_) => { Err(Errors::SaveError) }
+        }
+    }
+
+    /// Return text of config with every declared setting set to its default
+    /// value, or to placeholder of the declared type. Setting descriptions
+    /// are written as comments above settings.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::OptionType;
+    /// use librustconfig::schema::Schema;
+    ///
+    /// let schema = Schema::new()
+    ///     .require("server.port", OptionType::IntegerType)
+    ///         .description("TCP port to listen on.")
+    ///         .default_value(8080);
+    ///
+    /// assert_eq!(schema.render_example(),
+    ///     "server :\n{\n  # TCP port to listen on.\n  port = 8080;\n};\n");
+    /// ```
+    pub fn render_example(&self) -> String {
+        let mut root = IndexMap::new();
+        'fields: for field in self.fields.iter() {
+            let segments = path::split(&field.path);
+            let (name, groups) = match segments.split_last() {
+                Some(split) => { split },
+                None => { continue }
+            };
+
+            let mut parent = &mut root;
+            for group in groups.iter() {
+                let entry = parent.entry(group.to_string())
+                    .or_insert_with(|| Template::Group(IndexMap::new()));
+                parent = match entry {
+                    Template::Group(children) => { children },
+                    Template::Setting(_) => { continue 'fields }
+                };
+            }
+            if parent.contains_key(*name) {
+                continue;
+            }
+            parent.insert(name.to_string(), Template::Setting(field));
+        }
+
+        let mut out = String::new();
+        write_template(&mut out, &root, 0);
+        out
+    }
+}
+
+// Read setting declarations of schema config group.
+fn read_declarations(schema : &mut Schema,
+    declarations : &IndexMap<String, Value>, parent : &str) -> Result<()> {
+    for (name, declaration) in declarations.iter() {
+        let current = path::join(parent, name);
+        let members = match declaration {
+            Value::Group(members) => { members },
+            _ => { return Err(Errors::ParseError) }
+        };
+
+        match members.get("type") {
+            Some(Value::Str(_)) => {
+                schema.fields.push(Field::from_declaration(current,
+                    members)?);
+            },
+            _ => { read_declarations(schema, members, &current)?; }
+        }
+    }
+    Ok(())
+}
+
+// Declared settings arranged in groups.
+enum Template<'a> {
+    Setting(&'a Field),
+    Group(IndexMap<String, Template<'a>>)
+}
+
+// Write template settings of one group.
+fn write_template(out : &mut String, settings : &IndexMap<String, Template>,
+    depth : usize) {
+    let mut previous = None;
+    for (name, setting) in settings.iter() {
+        let separated = match setting {
+            Template::Setting(field) => { field.description.is_some() },
+            Template::Group(_) => { true }
+        };
+        if previous.is_some_and(|previous| previous || separated) {
+            out.push('\n');
+        }
+        previous = Some(separated);
+
+        match setting {
+            Template::Setting(field) => {
+                if let Some(description) = &field.description {
+                    format::write_comment(out, description, depth);
+                }
+                format::indent(out, depth);
+                out.push_str(name);
+                out.push_str(" = ");
+                match (field.value_type, field.example_value()) {
+                    (OptionType::Int64Type, Value::Int(val)) => {
+                        out.push_str(&format!("{}L", val));
+                    },
+                    (_, val) => {
+                        // Example values come from parsed schema, floats
+                        // are finite and formatting can't fail.
+                        let _ = format::write_value(out, &val, depth);
+                    }
+                }
+                out.push_str(";\n");
+            },
+            Template::Group(children) => {
+                format::indent(out, depth);
+                out.push_str(name);
+                out.push_str(" :\n");
+                format::indent(out, depth);
+                out.push_str("{\n");
+                write_template(out, children, depth + 1);
+                format::indent(out, depth);
+                out.push_str("};\n");
+            }
+        }
+    }
+}
+
+// Make value an empty JSON Schema object declaration.
+fn object_schema(value : &mut Value) {
+    let mut schema = IndexMap::new();
+    schema.insert("type".to_string(), Value::from("object"));
+    schema.insert("properties".to_string(), Value::Group(IndexMap::new()));
+    *value = Value::Group(schema);
+}
+
+// Return true if value is JSON Schema object declaration.
+fn is_object_schema(value : &Value) -> bool {
+    match value {
+        Value::Group(schema) => {
+            schema.get("type") == Some(&Value::from("object"))
+        },
+        _ => { false }
+    }
+}
+
+// Return properties of JSON Schema object declaration.
+fn property_map(value : &mut Value) -> &mut Value {
+    match value {
+        Value::Group(schema) => {
+            schema.entry("properties".to_string())
+                .or_insert_with(|| Value::Group(IndexMap::new()))
+        },
+        other => { other }
+    }
+}
+
+// Return property declaration creating it if not exists.
+fn child_schema<'a>(value : &'a mut Value, name : &str) -> &'a mut Value {
+    match property_map(value) {
+        Value::Group(properties) => {
+            properties.entry(name.to_string())
+                .or_insert_with(|| Value::Group(IndexMap::new()))
+        },
+        other => { other }
+    }
+}
+
+// Add name to JSON Schema object required properties.
+fn add_required(value : &mut Value, name : &str) {
+    if let Value::Group(schema) = value {
+        let required = schema.entry("required".to_string())
+            .or_insert_with(|| Value::Array(Vec::new()));
+        if let Value::Array(names) = required {
+            let name = Value::from(name);
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+}
+
+// Return optimal string alignment distance between two names.
+fn edit_distance(a : &str, b : &str) -> usize {
+    let a : Vec<char> = a.chars().collect();
+    let b : Vec<char> = b.chars().collect();
+    let mut distance = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in distance.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in distance[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut value = (distance[i - 1][j] + 1)
+                .min(distance[i][j - 1] + 1)
+                .min(distance[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                value = value.min(distance[i - 2][j - 2] + 1);
+            }
+            distance[i][j] = value;
+        }
+    }
+    distance[a.len()][b.len()]
+}
+
+/// Unknown settings search state.
+struct Linter {
+    // Declared field paths.
+    fields : HashSet<String>,
+    // Declared field paths and their parent groups.
+    known : HashSet<String>,
+    errors : Vec<ValidationError>
+}
+
+impl Linter {
+
+    // Constructor.
+    fn new(schema : &Schema) -> Linter {
+        let mut fields = HashSet::new();
+        let mut known = HashSet::new();
+
+        for field in schema.fields.iter() {
+            let mut current = String::new();
+            for segment in path::split(&field.path) {
+                current = path::join(&current, segment);
+                known.insert(current.clone());
+            }
+            fields.insert(current);
+        }
+
+        Linter {
+            fields,
+            known,
+            errors : Vec::new()
+        }
+    }
+
+    // Return the most similar declared path with the same parent.
+    fn suggest(&self, parent : &str, name : &str) -> Option<String> {
+        self.known.iter()
+            .filter_map(|known| {
+                let (known_parent, known_name) = match known
+                    .rfind(path::PATH_SEPARATOR) {
+                    Some(pos) => { (&known[..pos], &known[pos + 1..]) },
+                    None => { ("", known.as_str()) }
+                };
+
+                let distance = edit_distance(known_name, name);
+                if known_parent == parent && distance > 0 &&
+                    distance <= MAX_SUGGESTION_DISTANCE &&
+                    distance < name.chars().count() {
+                    Some((distance, known))
+                } else {
+                    None
+                }
+            })
+            .min()
+            .map(|(_, known)| known.clone())
+    }
+
+    // Check all group children.
+    fn lint(&mut self, option : *mut raw::config_setting_t, parent : &str) {
+        if raw::config_setting_is_group(option) != raw::CONFIG_TRUE {
+            return;
+        }
+
+        let length = unsafe { raw::config_setting_length(option) };
+        for index in 0..length {
+            let child = unsafe {
+                raw::config_setting_get_elem(option, index as u32)
+            };
+            if child.is_null() || raw::config_setting_name(child).is_null() {
+                continue;
+            }
+
+            let name = unsafe {
+                CStr::from_ptr(raw::config_setting_name(child))
+            }.to_string_lossy().into_owned();
+            let child_path = path::join(parent, &name);
+
+            if !self.known.contains(&child_path) {
+                let suggestion = self.suggest(parent, &name);
+                self.errors.push(ValidationError {
+                    path : child_path,
+                    kind : ValidationErrorKind::Unknown { suggestion }
+                });
+            } else if !self.fields.contains(&child_path) {
+                self.lint(child, &child_path);
+            }
+        }
+    }
+}
+
+impl Field {
+
+    // Create field from schema config declaration members.
+    fn from_declaration(path : String, members : &IndexMap<String, Value>)
+        -> Result<Field> {
+        let value_type = match members.get("type") {
+            Some(Value::Str(name)) => {
+                match name.as_str() {
+                    "int" => { OptionType::IntegerType },
+                    "int64" => { OptionType::Int64Type },
+                    "float" => { OptionType::FloatType },
+                    "bool" => { OptionType::BooleanType },
+                    "string" => { OptionType::StringType },
+                    "group" => { OptionType::GroupType },
+                    "array" => { OptionType::ArrayType },
+                    "list" => { OptionType::ListType },
+                    _ => { return Err(Errors::ParseError) }
+                }
+            },
+            _ => { return Err(Errors::ParseError) }
+        };
+        let number = |value : &Value| {
+            match value {
+                Value::Int(val) => { Ok(*val as f64) },
+                Value::Float(val) => { Ok(*val) },
+                _ => { Err(Errors::ParseError) }
+            }
+        };
+
+        let mut field = Field {
+            path,
+            value_type,
+            required : false,
+            default : None,
+            min : None,
+            max : None,
+            allowed : None,
+            description : None,
+            #[cfg(feature = "regex")]
+            pattern : None
+        };
+        for (name, value) in members.iter() {
+            match (name.as_str(), value) {
+                ("type", _) => {},
+                ("required", Value::Bool(val)) => { field.required = *val; },
+                ("default", val) => { field.default = Some(val.clone()); },
+                ("min", val) => { field.min = Some(number(val)?); },
+                ("max", val) => { field.max = Some(number(val)?); },
+                ("one_of", Value::Array(values)) |
+                ("one_of", Value::List(values)) => {
+                    let allowed = values.iter().map(|val| match val {
+                        Value::Str(text) => { Ok(text.clone()) },
+                        _ => { Err(Errors::ParseError) }
+                    }).collect::<Result<Vec<String>>>()?;
+                    field.allowed = Some(allowed);
+                },
+                ("description", Value::Str(text)) => {
+                    field.description = Some(text.clone());
+                },
+                #[cfg(feature = "regex")]
+                ("pattern", Value::Str(pattern)) => {
+                    let regex = regex::Regex::new(pattern)
+                        .map_err(|_| Errors::ParseError)?;
+                    field.pattern = Some(regex);
+                },
+                _ => { return Err(Errors::ParseError) }
+            }
+        }
+        Ok(field)
+    }
+
+    // Return JSON Schema declaration of field.
+    fn json_schema(&self, coercion : Coercion) -> Value {
+        let (type_name, coercible) = match self.value_type {
+            OptionType::IntegerType | OptionType::Int64Type => {
+                ("integer", true)
+            },
+            OptionType::FloatType => { ("number", true) },
+            OptionType::StringType => { ("string", false) },
+            OptionType::BooleanType => { ("boolean", true) },
+            OptionType::GroupType => { ("object", false) },
+            OptionType::ArrayType | OptionType::ListType => {
+                ("array", false)
+            },
+            OptionType::NoneType => { ("null", false) }
+        };
+
+        let mut schema = IndexMap::new();
+        if coercion != Coercion::Strict && coercible {
+            schema.insert("type".to_string(), Value::Array(vec![
+                Value::from(type_name), Value::from("string")]));
+        } else {
+            schema.insert("type".to_string(), Value::from(type_name));
+        }
+
+        if let Some(description) = &self.description {
+            schema.insert("description".to_string(),
+                Value::from(description.as_str()));
+        }
+
+        if let Some(min) = self.min {
+            schema.insert("minimum".to_string(), Value::Float(min));
+        }
+        if let Some(max) = self.max {
+            schema.insert("maximum".to_string(), Value::Float(max));
+        }
+        if let Some(allowed) = &self.allowed {
+            schema.insert("enum".to_string(), Value::Array(allowed.iter()
+                .map(|val| Value::from(val.as_str())).collect()));
+        }
+        #[cfg(feature = "regex")]
+        {
+            if let Some(pattern) = &self.pattern {
+                schema.insert("pattern".to_string(),
+                    Value::from(pattern.as_str()));
+            }
+        }
+        if let Some(default) = &self.default {
+            schema.insert("default".to_string(), default.clone());
+        }
+        Value::Group(schema)
+    }
+
+    // Return default value, or placeholder value valid for the field.
+    fn example_value(&self) -> Value {
+        let value = match (&self.default, &self.allowed) {
+            (Some(default), _) => { default.clone() },
+            (None, Some(allowed)) if !allowed.is_empty() => {
+                Value::from(allowed[0].as_str())
+            },
+            _ => {
+                let mut zero = 0.0;
+                if let Some(min) = self.min {
+                    zero = f64::max(zero, min);
+                }
+                if let Some(max) = self.max {
+                    zero = f64::min(zero, max);
+                }
+                match self.value_type {
+                    OptionType::IntegerType | OptionType::Int64Type => {
+                        Value::Int(zero.ceil() as i64)
+                    },
+                    OptionType::FloatType => { Value::Float(zero) },
+                    OptionType::StringType => { Value::from("") },
+                    OptionType::BooleanType => { Value::Bool(false) },
+                    OptionType::GroupType => { Value::Group(IndexMap::new()) },
+                    OptionType::ArrayType => { Value::Array(Vec::new()) },
+                    // Setting without value can't be written, empty list is
+                    // the closest placeholder.
+                    OptionType::ListType | OptionType::NoneType => {
+                        Value::List(Vec::new())
+                    }
+                }
+            }
+        };
+
+        match (self.value_type, value) {
+            (OptionType::FloatType, Value::Int(val)) => {
+                Value::Float(val as f64)
+            },
+            (_, value) => { value }
+        }
+    }
+
+    // Convert string value to declared type if possible.
+    fn coerce(&self, value : &Value) -> Option<Value> {
+        let text = match value {
+            Value::Str(text) => { text.trim() },
+            _ => { return None }
+        };
+
+        match self.value_type {
+            OptionType::IntegerType => {
+                let val = text.parse::<i32>().ok()?;
+                Some(Value::Int(val as i64))
+            },
+            OptionType::Int64Type => { Some(Value::Int(text.parse().ok()?)) },
+            OptionType::FloatType => {
+                let val = text.parse::<f64>().ok()?;
+                if val.is_finite() { Some(Value::Float(val)) } else { None }
+            },
+            OptionType::BooleanType => {
+                if text.eq_ignore_ascii_case("true") {
+                    Some(Value::Bool(true))
+                } else if text.eq_ignore_ascii_case("false") {
+                    Some(Value::Bool(false))
+                } else {
+                    None
+                }
+            },
+            _ => { None }
+        }
+    }
+
+    // Check setting value, return error kind if value is not valid.
+    fn check(&self, value : &Value, value_type : Option<OptionType>,
+        coercion : Coercion) -> Option<ValidationErrorKind> {
+
+        let type_matches = match (self.value_type, value_type) {
+            (OptionType::Int64Type, Some(OptionType::IntegerType)) => { true },
+            (expected, Some(found)) => { expected == found },
+            (_, None) => { false }
+        };
+
+        let coerced = if type_matches || coercion == Coercion::Strict {
+            None
+        } else {
+            self.coerce(value)
+        };
+        let value = match (&coerced, type_matches) {
+            (Some(coerced), _) => { coerced },
+            (None, true) => { value },
+            (None, false) => {
+                return Some(ValidationErrorKind::WrongType {
+                    expected : self.value_type,
+                    found : value_type
+                });
+            }
+        };
+
+        let number = match value {
+            Value::Int(val) => { Some(*val as f64) },
+            Value::Float(val) => { Some(*val) },
+            _ => { None }
+        };
+        if let Some(number) = number {
+            if self.min.is_some_and(|min| number < min) ||
+                self.max.is_some_and(|max| number > max) {
+                return Some(ValidationErrorKind::OutOfRange);
+            }
+        }
+
+        if let (Some(allowed), Value::Str(text)) = (&self.allowed, value) {
+            if !allowed.contains(text) {
+                return Some(ValidationErrorKind::NotAllowed);
+            }
+        }
+
+        #[cfg(feature = "regex")]
+        {
+            if let (Some(pattern), Value::Str(text)) = (&self.pattern, value) {
+                if !pattern.is_match(text) {
+                    return Some(ValidationErrorKind::PatternMismatch);
+                }
+            }
+        }
+
+        match (coerced, value_type) {
+            (Some(_), Some(found)) if coercion == Coercion::Warn => {
+                Some(ValidationErrorKind::Coerced { from : found })
+            },
+            _ => { None }
+        }
+    }
+}
+
+impl Config {
+
+    /// Check config against schema, return list of found problems. Empty
+    /// list means config is valid. Undeclared settings are reported after
+    /// declared ones if schema denies unknown settings.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::{Config, OptionType};
+    /// use librustconfig::schema::Schema;
+    ///
+    /// let schema = Schema::new()
+    ///     .require("server.port", OptionType::IntegerType)
+    ///         .range(1.0, 65535.0);
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { port = 8080; };").is_ok() {
+    ///     for error in cfg.validate(&schema) {
+    ///         println!("{}: {:?}", error.path, error.kind);
+    ///     }
+    /// }
+    /// ```
+    pub fn validate(&self, schema : &Schema) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+
+        for field in schema.fields.iter() {
+            let kind = match self.value(field.path.as_str()) {
+                Some(option) => {
+                    match option.element.and_then(value::read) {
+                        Some(val) => {
+                            field.check(&val, option.value_type(),
+                                schema.coercion)
+                        },
+                        None => {
+                            Some(ValidationErrorKind::WrongType {
+                                expected : field.value_type,
+                                found : option.value_type()
+                            })
+                        }
+                    }
+                },
+                None => {
+                    if field.required {
+                        Some(ValidationErrorKind::Missing)
+                    } else {
+                        None
+                    }
+                }
+            };
+
+            if let Some(kind) = kind {
+                errors.push(ValidationError {
+                    path : field.path.clone(),
+                    kind
+                });
+            }
+        }
+
+        if schema.deny_unknown {
+            errors.extend(self.lint(schema));
+        }
+        errors
+    }
+
+    /// Write default values of declared settings missing in config.
+    /// Missing parent groups are created.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::{Config, OptionType};
+    /// use librustconfig::schema::Schema;
+    ///
+    /// let schema = Schema::new()
+    ///     .optional("server.host", OptionType::StringType)
+    ///         .default_value("localhost");
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.apply_defaults(&schema).is_ok() {
+    ///     assert_eq!(cfg.value("server.host").unwrap().as_string().unwrap(),
+    ///         "localhost");
+    /// }
+    /// ```
+    pub fn apply_defaults(&mut self, schema : &Schema) -> Result<()> {
+        let _guard = self.write_guard()?;
+        let root = self.root_element.ok_or(Errors::ElementNotExists)?;
+
+        for field in schema.fields.iter() {
+            let default = match &field.default {
+                Some(default) => { default },
+                None => { continue }
+            };
+
+            if self.value(field.path.as_str()).is_none() {
+                let (parent, name) = path::create_parents(root, &field.path)?;
+                value::write(parent, name, default)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace string values of declared numeric and boolean settings with
+    /// converted values, so they can be read with typed accessors. Converted
+    /// settings keep their position, elements of arrays are not converted.
+    /// Does nothing if schema coercion is `Coercion::Strict`.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::{Config, OptionType};
+    /// use librustconfig::schema::{Coercion, Schema};
+    ///
+    /// let schema = Schema::new()
+    ///     .coercion(Coercion::Allow)
+    ///     .require("port", OptionType::IntegerType);
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("port = \"8080\";").is_ok() &&
+    ///     cfg.coerce(&schema).is_ok() {
+    ///     assert_eq!(cfg.value("port").unwrap().as_int32().unwrap(), 8080);
+    /// }
+    /// ```
+    pub fn coerce(&mut self, schema : &Schema) -> Result<()> {
+        let _guard = self.write_guard()?;
+        if schema.coercion == Coercion::Strict {
+            return Ok(());
+        }
+
+        for field in schema.fields.iter() {
+            let option = match self.value(field.path.as_str())
+                .and_then(|option| option.element) {
+                Some(option) => { option },
+                None => { continue }
+            };
+
+            let coerced = match value::read(option)
+                .and_then(|val| field.coerce(&val)) {
+                Some(coerced) => { coerced },
+                None => { continue }
+            };
+
+            if value::assign(option, &coerced)? {
+                continue;
+            }
+
+            // Array holds scalars of one type, its elements can't be
+            // replaced by value of another type.
+            let parent = raw::config_setting_parent(option);
+            if parent.is_null() ||
+                raw::config_setting_is_array(parent) == raw::CONFIG_TRUE {
+                continue;
+            }
+            let name = raw::config_setting_name(option);
+            let name = if name.is_null() {
+                ""
+            } else {
+                unsafe { CStr::from_ptr(name) }.to_str()
+                    .map_err(|_| Errors::WriteError)?
+            }.to_string();
+
+            let index = unsafe { raw::config_setting_index(option) };
+            if unsafe {
+                raw::config_setting_remove_elem(parent, index as u32)
+            } != raw::CONFIG_TRUE {
+                return Err(Errors::WriteError);
+            }
+            value::write(parent, &name, &coerced)?;
+            let last = unsafe { raw::config_setting_length(parent) } - 1;
+            OptionWriter::new(Some(parent))
+                .move_child_to(last as usize, index as usize)?;
+        }
+        Ok(())
+    }
+
+    /// Return settings present in config but not declared in schema, with
+    /// the closest declared name as suggestion for likely typos.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::{Config, OptionType};
+    /// use librustconfig::schema::{Schema, ValidationErrorKind};
+    ///
+    /// let schema = Schema::new()
+    ///     .require("server.port", OptionType::IntegerType);
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { prot = 8080; };").is_ok() {
+    ///     let errors = cfg.lint(&schema);
+    ///     assert_eq!(errors[0].kind, ValidationErrorKind::Unknown {
+    ///         suggestion : Some("server.port".to_string())
+    ///     });
+    /// }
+    /// ```
+    pub fn lint(&self, schema : &Schema) -> Vec<ValidationError> {
+        let mut linter = Linter::new(schema);
+        if let Some(root) = self.root_element {
+            linter.lint(root, "");
+        }
+        linter.errors
+    }
+}
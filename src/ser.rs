@@ -0,0 +1,490 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Serialization of Rust types into config settings, `serde` feature.
+//!
+//! Structs and maps become groups, sequences of scalars of one type become
+//! arrays and other sequences become lists. `None` fields are omitted. Enum
+//! unit variants are written as strings, other variants as groups with
+//! single member named after the variant, so values read back with
+//! `deserialize`.
+
+use crate::config::Config;
+use crate::path;
+use crate::value::Value;
+
+pub use crate::de::Error;
+
+use indexmap::IndexMap;
+use serde::ser::{self, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::mem;
+
+impl ser::Error for Error {
+    fn custom<T>(message : T) -> Error where T: fmt::Display {
+        Error { path : String::new(), message : message.to_string() }
+    }
+}
+
+/// Serialize value into owned config value.
+///
+/// # Example
+/// ```
+/// use librustconfig::ser;
+/// use librustconfig::value::Value;
+///
+/// assert_eq!(ser::to_value(&vec![1, 2]).unwrap(),
+///     Value::Array(vec![Value::Int(1), Value::Int(2)]));
+/// ```
+pub fn to_value<T>(value : &T) -> Result<Value, Error>
+    where T: Serialize + ?Sized {
+    to_value_at(value, "")
+}
+
+// Serialize value of setting at path.
+fn to_value_at<T>(value : &T, path : &str) -> Result<Value, Error>
+    where T: Serialize + ?Sized {
+    value.serialize(Serializer { path : path.to_string() })?
+        .ok_or_else(|| error(path, "value is empty"))
+}
+
+// Create error at path.
+fn error<S>(path : &str, message : S) -> Error where S: Into<String> {
+    Error { path : path.to_string(), message : message.into() }
+}
+
+// Set path of error raised by nested value.
+fn locate(path : &str, mut error : Error) -> Error {
+    if error.path.is_empty() {
+        error.path = path.to_string();
+    }
+    error
+}
+
+// Return array if elements are scalars of one type, list otherwise.
+fn collection(elements : Vec<Value>) -> Value {
+    let scalar = |value : &Value| {
+        !matches!(value, Value::Array(_) | Value::List(_) | Value::Group(_))
+    };
+    let same_type = elements.windows(2).all(|pair| {
+        mem::discriminant(&pair[0]) == mem::discriminant(&pair[1])
+    });
+    if same_type && elements.iter().all(scalar) {
+        Value::Array(elements)
+    } else {
+        Value::List(elements)
+    }
+}
+
+// Wrap variant value into group with single member.
+fn variant(name : &str, value : Value) -> Value {
+    let mut group = IndexMap::new();
+    group.insert(name.to_string(), value);
+    Value::Group(group)
+}
+
+/// Serializer of one value. `None` result means omitted value.
+struct Serializer {
+    path : String
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Option<Value>;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = VariantSerializer<SeqSerializer>;
+    type SerializeMap = GroupSerializer;
+    type SerializeStruct = GroupSerializer;
+    type SerializeStructVariant = VariantSerializer<GroupSerializer>;
+
+    fn serialize_bool(self, value : bool) -> Result<Option<Value>, Error> {
+        Ok(Some(Value::Bool(value)))
+    }
+
+    fn serialize_i8(self, value : i8) -> Result<Option<Value>, Error> {
+        self.serialize_i64(i64::from(value))
+    }
+
+    fn serialize_i16(self, value : i16) -> Result<Option<Value>, Error> {
+        self.serialize_i64(i64::from(value))
+    }
+
+    fn serialize_i32(self, value : i32) -> Result<Option<Value>, Error> {
+        self.serialize_i64(i64::from(value))
+    }
+
+    fn serialize_i64(self, value : i64) -> Result<Option<Value>, Error> {
+        Ok(Some(Value::Int(value)))
+    }
+
+    fn serialize_u8(self, value : u8) -> Result<Option<Value>, Error> {
+        self.serialize_i64(i64::from(value))
+    }
+
+    fn serialize_u16(self, value : u16) -> Result<Option<Value>, Error> {
+        self.serialize_i64(i64::from(value))
+    }
+
+    fn serialize_u32(self, value : u32) -> Result<Option<Value>, Error> {
+        self.serialize_i64(i64::from(value))
+    }
+
+    fn serialize_u64(self, value : u64) -> Result<Option<Value>, Error> {
+        match i64::try_from(value) {
+            Ok(value) => { self.serialize_i64(value) },
+            Err(_) => { Err(error(&self.path, "integer out of int64 range")) }
+        }
+    }
+
+    fn serialize_f32(self, value : f32) -> Result<Option<Value>, Error> {
+        self.serialize_f64(f64::from(value))
+    }
+
+    fn serialize_f64(self, value : f64) -> Result<Option<Value>, Error> {
+        Ok(Some(Value::Float(value)))
+    }
+
+    fn serialize_char(self, value : char) -> Result<Option<Value>, Error> {
+        Ok(Some(Value::Str(value.to_string())))
+    }
+
+    fn serialize_str(self, value : &str) -> Result<Option<Value>, Error> {
+        Ok(Some(Value::Str(value.to_string())))
+    }
+
+    fn serialize_bytes(self, value : &[u8]) -> Result<Option<Value>, Error> {
+        Ok(Some(Value::Array(value.iter()
+            .map(|byte| Value::Int(i64::from(*byte))).collect())))
+    }
+
+    fn serialize_none(self) -> Result<Option<Value>, Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T>(self, value : &T) -> Result<Option<Value>, Error>
+        where T: Serialize + ?Sized {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Option<Value>, Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name : &'static str)
+        -> Result<Option<Value>, Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(self, _name : &'static str, _index : u32,
+        variant : &'static str) -> Result<Option<Value>, Error> {
+        Ok(Some(Value::Str(variant.to_string())))
+    }
+
+    fn serialize_newtype_struct<T>(self, _name : &'static str, value : &T)
+        -> Result<Option<Value>, Error> where T: Serialize + ?Sized {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(self, _name : &'static str, _index : u32,
+        name : &'static str, value : &T) -> Result<Option<Value>, Error>
+        where T: Serialize + ?Sized {
+        let path = path::join(&self.path, name);
+        let value = value.serialize(Serializer { path : path.clone() })?
+            .ok_or_else(|| error(&path, "variant value is empty"))?;
+        Ok(Some(variant(name, value)))
+    }
+
+    fn serialize_seq(self, len : Option<usize>)
+        -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer {
+            path : self.path,
+            elements : Vec::with_capacity(len.unwrap_or(0))
+        })
+    }
+
+    fn serialize_tuple(self, len : usize) -> Result<SeqSerializer, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name : &'static str, len : usize)
+        -> Result<SeqSerializer, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(self, _name : &'static str, _index : u32,
+        name : &'static str, len : usize)
+        -> Result<VariantSerializer<SeqSerializer>, Error> {
+        let path = path::join(&self.path, name);
+        Ok(VariantSerializer {
+            name,
+            inner : Serializer { path }.serialize_seq(Some(len))?
+        })
+    }
+
+    fn serialize_map(self, _len : Option<usize>)
+        -> Result<GroupSerializer, Error> {
+        Ok(GroupSerializer {
+            path : self.path,
+            members : IndexMap::new(),
+            key : None
+        })
+    }
+
+    fn serialize_struct(self, _name : &'static str, len : usize)
+        -> Result<GroupSerializer, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(self, _name : &'static str, _index : u32,
+        name : &'static str, len : usize)
+        -> Result<VariantSerializer<GroupSerializer>, Error> {
+        let path = path::join(&self.path, name);
+        Ok(VariantSerializer {
+            name,
+            inner : Serializer { path }.serialize_map(Some(len))?
+        })
+    }
+}
+
+/// Elements of array or list.
+struct SeqSerializer {
+    path : String,
+    elements : Vec<Value>
+}
+
+impl SeqSerializer {
+
+    // Serialize collection element, omitted values are not allowed.
+    fn push<T>(&mut self, value : &T) -> Result<(), Error>
+        where T: Serialize + ?Sized {
+        let path = path::join(&self.path,
+            &format!("[{}]", self.elements.len()));
+        let value = value.serialize(Serializer { path : path.clone() })
+            .map_err(|error| locate(&path, error))?
+            .ok_or_else(|| error(&path, "empty values are not supported in \
+                collections"))?;
+        self.elements.push(value);
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value : &T) -> Result<(), Error>
+        where T: Serialize + ?Sized {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<Value>, Error> {
+        Ok(Some(collection(self.elements)))
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value : &T) -> Result<(), Error>
+        where T: Serialize + ?Sized {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<Value>, Error> {
+        Ok(Some(collection(self.elements)))
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value : &T) -> Result<(), Error>
+        where T: Serialize + ?Sized {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<Value>, Error> {
+        Ok(Some(collection(self.elements)))
+    }
+}
+
+/// Members of group.
+struct GroupSerializer {
+    path : String,
+    members : IndexMap<String, Value>,
+    key : Option<String>
+}
+
+impl GroupSerializer {
+
+    // Serialize group member, omitted values are skipped.
+    fn insert<T>(&mut self, name : String, value : &T) -> Result<(), Error>
+        where T: Serialize + ?Sized {
+        let path = path::join(&self.path, &name);
+        let value = value.serialize(Serializer { path : path.clone() })
+            .map_err(|error| locate(&path, error))?;
+        if let Some(value) = value {
+            self.members.insert(name, value);
+        }
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for GroupSerializer {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key : &T) -> Result<(), Error>
+        where T: Serialize + ?Sized {
+        let key = key.serialize(Serializer { path : self.path.clone() })?;
+        self.key = match key {
+            Some(Value::Str(name)) => { Some(name) },
+            Some(Value::Int(name)) => { Some(name.to_string()) },
+            _ => {
+                return Err(error(&self.path,
+                    "group member names must be strings"));
+            }
+        };
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value : &T) -> Result<(), Error>
+        where T: Serialize + ?Sized {
+        let name = self.key.take()
+            .ok_or_else(|| error(&self.path, "member name is missing"))?;
+        self.insert(name, value)
+    }
+
+    fn end(self) -> Result<Option<Value>, Error> {
+        Ok(Some(Value::Group(self.members)))
+    }
+}
+
+impl ser::SerializeStruct for GroupSerializer {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, name : &'static str, value : &T)
+        -> Result<(), Error> where T: Serialize + ?Sized {
+        self.insert(name.to_string(), value)
+    }
+
+    fn end(self) -> Result<Option<Value>, Error> {
+        Ok(Some(Value::Group(self.members)))
+    }
+}
+
+/// Enum variant written as group with single member.
+struct VariantSerializer<S> {
+    name : &'static str,
+    inner : S
+}
+
+impl ser::SerializeTupleVariant for VariantSerializer<SeqSerializer> {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value : &T) -> Result<(), Error>
+        where T: Serialize + ?Sized {
+        self.inner.push(value)
+    }
+
+    fn end(self) -> Result<Option<Value>, Error> {
+        Ok(Some(variant(self.name, collection(self.inner.elements))))
+    }
+}
+
+impl ser::SerializeStructVariant for VariantSerializer<GroupSerializer> {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, name : &'static str, value : &T)
+        -> Result<(), Error> where T: Serialize + ?Sized {
+        self.inner.insert(name.to_string(), value)
+    }
+
+    fn end(self) -> Result<Option<Value>, Error> {
+        Ok(Some(variant(self.name, Value::Group(self.inner.members))))
+    }
+}
+
+impl Config {
+
+    /// Serialize value into setting at path, `serde` feature. Existing
+    /// setting is replaced. Empty path writes members of serialized group
+    /// into root group.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Server {
+    ///     host : String,
+    ///     ports : Vec<u16>
+    /// }
+    ///
+    /// let mut cfg = Config::new();
+    /// let server = Server { host : "localhost".to_string(),
+    ///     ports : vec![80, 443] };
+    /// assert!(cfg.serialize_into("server", &server).is_ok());
+    /// assert_eq!(cfg.save_to_string().unwrap(), "server : \n{\n  \
+    ///     host = \"localhost\";\n  ports = [ 80, 443 ];\n};\n");
+    /// ```
+    pub fn serialize_into<S, T>(&mut self, path : S, value : &T)
+        -> Result<(), Error> where S: AsRef<str>, T: Serialize + ?Sized {
+        let path = path.as_ref();
+        let value = to_value_at(value, path)?;
+        let write_error = |path : &str, cause| {
+            error(path, format!("can't write setting: {:?}", cause))
+        };
+
+        if !path.is_empty() {
+            return self.set(path, value)
+                .map_err(|cause| write_error(path, cause));
+        }
+        match value {
+            Value::Group(members) => {
+                for (name, member) in members {
+                    self.set(&name, member)
+                        .map_err(|cause| write_error(&name, cause))?;
+                }
+                Ok(())
+            },
+            _ => { Err(error(path, "root value must be a group")) }
+        }
+    }
+}
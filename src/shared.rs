@@ -0,0 +1,146 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Config shared between threads.
+//!
+//! `Config` can be moved to another thread, but not used by many threads at
+//! once: readers record reads and fill lookup caches, writers modify
+//! settings through shared reference. `SharedConfig` serializes access, so
+//! both read and write guards hold the config exclusively.
+
+use crate::config::Config;
+
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Config shared between threads. Clones share the same config.
+///
+/// # Example
+/// ```
+/// use librustconfig::config::Config;
+/// use librustconfig::shared::SharedConfig;
+/// use std::thread;
+///
+/// let mut cfg = Config::new();
+/// if cfg.load_from_string("workers = 4;").is_ok() {
+///     let shared = SharedConfig::new(cfg);
+///     let worker = shared.clone();
+///     thread::spawn(move || {
+///         worker.write().set("workers", 8).unwrap();
+///     }).join().unwrap();
+///     assert_eq!(shared.read().int32("workers"), Some(8));
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SharedConfig {
+    config : Arc<Mutex<Config>>
+}
+
+/// Read access to shared config.
+pub struct ReadGuard<'a> {
+    guard : MutexGuard<'a, Config>
+}
+
+/// Write access to shared config.
+pub struct WriteGuard<'a> {
+    guard : MutexGuard<'a, Config>
+}
+
+impl SharedConfig {
+
+    /// Share config between threads.
+    pub fn new(config : Config) -> SharedConfig {
+        SharedConfig { config : Arc::new(Mutex::new(config)) }
+    }
+
+    // Lock config. Panic in another thread can't leave config in invalid
+    // state, so poisoned lock is recovered.
+    fn lock(&self) -> MutexGuard<'_, Config> {
+        self.config.lock().unwrap_or_else(|error| error.into_inner())
+    }
+
+    /// Lock config for reading, blocking until other guards are dropped.
+    pub fn read(&self) -> ReadGuard<'_> {
+        ReadGuard { guard : self.lock() }
+    }
+
+    /// Lock config for writing and loading, blocking until other guards are
+    /// dropped.
+    pub fn write(&self) -> WriteGuard<'_> {
+        WriteGuard { guard : self.lock() }
+    }
+
+    /// Replace shared config, returning the previous one. Threads see either
+    /// the old or the new config, never a partially loaded one.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    /// use librustconfig::shared::SharedConfig;
+    ///
+    /// let shared = SharedConfig::new(Config::new());
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("workers = 4;").is_ok() {
+    ///     shared.replace(cfg);
+    ///     assert_eq!(shared.read().int32("workers"), Some(4));
+    /// }
+    /// ```
+    pub fn replace(&self, config : Config) -> Config {
+        std::mem::replace(&mut *self.lock(), config)
+    }
+}
+
+impl From<Config> for SharedConfig {
+    fn from(config : Config) -> SharedConfig {
+        SharedConfig::new(config)
+    }
+}
+
+impl Deref for ReadGuard<'_> {
+    type Target = Config;
+
+    fn deref(&self) -> &Config {
+        &self.guard
+    }
+}
+
+impl Deref for WriteGuard<'_> {
+    type Target = Config;
+
+    fn deref(&self) -> &Config {
+        &self.guard
+    }
+}
+
+impl DerefMut for WriteGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Config {
+        &mut self.guard
+    }
+}
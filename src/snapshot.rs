@@ -0,0 +1,431 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Read-only config snapshots.
+//!
+//! Snapshot is an owned copy of the whole setting tree with every setting
+//! indexed by its dotted path, so reads don't cross the FFI boundary and
+//! take single hash lookup. Snapshot never changes, it can be shared between
+//! threads.
+//!
+//! Settings are stored in one arena with children of every group or
+//! collection in consecutive nodes, all names, paths and strings are kept in
+//! one buffer. Snapshot of any size takes a few allocations and is dropped
+//! at once.
+
+use crate::config::Config;
+use crate::value::Value;
+use libconfig_sys as raw;
+
+use hashbrown::HashTable;
+use indexmap::IndexMap;
+use std::collections::hash_map::RandomState;
+use std::ffi::CStr;
+use std::fmt::Write;
+use std::hash::BuildHasher;
+use std::ops::Range;
+
+/// Range of snapshot string buffer or node arena.
+#[derive(Debug, Clone, Copy, Default)]
+struct Span {
+    start : u32,
+    len : u32
+}
+
+impl Span {
+    fn range(self) -> Range<usize> {
+        self.start as usize..(self.start + self.len) as usize
+    }
+}
+
+/// Value of setting, aggregates hold span of their children.
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Str(Span),
+    Array(Span),
+    List(Span),
+    Group(Span)
+}
+
+/// Setting stored in arena.
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    path : Span,
+    name : Span,
+    kind : Kind
+}
+
+/// Immutable owned copy of config.
+pub struct Snapshot {
+    // Root group is the first node.
+    nodes : Vec<Node>,
+    strings : String,
+    // Indexes of nodes hashed by their path.
+    index : HashTable<u32>,
+    hasher : RandomState
+}
+
+/// Setting of snapshot.
+#[derive(Clone, Copy)]
+pub struct SnapshotValue<'a> {
+    snapshot : &'a Snapshot,
+    node : &'a Node
+}
+
+/// Snapshot being filled from setting tree.
+struct Builder {
+    nodes : Vec<Node>,
+    strings : String
+}
+
+impl Builder {
+
+    // Append string to buffer.
+    fn push_str(&mut self, value : &str) -> Span {
+        let start = self.strings.len() as u32;
+        self.strings.push_str(value);
+        Span { start, len : value.len() as u32 }
+    }
+
+    // Add children of group or collection into consecutive nodes.
+    fn add_children(&mut self, option : *mut raw::config_setting_t,
+        parent_path : Span) -> Span {
+        let length = unsafe { raw::config_setting_length(option) }.max(0);
+        let start = self.nodes.len() as u32;
+        self.nodes.resize(start as usize + length as usize, Node {
+            path : Span::default(),
+            name : Span::default(),
+            kind : Kind::Group(Span::default())
+        });
+
+        let group = raw::config_setting_is_group(option) == raw::CONFIG_TRUE;
+        for index in 0..length as u32 {
+            let child = unsafe { raw::config_setting_get_elem(option, index) };
+            if child.is_null() {
+                continue;
+            }
+
+            let path_start = self.strings.len();
+            self.strings.extend_from_within(parent_path.range());
+            if parent_path.len != 0 {
+                self.strings.push(crate::path::PATH_SEPARATOR);
+            }
+            let name_start = self.strings.len();
+            let name = raw::config_setting_name(child);
+            if group && !name.is_null() {
+                self.strings.push_str(&unsafe { CStr::from_ptr(name) }
+                    .to_string_lossy());
+            } else {
+                let _ = write!(self.strings, "[{}]", index);
+            }
+
+            let path = Span {
+                start : path_start as u32,
+                len : (self.strings.len() - path_start) as u32
+            };
+            let name = Span {
+                start : name_start as u32,
+                len : (self.strings.len() - name_start) as u32
+            };
+            let kind = self.kind(child, path);
+            self.nodes[(start + index) as usize] = Node { path, name, kind };
+        }
+        Span { start, len : length as u32 }
+    }
+
+    // Read setting value, children of aggregates are added to arena.
+    fn kind(&mut self, option : *mut raw::config_setting_t, path : Span)
+        -> Kind {
+        unsafe {
+            match raw::config_setting_type(option) as i16 {
+                raw::CONFIG_TYPE_INT => {
+                    Kind::Int(raw::config_setting_get_int(option) as i64)
+                },
+                raw::CONFIG_TYPE_INT64 => {
+                    Kind::Int(raw::config_setting_get_int64(option))
+                },
+                raw::CONFIG_TYPE_FLOAT => {
+                    Kind::Float(raw::config_setting_get_float(option))
+                },
+                raw::CONFIG_TYPE_BOOL => {
+                    Kind::Bool(raw::config_setting_get_bool(option) ==
+                        raw::CONFIG_TRUE)
+                },
+                raw::CONFIG_TYPE_STRING => {
+                    let str = raw::config_setting_get_string(option);
+                    if str.is_null() {
+                        Kind::Str(Span::default())
+                    } else {
+                        Kind::Str(self.push_str(&CStr::from_ptr(str)
+                            .to_string_lossy()))
+                    }
+                },
+                raw::CONFIG_TYPE_ARRAY => {
+                    Kind::Array(self.add_children(option, path))
+                },
+                raw::CONFIG_TYPE_LIST => {
+                    Kind::List(self.add_children(option, path))
+                },
+                _ => { Kind::Group(self.add_children(option, path)) }
+            }
+        }
+    }
+}
+
+impl Snapshot {
+
+    // Return string stored in buffer.
+    fn str(&self, span : Span) -> &str {
+        &self.strings[span.range()]
+    }
+
+    // Return view of node.
+    fn view(&self, index : u32) -> SnapshotValue<'_> {
+        SnapshotValue {
+            snapshot : self,
+            node : &self.nodes[index as usize]
+        }
+    }
+
+    /// Return setting at dotted path, collection elements are addressed by
+    /// `[N]` segments. Empty path returns root group.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("ports = [ 80, 81 ];").is_ok() {
+    ///     let snapshot = cfg.to_snapshot();
+    ///     assert_eq!(snapshot.get("ports.[1]").unwrap().as_int(), Some(81));
+    /// }
+    /// ```
+    pub fn get<S>(&self, path : S) -> Option<SnapshotValue<'_>>
+        where S: AsRef<str> {
+        let path = path.as_ref();
+        let hash = self.hasher.hash_one(path);
+        self.index.find(hash, |index| {
+            self.str(self.nodes[*index as usize].path) == path
+        }).map(|index| self.view(*index))
+    }
+
+    /// Return integer value at path.
+    pub fn get_int<S>(&self, path : S) -> Option<i64>
+        where S: AsRef<str> {
+        self.get(path)?.as_int()
+    }
+
+    /// Return float value at path.
+    pub fn get_float<S>(&self, path : S) -> Option<f64>
+        where S: AsRef<str> {
+        self.get(path)?.as_float()
+    }
+
+    /// Return boolean value at path.
+    pub fn get_bool<S>(&self, path : S) -> Option<bool>
+        where S: AsRef<str> {
+        self.get(path)?.as_bool()
+    }
+
+    /// Return string value at path.
+    pub fn get_str<S>(&self, path : S) -> Option<&str>
+        where S: AsRef<str> {
+        self.get(path)?.as_str()
+    }
+
+    /// Return root group.
+    pub fn root(&self) -> SnapshotValue<'_> {
+        self.view(0)
+    }
+
+    /// Return number of settings in snapshot, root group excluded.
+    pub fn len(&self) -> usize {
+        self.nodes.len() - 1
+    }
+
+    /// Return true if snapshot has no settings.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a> SnapshotValue<'a> {
+
+    /// Return full path of setting.
+    pub fn path(&self) -> &'a str {
+        self.snapshot.str(self.node.path)
+    }
+
+    /// Return name of setting, `[N]` for collection elements.
+    pub fn name(&self) -> &'a str {
+        self.snapshot.str(self.node.name)
+    }
+
+    /// Present value as integer.
+    pub fn as_int(&self) -> Option<i64> {
+        match self.node.kind {
+            Kind::Int(value) => { Some(value) },
+            _ => { None }
+        }
+    }
+
+    /// Present value as float.
+    pub fn as_float(&self) -> Option<f64> {
+        match self.node.kind {
+            Kind::Float(value) => { Some(value) },
+            _ => { None }
+        }
+    }
+
+    /// Present value as boolean.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.node.kind {
+            Kind::Bool(value) => { Some(value) },
+            _ => { None }
+        }
+    }
+
+    /// Present value as string.
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self.node.kind {
+            Kind::Str(value) => { Some(self.snapshot.str(value)) },
+            _ => { None }
+        }
+    }
+
+    /// Return true if setting is group.
+    pub fn is_section(&self) -> bool {
+        matches!(self.node.kind, Kind::Group(_))
+    }
+
+    /// Return true if setting is array.
+    pub fn is_array(&self) -> bool {
+        matches!(self.node.kind, Kind::Array(_))
+    }
+
+    /// Return true if setting is list.
+    pub fn is_list(&self) -> bool {
+        matches!(self.node.kind, Kind::List(_))
+    }
+
+    /// Iterate over children of group, array or list.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { host = \"a\"; port = 80; };")
+    ///     .is_ok() {
+    ///     let snapshot = cfg.to_snapshot();
+    ///     for child in snapshot.get("server").unwrap().children() {
+    ///         println!("{} = {:?}", child.name(), child.to_value());
+    ///     }
+    /// }
+    /// ```
+    pub fn children(&self) -> impl Iterator<Item = SnapshotValue<'a>> + 'a {
+        let snapshot = self.snapshot;
+        let span = match self.node.kind {
+            Kind::Array(span) | Kind::List(span) | Kind::Group(span) => {
+                span
+            },
+            _ => { Span::default() }
+        };
+        span.range().map(move |index| snapshot.view(index as u32))
+    }
+
+    /// Copy setting with all its children into owned value.
+    pub fn to_value(&self) -> Value {
+        match self.node.kind {
+            Kind::Int(value) => { Value::Int(value) },
+            Kind::Float(value) => { Value::Float(value) },
+            Kind::Bool(value) => { Value::Bool(value) },
+            Kind::Str(value) => {
+                Value::Str(self.snapshot.str(value).to_string())
+            },
+            Kind::Array(_) => {
+                Value::Array(self.children().map(|c| c.to_value()).collect())
+            },
+            Kind::List(_) => {
+                Value::List(self.children().map(|c| c.to_value()).collect())
+            },
+            Kind::Group(_) => {
+                Value::Group(self.children().map(|c| {
+                    (c.name().to_string(), c.to_value())
+                }).collect::<IndexMap<_, _>>())
+            }
+        }
+    }
+}
+
+impl Config {
+
+    /// Create immutable owned copy of config with settings indexed by path.
+    /// Later changes of config are not visible in snapshot.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { port = 80; };").is_ok() {
+    ///     let snapshot = cfg.to_snapshot();
+    ///     assert_eq!(snapshot.get_int("server.port"), Some(80));
+    /// }
+    /// ```
+    pub fn to_snapshot(&self) -> Snapshot {
+        let mut builder = Builder {
+            nodes : vec![Node {
+                path : Span::default(),
+                name : Span::default(),
+                kind : Kind::Group(Span::default())
+            }],
+            strings : String::new()
+        };
+        if let Some(root) = self.root_element {
+            let kind = builder.kind(root, Span::default());
+            builder.nodes[0].kind = kind;
+        }
+
+        let Builder { nodes, strings } = builder;
+        let hasher = RandomState::new();
+        let mut index = HashTable::with_capacity(nodes.len());
+        for (position, node) in nodes.iter().enumerate() {
+            let hash = hasher.hash_one(&strings[node.path.range()]);
+            index.insert_unique(hash, position as u32, |index| {
+                hasher.hash_one(&strings[nodes[*index as usize].path.range()])
+            });
+        }
+        Snapshot { nodes, strings, index, hasher }
+    }
+}
@@ -0,0 +1,177 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Typed settings access independent of storage.
+//!
+//! Application code reading settings through `ConfigSource` can be tested
+//! with plain `HashMap` of values instead of config files.
+
+use crate::config::{Config, OptionType};
+use crate::value::Value;
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// Typed getters of settings by path. Getters return `None` if setting not
+/// exists or has another type.
+pub trait ConfigSource {
+
+    /// Return setting value with all its children.
+    fn get_value(&self, path : &str) -> Option<Value>;
+
+    /// Return true if setting exists.
+    fn contains(&self, path : &str) -> bool {
+        self.get_value(path).is_some()
+    }
+
+    /// Return integer setting value fitting into i32.
+    fn get_int32(&self, path : &str) -> Option<i32> {
+        match self.get_value(path) {
+            Some(Value::Int(val)) => { i32::try_from(val).ok() },
+            _ => { None }
+        }
+    }
+
+    /// Return integer setting value.
+    fn get_int64(&self, path : &str) -> Option<i64> {
+        match self.get_value(path) {
+            Some(Value::Int(val)) => { Some(val) },
+            _ => { None }
+        }
+    }
+
+    /// Return float setting value.
+    fn get_float64(&self, path : &str) -> Option<f64> {
+        match self.get_value(path) {
+            Some(Value::Float(val)) => { Some(val) },
+            _ => { None }
+        }
+    }
+
+    /// Return boolean setting value.
+    fn get_bool(&self, path : &str) -> Option<bool> {
+        match self.get_value(path) {
+            Some(Value::Bool(val)) => { Some(val) },
+            _ => { None }
+        }
+    }
+
+    /// Return string setting value.
+    fn get_string(&self, path : &str) -> Option<String> {
+        match self.get_value(path) {
+            Some(Value::Str(val)) => { Some(val) },
+            _ => { None }
+        }
+    }
+}
+
+impl ConfigSource for Config {
+
+    fn get_value(&self, path : &str) -> Option<Value> {
+        self.read_many(&[path]).pop().flatten()
+    }
+
+    fn contains(&self, path : &str) -> bool {
+        self.value(path).is_some()
+    }
+
+    fn get_int32(&self, path : &str) -> Option<i32> {
+        let option = self.value(path)?;
+        match option.value_type()? {
+            OptionType::IntegerType => { option.as_int32() },
+            OptionType::Int64Type => {
+                i32::try_from(option.as_int64()?).ok()
+            },
+            _ => { None }
+        }
+    }
+
+    fn get_int64(&self, path : &str) -> Option<i64> {
+        let option = self.value(path)?;
+        match option.value_type()? {
+            OptionType::IntegerType | OptionType::Int64Type => {
+                option.as_int64()
+            },
+            _ => { None }
+        }
+    }
+
+    fn get_float64(&self, path : &str) -> Option<f64> {
+        let option = self.value(path)?;
+        match option.value_type()? {
+            OptionType::FloatType => { option.as_float64() },
+            _ => { None }
+        }
+    }
+
+    fn get_bool(&self, path : &str) -> Option<bool> {
+        let option = self.value(path)?;
+        match option.value_type()? {
+            OptionType::BooleanType => { option.as_bool() },
+            _ => { None }
+        }
+    }
+
+    fn get_string(&self, path : &str) -> Option<String> {
+        let option = self.value(path)?;
+        match option.value_type()? {
+            OptionType::StringType => { option.as_string() },
+            _ => { None }
+        }
+    }
+}
+
+/// In-memory settings keyed by full path, for tests.
+///
+/// # Example
+/// ```
+/// use librustconfig::source::ConfigSource;
+/// use librustconfig::value::Value;
+/// use std::collections::HashMap;
+///
+/// fn listen_port(source : &impl ConfigSource) -> i32 {
+///     source.get_int32("server.port").unwrap_or(80)
+/// }
+///
+/// let mut mock = HashMap::new();
+/// mock.insert("server.port".to_string(), Value::from(8080));
+/// assert_eq!(listen_port(&mock), 8080);
+/// assert_eq!(listen_port(&HashMap::new()), 80);
+/// ```
+impl ConfigSource for HashMap<String, Value> {
+
+    fn get_value(&self, path : &str) -> Option<Value> {
+        self.get(path).cloned()
+    }
+
+    fn contains(&self, path : &str) -> bool {
+        self.contains_key(path)
+    }
+}
@@ -0,0 +1,162 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Config size statistics.
+
+use crate::config::Config;
+use libconfig_sys as raw;
+
+use std::ffi::CStr;
+use std::mem;
+
+/// Counts and sizes of config settings.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stats {
+    /// Number of groups, root group excluded.
+    pub groups : usize,
+    /// Number of arrays.
+    pub arrays : usize,
+    /// Number of lists.
+    pub lists : usize,
+    /// Number of 32-bit integers.
+    pub integers : usize,
+    /// Number of 64-bit integers.
+    pub integers64 : usize,
+    /// Number of floats.
+    pub floats : usize,
+    /// Number of booleans.
+    pub booleans : usize,
+    /// Number of strings.
+    pub strings : usize,
+    /// Deepest nesting level, settings of root group have level 1.
+    pub max_depth : usize,
+    /// Total length of string values in bytes.
+    pub string_bytes : usize,
+    /// Total length of setting names in bytes.
+    pub name_bytes : usize,
+    /// Estimate of memory held by libconfig for the settings in bytes.
+    pub estimated_bytes : usize
+}
+
+impl Stats {
+
+    /// Return total number of settings.
+    pub fn settings(&self) -> usize {
+        self.groups + self.arrays + self.lists + self.integers +
+            self.integers64 + self.floats + self.booleans + self.strings
+    }
+
+    // Add setting and all its children.
+    fn add(&mut self, option : *mut raw::config_setting_t, depth : usize) {
+        self.estimated_bytes += mem::size_of::<raw::config_setting_t>();
+
+        let name = raw::config_setting_name(option);
+        if !name.is_null() {
+            let length = unsafe { CStr::from_ptr(name) }.to_bytes().len();
+            self.name_bytes += length;
+            self.estimated_bytes += length + 1;
+        }
+
+        match raw::config_setting_type(option) as i16 {
+            raw::CONFIG_TYPE_INT => { self.integers += 1; },
+            raw::CONFIG_TYPE_INT64 => { self.integers64 += 1; },
+            raw::CONFIG_TYPE_FLOAT => { self.floats += 1; },
+            raw::CONFIG_TYPE_BOOL => { self.booleans += 1; },
+            raw::CONFIG_TYPE_STRING => {
+                self.strings += 1;
+                let str = unsafe { raw::config_setting_get_string(option) };
+                if !str.is_null() {
+                    let length = unsafe { CStr::from_ptr(str) }.to_bytes()
+                        .len();
+                    self.string_bytes += length;
+                    self.estimated_bytes += length + 1;
+                }
+            },
+            setting_type => {
+                if depth > 0 {
+                    match setting_type {
+                        raw::CONFIG_TYPE_ARRAY => { self.arrays += 1; },
+                        raw::CONFIG_TYPE_LIST => { self.lists += 1; },
+                        _ => { self.groups += 1; }
+                    }
+                }
+                self.add_children(option, depth);
+            }
+        }
+    }
+
+    // Add children of group or collection.
+    fn add_children(&mut self, option : *mut raw::config_setting_t,
+        depth : usize) {
+        let length = unsafe { raw::config_setting_length(option) }.max(0)
+            as usize;
+        if length > 0 {
+            self.estimated_bytes += mem::size_of::<raw::config_list_t>() +
+                length * mem::size_of::<*mut raw::config_setting_t>();
+            self.max_depth = self.max_depth.max(depth + 1);
+        }
+
+        for index in 0..length {
+            let child = unsafe {
+                raw::config_setting_get_elem(option, index as u32)
+            };
+            if !child.is_null() {
+                self.add(child, depth + 1);
+            }
+        }
+    }
+}
+
+impl Config {
+
+    /// Return counts of settings by type, nesting depth, size of names and
+    /// strings and estimate of memory used by config.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { host = \"web\"; port = 80; };")
+    ///     .is_ok() {
+    ///     let stats = cfg.stats();
+    ///     assert_eq!(stats.settings(), 3);
+    ///     assert_eq!(stats.string_bytes, 3);
+    ///     assert_eq!(stats.max_depth, 2);
+    /// }
+    /// ```
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats::default();
+        if let Some(root) = self.root_element {
+            stats.add(root, 0);
+        }
+        stats
+    }
+}
@@ -0,0 +1,133 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! proptest strategies of random config trees.
+//!
+//! Generated values are valid libconfig settings: names are identifiers,
+//! arrays hold scalars of one type, floats are finite and strings have no
+//! NUL characters.
+
+use crate::format;
+use crate::value::Value;
+
+use indexmap::IndexMap;
+use proptest::prelude::*;
+
+/// Maximal nesting depth of generated trees.
+const MAX_DEPTH : u32 = 4;
+
+/// Maximal number of elements of generated groups and collections.
+const MAX_ELEMENTS : usize = 6;
+
+/// Setting name.
+pub fn name() -> impl Strategy<Value = String> {
+    "[a-zA-Z][a-zA-Z0-9_-]{0,7}"
+}
+
+// Finite float.
+fn float() -> impl Strategy<Value = f64> {
+    any::<f64>().prop_filter("finite float", |val| val.is_finite())
+}
+
+// String without NUL characters.
+fn string() -> impl Strategy<Value = String> {
+    "[^\\x00]{0,16}"
+}
+
+/// Integer, float, boolean or string value.
+pub fn scalar() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        any::<i32>().prop_map(|val| Value::Int(val as i64)),
+        any::<i64>().prop_map(Value::Int),
+        float().prop_map(Value::Float),
+        any::<bool>().prop_map(Value::Bool),
+        string().prop_map(Value::Str)
+    ]
+}
+
+/// Array of scalars of one type.
+pub fn array() -> impl Strategy<Value = Value> {
+    let elements = |element : BoxedStrategy<Value>| {
+        proptest::collection::vec(element, 0..MAX_ELEMENTS)
+            .prop_map(Value::Array)
+    };
+
+    prop_oneof![
+        elements(any::<i32>().prop_map(|val| Value::Int(val as i64))
+            .boxed()),
+        elements(any::<i64>().prop_filter("int64 value", |val| {
+            *val < i32::MIN as i64 || *val > i32::MAX as i64
+        }).prop_map(Value::Int).boxed()),
+        elements(float().prop_map(Value::Float).boxed()),
+        elements(any::<bool>().prop_map(Value::Bool).boxed()),
+        elements(string().prop_map(Value::Str).boxed())
+    ]
+}
+
+/// Group of named values.
+pub fn group<S>(element : S) -> impl Strategy<Value = Value>
+    where S: Strategy<Value = Value> {
+    proptest::collection::vec((name(), element), 0..MAX_ELEMENTS)
+        .prop_map(|members| {
+            Value::Group(members.into_iter().collect::<IndexMap<_, _>>())
+        })
+}
+
+/// Any setting value, including nested groups and collections.
+pub fn value() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![scalar(), array()];
+    leaf.prop_recursive(MAX_DEPTH, 64, MAX_ELEMENTS as u32, |inner| {
+        prop_oneof![
+            array(),
+            proptest::collection::vec(inner.clone(), 0..MAX_ELEMENTS)
+                .prop_map(Value::List),
+            group(inner)
+        ]
+    })
+}
+
+/// Root group of config.
+pub fn document() -> impl Strategy<Value = Value> {
+    group(value())
+}
+
+/// Root group of config with its text in libconfig format.
+pub fn document_text() -> impl Strategy<Value = (Value, String)> {
+    document().prop_map(|root| {
+        let mut text = String::new();
+        if let Value::Group(members) = &root {
+            for (name, member) in members.iter() {
+                // Generated floats are finite, formatting can't fail.
+                let _ = format::write_setting(&mut text, name, member, 0);
+            }
+        }
+        (root, text)
+    })
+}
@@ -28,9 +28,18 @@
 /*                                                                            */
 /******************************************************************************/
 
-use crate::config::{Config, OptionType};
+use crate::build;
+use crate::config::{Config, Errors, LineEnding, OptionType, ParseErrorKind};
+use crate::diff::{Change, ChangeKind};
+use crate::key::Key;
+use crate::schema::{Coercion, Schema, ValidationError, ValidationErrorKind};
+use crate::source::ConfigSource;
+use crate::value::Value;
+use crate::watch::Watcher;
 use std::path::Path;
 use std::fs;
+use std::cell::RefCell;
+use std::rc::Rc;
 
 macro_rules! assert_delta {
     ($x:expr, $y:expr, $d:expr) => {
@@ -82,7 +91,7 @@ fn test_parse_config_string() {
 #[test]
 fn test_create_section() {
     let mut cfg = Config::new();
-    let root = cfg.create_section("root_section").unwrap();
+    let mut root = cfg.create_section("root_section").unwrap();
     let group = root.create_section("group").unwrap();
     let mut _val_i32 = group.write_int32("test", 123);
     let mut _val_i64 = group.write_int64("test2", 100000002);
@@ -115,40 +124,41 @@ fn test_create_section() {
 
 #[test]
 fn test_delete_element() {
-    let cfg = Config::new();
-    let root = cfg.create_section("root_section").unwrap();
+    let mut cfg = Config::new();
+    let mut root = cfg.create_section("root_section").unwrap();
     let group = root.create_section("group").unwrap();
-    group.write_int32("some_value", 11);
-    group.write_int32("another_value", -123);
+    group.write_int32("some_value", 11).unwrap();
+    group.write_int32("another_value", -123).unwrap();
 
     assert_eq!(cfg.value("root_section.group.some_value").unwrap()
         .as_int32().unwrap(), 11);
     assert_eq!(cfg.value("root_section.group.another_value").unwrap()
         .as_int32().unwrap(), -123);
     
-    assert_eq!(cfg.value("root_section.group.some_value").unwrap()
+    assert_eq!(cfg.writer("root_section.group.some_value").unwrap()
         .delete().is_ok(), true);
     assert_eq!(cfg.value("root_section.group.some_value").is_none(), true);
 
-    assert_eq!(cfg.value("root_section.group").unwrap().delete().is_ok(), true);
+    assert_eq!(cfg.writer("root_section.group").unwrap().delete().is_ok(),
+        true);
     assert_eq!(cfg.value("root_section.group").is_none(), true);
 }
 
 #[test]
 fn test_collection_options() {
-    let cfg = Config::new();
-    let root = cfg.create_section("root").unwrap();
-    let group = root.create_section("group").unwrap();
+    let mut cfg = Config::new();
+    let mut root = cfg.create_section("root").unwrap();
+    let mut group = root.create_section("group").unwrap();
 
     let array = group.create_array("array").unwrap();
-    array.write_int32(123);
-    array.write_int32(321);
-    array.write_int32(411);
+    array.write_int32(123).unwrap();
+    array.write_int32(321).unwrap();
+    array.write_int32(411).unwrap();
 
     let list = group.create_list("list").unwrap();
-    list.write_string("value1");
-    list.write_string("value2");
-    list.write_string("value3");
+    list.write_string("value1").unwrap();
+    list.write_string("value2").unwrap();
+    list.write_string("value3").unwrap();
 
     let mut counter = 0;
     for val in cfg.value("root.group.array").unwrap().as_array() {
@@ -171,4 +181,2816 @@ fn test_collection_options() {
         }
         counter += 1;
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_load_from_kv_pairs() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_kv_pairs("app/", vec![
+        ("app/server/port", "8080"),
+        ("app/server/debug", "true"),
+        ("app/server/ratio", "0.5"),
+        ("app/server/name", "main server"),
+        ("app/server/hosts/1", "beta"),
+        ("app/server/hosts/0", "alpha"),
+        ("other/value", "ignored")
+    ]).is_ok());
+
+    assert_eq!(cfg.value("server.port").unwrap().value_type().unwrap(),
+        OptionType::IntegerType);
+    assert_eq!(cfg.value("server.port").unwrap().as_int32().unwrap(), 8080);
+    assert!(cfg.value("server.debug").unwrap().as_bool().unwrap());
+    assert_delta!(cfg.value("server.ratio").unwrap().as_float64().unwrap(),
+        0.5, 0.00001);
+    assert_eq!(cfg.value("server.name").unwrap().as_string().unwrap(),
+        "main server");
+    assert!(cfg.value("server.hosts").unwrap().is_list().unwrap());
+    assert_eq!(cfg.value("server.hosts.[0]").unwrap().as_string().unwrap(),
+        "alpha");
+    assert_eq!(cfg.value("server.hosts.[1]").unwrap().as_string().unwrap(),
+        "beta");
+    assert!(cfg.value("value").is_none());
+
+    assert!(cfg.load_from_kv_pairs("app/", vec![("app/a", "1"),
+        ("app/a/b", "2")]).is_err());
+    assert!(cfg.load_from_kv_pairs("app/", vec![("app/a", "1"),
+        ("app/0", "2")]).is_err());
+    assert!(cfg.load_from_kv_pairs("app/", vec![("app/a", "1"),
+        ("app/b/0", "2"), ("app/b/c", "3")]).is_err());
+    assert_eq!(cfg.load_from_kv_pairs("app/", vec![("app/a", "1"),
+        ("app/b\0c", "2")]), Err(Errors::InvalidString));
+    assert_eq!(cfg.value("server.port").unwrap().as_int32(), Some(8080));
+    assert!(cfg.value("a").is_none());
+
+    cfg.set_format_options(crate::config::FormatOptions::new().tab_width(4));
+    assert!(cfg.load_from_kv_pairs("app/", vec![("app/b/c", "1")]).is_ok());
+    assert!(cfg.value("server").is_none());
+    assert_eq!(cfg.save_to_string().unwrap(), "b : \n{\n    c = 1;\n};\n");
+}
+
+#[cfg(feature = "kv")]
+#[test]
+fn test_kv_watch_retry() {
+    use crate::kv::{self, KvBackend, KvSnapshot};
+
+    struct Backend {
+        replies : RefCell<Vec<Result<KvSnapshot, Errors>>>
+    }
+
+    impl KvBackend for Backend {
+        fn fetch(&self, _prefix : &str) -> Result<KvSnapshot, Errors> {
+            Ok(KvSnapshot {
+                index : 1,
+                entries : vec![("app/port".to_string(), "80".to_string())]
+            })
+        }
+
+        fn wait(&self, _prefix : &str, _index : u64)
+            -> Result<KvSnapshot, Errors> {
+            self.replies.borrow_mut().remove(0)
+        }
+    }
+
+    let backend = Backend {
+        replies : RefCell::new(vec![
+            Err(Errors::BackendError),
+            Ok(KvSnapshot {
+                index : 2,
+                entries : vec![("app/port".to_string(), "81".to_string())]
+            }),
+            Err(Errors::ParseError)
+        ])
+    };
+    let mut ports = Vec::new();
+    assert_eq!(kv::watch(&backend, "app/", |cfg| {
+        ports.push(cfg.value("port").unwrap().as_int32());
+        true
+    }), Err(Errors::ParseError));
+    assert_eq!(ports, vec![Some(81)]);
+}
+
+#[test]
+fn test_interpolate() {
+    std::env::set_var("LIBRUSTCONFIG_TEST_USER", "admin");
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "paths : {
+            base = \"/opt/app\";
+            logs = \"${paths.logs_dir}/${LIBRUSTCONFIG_TEST_USER}\";
+            logs_dir = \"${paths.base}/logs\";
+            literal = \"$${paths.base} costs $5\";
+        };
+        port = 8080;
+        url = \"http://localhost:${port}/\";"
+    ).is_ok());
+    assert_eq!(cfg.interpolate(), Ok(true));
+
+    assert_eq!(cfg.value("paths.logs").unwrap().as_string().unwrap(),
+        "/opt/app/logs/admin");
+    assert_eq!(cfg.value("paths.literal").unwrap().as_string().unwrap(),
+        "${paths.base} costs $5");
+    assert_eq!(cfg.value("url").unwrap().as_string().unwrap(),
+        "http://localhost:8080/");
+    assert!(cfg.set("late", "${port}").is_ok());
+    assert_eq!(cfg.interpolate(), Ok(false));
+    assert_eq!(cfg.value("paths.literal").unwrap().as_string().unwrap(),
+        "${paths.base} costs $5");
+    assert_eq!(cfg.value("late").unwrap().as_string().unwrap(), "${port}");
+
+    assert!(cfg.load_from_string(
+        "a = \"${b}\";
+        b = \"${a}\";"
+    ).is_ok());
+    assert_eq!(cfg.interpolate(), Err(Errors::RecursiveVariable));
+    assert_eq!(cfg.value("a").unwrap().as_string().unwrap(), "${b}");
+
+    assert!(cfg.load_from_string("a = \"${LIBRUSTCONFIG_NOT_SET}\";").is_ok());
+    assert_eq!(cfg.interpolate(), Err(Errors::UnresolvedVariable));
+
+    assert!(cfg.load_from_string("a = \"\"; b = \"x\";").is_ok());
+    let text = std::ffi::CString::new(b"\xFF${b}".to_vec()).unwrap();
+    unsafe {
+        libconfig_sys::config_setting_set_string(
+            cfg.value("a").unwrap().element.unwrap(), text.as_ptr());
+    }
+    assert_eq!(cfg.interpolate(), Err(Errors::InvalidString));
+    assert!(cfg.value("a").unwrap().as_string().is_none());
+}
+
+#[test]
+fn test_references() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "paths : { base = \"/opt/app\"; };
+        logs : { dir = \"@paths.base\"; level = \"@@debug\"; };
+        alias = \"@logs\";
+        loop_a = \"@loop_b\";
+        loop_b = \"@loop_a\";"
+    ).is_ok());
+
+    assert_eq!(cfg.value("logs.dir").unwrap().as_string().unwrap(),
+        "@paths.base");
+    assert!(cfg.value("logs.dir").unwrap().is_reference().unwrap());
+    assert_eq!(cfg.value("logs.dir").unwrap().resolve().unwrap()
+        .as_string().unwrap(), "/opt/app");
+    assert_eq!(cfg.value("logs.level").unwrap().as_str(), Some("@@debug"));
+    assert!(!cfg.value("logs.level").unwrap().is_reference().unwrap());
+    assert_eq!(cfg.value("logs.level").unwrap().resolve().unwrap().as_str(),
+        Some("@debug"));
+
+    cfg.resolve_references(true);
+    assert_eq!(cfg.value("logs.dir").unwrap().as_string().unwrap(),
+        "/opt/app");
+    assert_eq!(cfg.value("alias.dir").unwrap().as_string().unwrap(),
+        "/opt/app");
+    assert_eq!(cfg.value("logs.level").unwrap().as_string().unwrap(),
+        "@debug");
+    assert_eq!(cfg["logs"]["level"].as_str(), Some("@debug"));
+    assert_eq!(cfg.value("alias").unwrap().children()
+        .find(|option| option.name() == Some("level")).unwrap()
+        .as_str(), Some("@debug"));
+    assert!(cfg.value("loop_a").is_none());
+
+    cfg.resolve_references(false);
+    assert_eq!(cfg["logs"]["level"].as_str(), Some("@@debug"));
+}
+
+#[test]
+fn test_schema_validate() {
+    let schema = Schema::new()
+        .require("server.port", OptionType::IntegerType)
+            .range(1.0, 65535.0)
+        .require("server.name", OptionType::StringType)
+        .optional("server.host", OptionType::StringType)
+            .default_value("localhost")
+        .optional("server.timeout", OptionType::FloatType)
+        .require("database.url", OptionType::StringType);
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "server : {
+            port = 70000;
+            name = 12;
+            timeout = 1.5;
+        };"
+    ).is_ok());
+
+    let errors = cfg.validate(&schema);
+    assert_eq!(errors.len(), 3);
+    assert_eq!(errors[0], ValidationError {
+        path : "server.port".to_string(),
+        kind : ValidationErrorKind::OutOfRange
+    });
+    assert_eq!(errors[1], ValidationError {
+        path : "server.name".to_string(),
+        kind : ValidationErrorKind::WrongType {
+            expected : OptionType::StringType,
+            found : Some(OptionType::IntegerType)
+        }
+    });
+    assert_eq!(errors[2], ValidationError {
+        path : "database.url".to_string(),
+        kind : ValidationErrorKind::Missing
+    });
+
+    assert!(cfg.apply_defaults(&schema).is_ok());
+    assert_eq!(cfg.value("server.host").unwrap().as_string().unwrap(),
+        "localhost");
+}
+
+#[test]
+fn test_schema_from_config() {
+    let mut schema_cfg = Config::new();
+    assert!(schema_cfg.load_from_string(
+        "coercion = \"warn\";
+        server : {
+            port : { type = \"int\"; required = true; min = 1;
+                max = 65535; description = \"TCP port.\"; };
+            mode : { type = \"string\"; one_of = [ \"fast\", \"safe\" ];
+                default = \"safe\"; };
+        };
+        database : { url : { type = \"string\"; required = true; }; };"
+    ).is_ok());
+    let schema = Schema::from_config(&schema_cfg).unwrap();
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "server : { port = \"70000\"; mode = \"slow\"; };").is_ok());
+    let errors = cfg.validate(&schema);
+    assert_eq!(errors.iter().map(|error| error.kind.clone())
+        .collect::<Vec<_>>(), vec![
+        ValidationErrorKind::OutOfRange,
+        ValidationErrorKind::NotAllowed,
+        ValidationErrorKind::Missing
+    ]);
+    assert!(schema.render_example().contains("mode = \"safe\";"));
+
+    assert!(schema_cfg.load_from_string(
+        "port : { type = \"int\"; maximum = 10; };").is_ok());
+    assert_eq!(Schema::from_config(&schema_cfg).err(),
+        Some(Errors::ParseError));
+    assert!(schema_cfg.load_from_string("port : { type = \"number\"; };")
+        .is_ok());
+    assert_eq!(Schema::from_config(&schema_cfg).err(),
+        Some(Errors::ParseError));
+}
+
+#[test]
+fn test_schema_coercion() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "port = \"8080\";
+        debug = \"TRUE\";
+        level = \"verbose\";
+        workers = \"many\";"
+    ).is_ok());
+
+    let schema = Schema::new()
+        .require("port", OptionType::IntegerType).range(1.0, 65535.0)
+        .require("debug", OptionType::BooleanType)
+        .require("level", OptionType::StringType)
+            .one_of(&["debug", "info"])
+        .optional("workers", OptionType::IntegerType);
+
+    let errors = cfg.validate(&schema);
+    assert_eq!(errors.len(), 4);
+    assert!(errors.iter().all(|error| !error.is_warning()));
+
+    let schema = schema.coercion(Coercion::Warn);
+    let errors = cfg.validate(&schema);
+    assert_eq!(errors.len(), 4);
+    assert_eq!(errors[0].kind, ValidationErrorKind::Coerced {
+        from : OptionType::StringType
+    });
+    assert!(errors[1].is_warning());
+    assert_eq!(errors[2].kind, ValidationErrorKind::NotAllowed);
+    assert_eq!(errors[3].kind, ValidationErrorKind::WrongType {
+        expected : OptionType::IntegerType,
+        found : Some(OptionType::StringType)
+    });
+
+    assert!(cfg.coerce(&schema).is_ok());
+    assert_eq!(cfg.value("port").unwrap().as_int32().unwrap(), 8080);
+    assert!(cfg.value("debug").unwrap().as_bool().unwrap());
+    assert_eq!(cfg.value("workers").unwrap().as_string().unwrap(), "many");
+    assert_eq!(cfg.validate(&schema.coercion(Coercion::Strict)).len(), 2);
+    assert_eq!(cfg.save_to_string().unwrap().lines().next(),
+        Some("port = 8080;"));
+
+    assert!(cfg.load_from_string(
+        "limits = ( \"10\", \"20\", \"30\" );
+        ports = [ \"80\", \"443\" ];"
+    ).is_ok());
+    let schema = Schema::new()
+        .coercion(Coercion::Allow)
+        .require("limits.[1]", OptionType::IntegerType)
+        .require("ports.[0]", OptionType::IntegerType);
+    assert!(cfg.coerce(&schema).is_ok());
+    assert_eq!(cfg.value("limits.[0]").unwrap().as_str(), Some("10"));
+    assert_eq!(cfg.value("limits.[1]").unwrap().as_int32(), Some(20));
+    assert_eq!(cfg.value("limits.[2]").unwrap().as_str(), Some("30"));
+    assert_eq!(cfg.value("ports.[0]").unwrap().as_str(), Some("80"));
+}
+
+#[test]
+fn test_schema_to_json_schema() {
+    let schema = Schema::new()
+        .require("server.port", OptionType::IntegerType)
+            .range(1.0, 65535.0)
+        .optional("server.host", OptionType::StringType)
+            .default_value("localhost")
+        .optional("log.level", OptionType::StringType)
+            .one_of(&["debug", "info"]);
+
+    assert_eq!(schema.to_json_schema(),
+"{
+  \"$schema\": \"https://json-schema.org/draft/2020-12/schema\",
+  \"type\": \"object\",
+  \"properties\": {
+    \"server\": {
+      \"type\": \"object\",
+      \"properties\": {
+        \"port\": {
+          \"type\": \"integer\",
+          \"minimum\": 1,
+          \"maximum\": 65535
+        },
+        \"host\": {
+          \"type\": \"string\",
+          \"default\": \"localhost\"
+        }
+      },
+      \"required\": [
+        \"port\"
+      ]
+    },
+    \"log\": {
+      \"type\": \"object\",
+      \"properties\": {
+        \"level\": {
+          \"type\": \"string\",
+          \"enum\": [
+            \"debug\",
+            \"info\"
+          ]
+        }
+      }
+    }
+  },
+  \"required\": [
+    \"server\"
+  ]
+}
+");
+}
+
+#[test]
+fn test_schema_lint() {
+    let schema = Schema::new()
+        .require("server.port", OptionType::IntegerType)
+        .optional("server.hosts", OptionType::StringType)
+        .optional("debug", OptionType::BooleanType);
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "server : {
+            prot = 8080;
+            hosts = \"a\";
+            backlog = 10;
+        };
+        debgu = true;
+        cache : { size = 10; };"
+    ).is_ok());
+
+    assert_eq!(cfg.lint(&schema), vec![
+        ValidationError {
+            path : "server.prot".to_string(),
+            kind : ValidationErrorKind::Unknown {
+                suggestion : Some("server.port".to_string())
+            }
+        },
+        ValidationError {
+            path : "server.backlog".to_string(),
+            kind : ValidationErrorKind::Unknown { suggestion : None }
+        },
+        ValidationError {
+            path : "debgu".to_string(),
+            kind : ValidationErrorKind::Unknown {
+                suggestion : Some("debug".to_string())
+            }
+        },
+        ValidationError {
+            path : "cache".to_string(),
+            kind : ValidationErrorKind::Unknown { suggestion : None }
+        }
+    ]);
+}
+
+#[test]
+fn test_schema_deny_unknown() {
+    let schema = Schema::new()
+        .require("server.port", OptionType::IntegerType)
+        .optional("debug", OptionType::BooleanType);
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("server : { prot = 8080; };\n\
+        debug = true;\ncache : { size = 10; };").is_ok());
+    assert_eq!(cfg.validate(&schema).len(), 1);
+
+    let errors = cfg.validate(&schema.deny_unknown(true));
+    assert_eq!(errors.iter().map(|error| (error.path.as_str(),
+        error.kind.clone())).collect::<Vec<_>>(), vec![
+        ("server.port", ValidationErrorKind::Missing),
+        ("server.prot", ValidationErrorKind::Unknown {
+            suggestion : Some("server.port".to_string())
+        }),
+        ("cache", ValidationErrorKind::Unknown { suggestion : None })
+    ]);
+
+    let mut schema_cfg = Config::new();
+    assert!(schema_cfg.load_from_string("deny_unknown = true;\n\
+        debug : { type = \"bool\"; };").is_ok());
+    let schema = Schema::from_config(&schema_cfg).unwrap();
+    assert_eq!(cfg.validate(&schema).len(), 2);
+    assert!(schema_cfg.load_from_string("deny_unknown = 1;").is_ok());
+    assert!(Schema::from_config(&schema_cfg).is_err());
+}
+
+#[test]
+fn test_schema_write_default_config() {
+    let schema = Schema::new()
+        .require("server.port", OptionType::IntegerType)
+            .description("TCP port to listen on.")
+            .default_value(8080)
+        .optional("server.host", OptionType::StringType)
+        .optional("workers", OptionType::IntegerType)
+            .range(1.0, 64.0)
+        .optional("log.level", OptionType::StringType)
+            .description("Minimal level of logged messages.")
+            .one_of(&["info", "error"])
+        .optional("log.max_size", OptionType::Int64Type)
+        .optional("ratio", OptionType::FloatType)
+            .default_value(1);
+
+    let file_name = Path::new("default_config_test.cfg");
+    assert!(schema.write_default_config(file_name).is_ok());
+    let text = fs::read_to_string(file_name).unwrap();
+    let _ = fs::remove_file(file_name);
+
+    assert_eq!(text,
+"server :
+{
+  # TCP port to listen on.
+  port = 8080;
+
+  host = \"\";
+};
+
+workers = 1;
+
+log :
+{
+  # Minimal level of logged messages.
+  level = \"info\";
+
+  max_size = 0L;
+};
+
+ratio = 1.0;
+");
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(&text).is_ok());
+    assert!(cfg.validate(&schema).is_empty());
+    assert_eq!(cfg.value("log.max_size").unwrap().as_int64(), Some(0));
+}
+
+#[test]
+fn test_extract() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "plugins : {
+            redis : {
+                port = 6379;
+                max_memory = 100L;
+                hosts = [ \"a\", \"b\" ];
+                pool : { size = 4; };
+            };
+        };"
+    ).is_ok());
+
+    let mut redis = cfg.extract("plugins.redis").unwrap();
+    assert_eq!(redis.value("port").unwrap().as_int32(), Some(6379));
+    assert_eq!(redis.value("max_memory").unwrap().value_type(),
+        Some(OptionType::Int64Type));
+    assert_eq!(redis.value("hosts.[1]").unwrap().as_string(),
+        Some("b".to_string()));
+    assert_eq!(redis.value("pool.size").unwrap().as_int32(), Some(4));
+
+    assert!(redis.writer("port").unwrap().delete().is_ok());
+    assert!(redis.value("port").is_none());
+    assert_eq!(cfg.value("plugins.redis.port").unwrap().as_int32(), Some(6379));
+
+    assert!(cfg.extract("plugins.redis.port").is_none());
+    assert!(cfg.extract("plugins.memcached").is_none());
+}
+
+#[test]
+fn test_scoped() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "server : {
+            port = 8080;
+            tls : { enabled = true; };
+        };"
+    ).is_ok());
+
+    let server = cfg.scoped("server");
+    assert_eq!(server.value("port").unwrap().as_int32(), Some(8080));
+    assert!(server.value("server.port").is_none());
+
+    let tls = server.scoped("tls");
+    assert_eq!(tls.prefix(), "server.tls");
+    assert_eq!(tls.value("enabled").unwrap().as_bool(), Some(true));
+
+    assert!(cfg.writer("server").unwrap().write_string("host", "localhost")
+        .is_ok());
+    assert_eq!(cfg.scoped("server").value("host").unwrap().as_string(),
+        Some("localhost".to_string()));
+
+    let missing = cfg.scoped("client");
+    assert!(missing.value("port").is_none());
+    assert!(missing.extract().is_none());
+
+    assert_eq!(cfg.scoped("").value("server.port").unwrap().as_int32(),
+        Some(8080));
+}
+
+#[test]
+fn test_query() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "servers : {
+            a : { port = 80; };
+            b : { port = 81; tls : { port = 443; }; };
+        };
+        pools = ( { port = 90; }, { name = \"x\"; } );
+        port = 1;"
+    ).is_ok());
+
+    let paths = |pattern : &str| -> Vec<String> {
+        cfg.query(pattern).into_iter().map(|(path, _)| path).collect()
+    };
+
+    assert_eq!(paths("servers.*.port"), vec!["servers.a.port",
+        "servers.b.port"]);
+    assert_eq!(paths("**.port"), vec!["servers.a.port", "servers.b.port",
+        "servers.b.tls.port", "pools.[0].port", "port"]);
+    assert_eq!(paths("servers.**.port"), vec!["servers.a.port",
+        "servers.b.port", "servers.b.tls.port"]);
+    assert_eq!(paths("pools.*.port"), vec!["pools.[0].port"]);
+    assert_eq!(paths("pools.[1].name"), vec!["pools.[1].name"]);
+    assert_eq!(paths("**.**.tls"), vec!["servers.b.tls"]);
+    assert!(paths("clients.*").is_empty());
+
+    for (path, option) in cfg.query("**.port") {
+        assert_eq!(cfg.value(path).unwrap().as_int32(), option.as_int32());
+    }
+}
+
+#[test]
+fn test_set_index_paths() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "servers = (
+            { host = \"a\"; port = 8080; },
+            { host = \"b\"; port = 8081; }
+        );
+        ports = [ 1, 2, 3 ];
+        ratio = 0.5;
+        name = \"x\";"
+    ).is_ok());
+
+    assert_eq!(cfg.value("servers.[1].host").unwrap().as_string(),
+        Some("b".to_string()));
+    assert!(cfg.value("servers.[2].host").is_none());
+
+    assert!(cfg.set("servers.[0].port", 80).is_ok());
+    assert_eq!(cfg.value("servers.[0].port").unwrap().as_int32(), Some(80));
+    assert!(cfg.set("servers.[1].tls", true).is_ok());
+    assert_eq!(cfg.value("servers.[1].tls").unwrap().as_bool(), Some(true));
+
+    assert!(cfg.set("ports.[2]", 30).is_ok());
+    assert!(cfg.set("ports.[3]", 40).is_ok());
+    assert_eq!(cfg.value("ports").unwrap().as_array()
+        .map(|option| option.as_int32().unwrap()).collect::<Vec<_>>(),
+        vec![1, 2, 30, 40]);
+    assert_eq!(cfg.set("ports.[5]", 60), Err(Errors::ElementNotExists));
+    assert_eq!(cfg.set("ports.[0]", "one"), Err(Errors::WriteError));
+
+    assert!(cfg.set("ratio", 1).is_ok());
+    assert_delta!(cfg.value("ratio").unwrap().as_float64().unwrap(), 1.0,
+        0.001);
+    assert!(cfg.set("name", 5).is_ok());
+    assert_eq!(cfg.value("name").unwrap().value_type(),
+        Some(OptionType::IntegerType));
+
+    assert_eq!(cfg.set("clients.[0].port", 1), Err(Errors::ElementNotExists));
+    assert_eq!(cfg.set("", 1), Err(Errors::ElementNotExists));
+}
+
+#[test]
+fn test_value_segments() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "hosts : {
+            web : { ports = [ 80, 443 ]; };
+            api = \"@hosts.web\";
+        };"
+    ).is_ok());
+
+    assert_eq!(cfg.value_segments(&["hosts", "web", "ports", "[1]"]).unwrap()
+        .as_int32(), Some(443));
+    let segments = vec!["hosts".to_string(), "web".to_string()];
+    assert!(cfg.value_segments(&segments).unwrap().is_section().unwrap());
+    assert!(cfg.value_segments(&["hosts", "example.com"]).is_none());
+    assert!(cfg.value_segments(&["hosts", "api", "ports"]).is_none());
+
+    cfg.resolve_references(true);
+    assert_eq!(cfg.value_segments(&["hosts", "api", "ports", "[0]"]).unwrap()
+        .as_int32(), Some(80));
+}
+
+#[test]
+fn test_value_ptr() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "server : {
+            hosts = ( { port = 80; }, { port = 81; } );
+            ids = [ 5, 6 ];
+        };"
+    ).is_ok());
+
+    assert_eq!(cfg.value_ptr("/server/hosts/1/port").unwrap().as_int32(),
+        Some(81));
+    assert_eq!(cfg.value_ptr("/server/ids/0").unwrap().as_int32(), Some(5));
+    assert!(cfg.value_ptr("").unwrap().is_section().unwrap());
+
+    assert!(cfg.value_ptr("/server/ids/01").is_none());
+    assert!(cfg.value_ptr("/server/ids/-").is_none());
+    assert!(cfg.value_ptr("/server/ids/2").is_none());
+    assert!(cfg.value_ptr("/server/0").is_none());
+    assert!(cfg.value_ptr("/server/[0]").is_none());
+    assert!(cfg.value_ptr("server/ids").is_none());
+    assert!(cfg.value_ptr("/serv~1er").is_none());
+}
+
+#[test]
+fn test_query_matches() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "databases : {
+            main : { pool_size = 8; };
+            cache : { pool_size = 2; replica : { pool_size = 1; }; };
+        };"
+    ).is_ok());
+
+    let captures = |pattern : &str| -> Vec<(String, Vec<String>)> {
+        cfg.query_matches(pattern).into_iter()
+            .map(|found| (found.path, found.captures)).collect()
+    };
+
+    assert_eq!(captures("databases.*.pool_size"), vec![
+        ("databases.main.pool_size".to_string(), vec!["main".to_string()]),
+        ("databases.cache.pool_size".to_string(), vec!["cache".to_string()])
+    ]);
+    assert_eq!(captures("databases.**.pool_size"), vec![
+        ("databases.main.pool_size".to_string(), vec!["main".to_string()]),
+        ("databases.cache.pool_size".to_string(), vec!["cache".to_string()]),
+        ("databases.cache.replica.pool_size".to_string(),
+            vec!["cache.replica".to_string()])
+    ]);
+    assert_eq!(captures("*.cache.**.pool_size"), vec![
+        ("databases.cache.pool_size".to_string(),
+            vec!["databases".to_string(), "".to_string()]),
+        ("databases.cache.replica.pool_size".to_string(),
+            vec!["databases".to_string(), "replica".to_string()])
+    ]);
+
+    let found = cfg.query_matches("databases.main.pool_size");
+    assert!(found[0].captures.is_empty());
+    assert_eq!(found[0].option.as_int32(), Some(8));
+}
+
+#[test]
+fn test_watch_key() {
+    let file_name = Path::new("watch_key_test.cfg");
+    fs::write(file_name, "log : { level = \"info\"; }; port = 80;").unwrap();
+
+    let mut watcher = Watcher::new(file_name).unwrap();
+    let changes = Rc::new(RefCell::new(Vec::new()));
+    let level_changes = changes.clone();
+    watcher.watch_key("log.level", move |old, new| {
+        level_changes.borrow_mut().push((old.cloned(), new.cloned()));
+    });
+
+    assert_eq!(watcher.poll(), Ok(false));
+
+    fs::write(file_name, "log : { level = \"info\"; }; port = 8080;")
+        .unwrap();
+    assert_eq!(watcher.poll(), Ok(true));
+    assert!(changes.borrow().is_empty());
+    assert_eq!(watcher.config().value("port").unwrap().as_int32(),
+        Some(8080));
+
+    fs::write(file_name, "log : { level = \"debug\"; }; port = 8080;")
+        .unwrap();
+    assert_eq!(watcher.poll(), Ok(true));
+
+    fs::write(file_name, "log : { level = ; };").unwrap();
+    assert_eq!(watcher.poll(), Err(Errors::ParseError));
+    assert_eq!(watcher.config().value("port").unwrap().as_int32(),
+        Some(8080));
+
+    fs::write(file_name, "port = 8080;").unwrap();
+    assert_eq!(watcher.poll(), Ok(true));
+    let _ = fs::remove_file(file_name);
+
+    assert_eq!(*changes.borrow(), vec![
+        (Some(Value::from("info")), Some(Value::from("debug"))),
+        (Some(Value::from("debug")), None)
+    ]);
+}
+
+#[test]
+fn test_track_reads() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "server : { port = 80; legacy = true; tls : { cert = \"a\"; }; };
+        ids = [ 1, 2 ];
+        names = [ \"a\" ];
+        pools = ( { size = 1; }, { size = 2; } );
+        tags = ( \"x\", 1 );"
+    ).is_ok());
+    assert!(cfg.unused_settings().is_empty());
+
+    cfg.track_reads(true);
+    let server = cfg.value("server").unwrap();
+    assert_eq!(server.value("port").unwrap().as_int32(), Some(80));
+    let _ = cfg.scoped("server.tls").value("cert").unwrap().as_string();
+    let _ = cfg.value("ids.[0]").unwrap().as_int32();
+    let _ = cfg.value("pools").unwrap().as_list()
+        .map(|pool| pool.value("size").unwrap().as_int32()).take(1).count();
+    let _ = cfg.value("tags").unwrap().as_list().count();
+
+    assert_eq!(cfg.unused_settings(), vec!["server.legacy", "names",
+        "pools.[1].size"]);
+
+    assert!(cfg.load_from_string("a = 1;").is_ok());
+    assert_eq!(cfg.unused_settings(), vec!["a"]);
+    let _ = cfg.query("*")[0].1.as_int32();
+    assert!(cfg.unused_settings().is_empty());
+}
+
+#[test]
+fn test_finalize() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("server : { port = 8080; };").is_ok());
+    assert!(!cfg.is_frozen());
+    cfg.finalize();
+    assert!(cfg.is_frozen());
+    assert_eq!(cfg.value("server.port").unwrap().as_int32(), Some(8080));
+
+    let mut other = Config::new();
+    assert!(!other.is_frozen());
+    assert!(other.root().write_int32("port", 1).is_ok());
+}
+
+#[test]
+#[should_panic(expected = "write to frozen config at \"server\"")]
+fn test_finalize_write() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("server : { port = 8080; };").is_ok());
+    cfg.finalize();
+    let _ = cfg.writer("server").unwrap().write_int32("workers", 4);
+}
+
+#[test]
+fn test_load_dir() {
+    let dir = Path::new("load_dir_test.d");
+    let _ = fs::remove_dir_all(dir);
+    fs::create_dir(dir).unwrap();
+    fs::write(dir.join("10-base.cfg"),
+        "server : { port = 80; host = \"a\"; }; ids = [ 1, 2 ];").unwrap();
+    fs::write(dir.join("20-override.cfg"),
+        "server : { port = 8080; tls : { enabled = true; }; }; ids = [ 3 ];")
+        .unwrap();
+    fs::write(dir.join("05-ignored.txt"), "server = 1;").unwrap();
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("old = 1;").is_ok());
+    assert!(cfg.load_dir(dir, "*.cfg").is_ok());
+    assert!(cfg.value("old").is_none());
+    assert_eq!(cfg.value("server.port").unwrap().as_int32(), Some(8080));
+    assert_eq!(cfg.value("server.host").unwrap().as_string(),
+        Some("a".to_string()));
+    assert_eq!(cfg.value("server.tls.enabled").unwrap().as_bool(), Some(true));
+    assert_eq!(cfg.value("ids").unwrap().as_array().count(), 1);
+
+    fs::write(dir.join("30-broken.cfg"), "server = ;").unwrap();
+    assert_eq!(cfg.load_dir(dir, "*.cfg"), Err(Errors::ParseError));
+    assert_eq!(cfg.value("server.port").unwrap().as_int32(), Some(8080));
+
+    assert!(cfg.load_dir(dir, "1?-*.cfg").is_ok());
+    assert_eq!(cfg.value("server.port").unwrap().as_int32(), Some(80));
+    let _ = fs::remove_dir_all(dir);
+
+    assert_eq!(cfg.load_dir(dir, "*.cfg"), Err(Errors::FileNotExists));
+}
+
+#[test]
+fn test_origin() {
+    let base = Path::new("origin_test_base.cfg");
+    let site = Path::new("origin_test_site.cfg");
+    fs::write(base, "server : {\n  port = 80;\n  host = \"a\";\n};\n").unwrap();
+    fs::write(site, "server : {\n  port = 8080;\n};\n\
+        pools = ( { size = 1; } );\n").unwrap();
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_layers(&[base, site]).is_ok());
+    assert_eq!(cfg.value("server.port").unwrap().as_int32(), Some(8080));
+
+    let port = cfg.origin("server.port").unwrap();
+    assert_eq!((port.file.as_path(), port.layer, port.line), (site, 1, 2));
+    let host = cfg.origin("server.host").unwrap();
+    assert_eq!((host.file.as_path(), host.layer, host.line), (base, 0, 3));
+    assert_eq!(cfg.origin("pools.[0].size").unwrap().layer, 1);
+
+    assert!(cfg.set("server.port", 9000).is_ok());
+    assert!(cfg.origin("server.port").is_none());
+    assert!(cfg.origin("server.missing").is_none());
+
+    assert!(cfg.load_from_file(base).is_ok());
+    let port = cfg.origin("server.port").unwrap();
+    assert_eq!((port.file.as_path(), port.layer, port.line), (base, 0, 2));
+
+    let _ = fs::remove_file(base);
+    let _ = fs::remove_file(site);
+}
+
+#[test]
+fn test_lookup_cache() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("server : { port = 80; };").is_ok());
+    assert_eq!(cfg.value("server.port").unwrap().as_int32(), Some(80));
+    assert_eq!(cfg.value("server.port").unwrap().as_int32(), Some(80));
+
+    assert!(cfg.writer("server.port").unwrap().delete().is_ok());
+    assert!(cfg.value("server.port").is_none());
+    assert!(cfg.value("server.host").is_none());
+    assert!(cfg.writer("server").unwrap().write_string("host", "a").is_ok());
+    assert_eq!(cfg.value("server.host").unwrap().as_string(),
+        Some("a".to_string()));
+
+    let schema = Schema::new()
+        .optional("server.workers", OptionType::IntegerType)
+            .default_value(4);
+    assert!(cfg.value("server.workers").is_none());
+    assert!(cfg.apply_defaults(&schema).is_ok());
+    assert_eq!(cfg.value("server.workers").unwrap().as_int32(), Some(4));
+
+    assert!(cfg.load_from_string("server : { port = 8080; };").is_ok());
+    assert_eq!(cfg.value("server.port").unwrap().as_int32(), Some(8080));
+    assert!(cfg.value("server.host").is_none());
+    assert!(cfg.value("").unwrap().is_section().unwrap());
+}
+
+#[test]
+fn test_read_many() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "server : { host = \"a\"; ports = [ 80, 81 ]; };
+         workers = ( { id = 1; }, { id = 2; } );").is_ok());
+    cfg.track_reads(true);
+
+    let values = cfg.read_many(&["workers.[1].id", "server.host",
+        "server.missing", "server.ports", "server.host", "nothing.here"]);
+    assert_eq!(values, vec![
+        Some(Value::Int(2)),
+        Some(Value::Str("a".to_string())),
+        None,
+        Some(Value::Array(vec![Value::Int(80), Value::Int(81)])),
+        Some(Value::Str("a".to_string())),
+        None
+    ]);
+    assert_eq!(cfg.unused_settings(), vec!["workers.[0].id"]);
+    assert!(cfg.read_many::<&str>(&[]).is_empty());
+}
+
+#[test]
+fn test_as_str() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("name = \"server\"; port = 80;").is_ok());
+    cfg.track_reads(true);
+
+    let name = cfg.value("name").unwrap();
+    assert_eq!(name.as_str(), Some("server"));
+    assert_eq!(name.as_string(), Some("server".to_string()));
+    assert_eq!(cfg.value("port").unwrap().as_str(), None);
+    assert_eq!(cfg.unused_settings(), Vec::<String>::new());
+}
+
+#[test]
+fn test_key() {
+    let port = Key::new("server.port");
+    assert_eq!(port, Key::new(String::from("server.port")));
+    assert_eq!(port.path(), "server.port");
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("server : { port = 80; };").is_ok());
+    assert_eq!(cfg.value_key(&port).unwrap().as_int32(), Some(80));
+    assert_eq!(cfg.value_key(&port.clone()).unwrap().as_int32(), Some(80));
+    assert!(cfg.value_key(&Key::new("server.host")).is_none());
+    assert!(cfg.value_key(&Key::new("server\0port")).is_none());
+
+    assert!(cfg.writer("server").unwrap().write_int32("a\0b", 1).is_err());
+    assert!(cfg.writer("server").unwrap().write_int32("workers", 4).is_ok());
+    assert_eq!(cfg.value_key(&Key::new("server.workers")).unwrap()
+        .as_int32(), Some(4));
+}
+
+#[test]
+fn test_snapshot() {
+    let mut cfg = Config::new();
+    assert!(Config::new().to_snapshot().is_empty());
+    assert!(cfg.load_from_string(
+        "server : { host = \"a\"; debug = true; ratio = 0.5; };
+         workers = ( { id = 1; }, { id = 2; } );").is_ok());
+
+    let snapshot = cfg.to_snapshot();
+    assert!(cfg.writer("server").unwrap().write_int32("port", 80).is_ok());
+    assert_eq!(snapshot.len(), 9);
+    assert_eq!(snapshot.get_str("server.host"), Some("a"));
+    assert_eq!(snapshot.get_bool("server.debug"), Some(true));
+    assert_eq!(snapshot.get_float("server.ratio"), Some(0.5));
+    assert_eq!(snapshot.get_int("workers.[1].id"), Some(2));
+    assert_eq!(snapshot.get_int("server.host"), None);
+    assert!(snapshot.get("server.port").is_none());
+    assert!(snapshot.get("workers").unwrap().is_list());
+    assert_eq!(snapshot.get("workers.[1]").unwrap().name(), "[1]");
+    assert_eq!(snapshot.get("workers.[1].id").unwrap().path(),
+        "workers.[1].id");
+    let names : Vec<&str> = snapshot.get("server").unwrap().children()
+        .map(|child| child.name()).collect();
+    assert_eq!(names, vec!["host", "debug", "ratio"]);
+    assert!(snapshot.get("").unwrap().to_value() !=
+        cfg.read_many(&[""]).remove(0).unwrap());
+    assert_eq!(cfg.to_snapshot().get("").unwrap().to_value(),
+        cfg.read_many(&[""]).remove(0).unwrap());
+    assert!(snapshot.root().is_section());
+
+    let shared = std::sync::Arc::new(snapshot);
+    let reader = shared.clone();
+    let host = std::thread::spawn(move || {
+        reader.get_str("server.host").map(|host| host.to_string())
+    }).join().unwrap();
+    assert_eq!(host, Some("a".to_string()));
+}
+
+#[test]
+fn test_entries() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "server : { host = \"a\"; port = 80; };
+         ports = [ 1, 2, 3 ];").is_ok());
+
+    let server = cfg.value("server").unwrap();
+    assert_eq!(server.name(), Some("server"));
+    let names : Vec<&str> = server.entries().map(|(name, _)| name).collect();
+    assert_eq!(names, vec!["host", "port"]);
+    assert_eq!(server.children().size_hint(), (2, Some(2)));
+
+    let ports = cfg.value("ports").unwrap();
+    assert_eq!(ports.entries().count(), 0);
+    let ports : Vec<i32> = ports.children()
+        .filter_map(|option| option.as_int32()).collect();
+    assert_eq!(ports, vec![1, 2, 3]);
+    assert_eq!(cfg.value("ports.[0]").unwrap().name(), None);
+}
+
+#[test]
+fn test_value_lookup() {
+    let mut cfg = Config::new();
+    assert!(cfg.value("").unwrap().is_section().unwrap());
+    assert!(cfg.value("missing").is_none());
+    assert!(cfg.create_section("group").is_some());
+    assert!(cfg.value("group").unwrap().is_section().unwrap());
+
+    assert!(cfg.load_from_string("a = 1; b = ").is_err());
+    assert!(cfg.value("a").is_none());
+    assert!(cfg.value("group").is_none());
+    assert!(cfg.load_from_string("a = 1;").is_ok());
+    assert_eq!(cfg.value("a").unwrap().as_int32(), Some(1));
+}
+
+#[test]
+fn test_set_deep_path() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("a : { b : { c : { d : { e : { f : { g : {
+        h : { i : { j = 1; }; }; }; }; }; }; }; }; };").is_ok());
+    assert!(cfg.set("a.b.c.d.e.f.g.h.i.j", 2).is_ok());
+    assert_eq!(cfg.value("a.b.c.d.e.f.g.h.i.j").unwrap().as_int32(), Some(2));
+    assert!(cfg.set("a.b.c.d.e.f.g.h.i.k", "x").is_ok());
+    assert_eq!(cfg.value("a.b.c.d.e.f.g.h.i.k").unwrap().as_str(), Some("x"));
+    assert_eq!(cfg.set("a.b\0c", 1), Err(Errors::InvalidString));
+}
+
+#[test]
+fn test_str_arguments() {
+    let mut cfg = Config::new();
+    let name = String::from("name");
+    let group = cfg.create_section(&name).unwrap();
+    assert!(group.write_string("host", String::from("a")).is_ok());
+    assert!(group.write_string(&name, "b").is_ok());
+    assert!(group.write_int32(std::borrow::Cow::from("port"), 80).is_ok());
+
+    let path = format!("{}.host", name);
+    assert_eq!(cfg.value(&path).unwrap().as_str(), Some("a"));
+    assert_eq!(cfg.value(path).unwrap().as_str(), Some("a"));
+    assert_eq!(cfg.value("name.name").unwrap().as_str(), Some("b"));
+    assert_eq!(cfg.scoped(&name).value("port").unwrap().as_int32(),
+        Some(80));
+}
+
+#[test]
+fn test_stats() {
+    let empty = Config::new().stats();
+    assert_eq!(empty.settings(), 0);
+    assert_eq!(empty.max_depth, 0);
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "name = \"app\"; debug = false; ratio = 0.5; big = 10000000000L;
+         server : { ports = [ 80, 81 ]; hosts = ( { host = \"web\"; } ); };")
+        .is_ok());
+    let stats = cfg.stats();
+    assert_eq!((stats.groups, stats.arrays, stats.lists), (2, 1, 1));
+    assert_eq!((stats.integers, stats.integers64), (2, 1));
+    assert_eq!((stats.floats, stats.booleans, stats.strings), (1, 1, 2));
+    assert_eq!(stats.settings(), 11);
+    assert_eq!(stats.max_depth, 4);
+    assert_eq!(stats.string_bytes, 6);
+    assert_eq!(stats.name_bytes, "namedebugratiobigserverportshostshost"
+        .len());
+    assert!(stats.estimated_bytes > stats.settings() *
+        std::mem::size_of::<libconfig_sys::config_setting_t>());
+}
+
+#[test]
+fn test_save_to_string() {
+    let mut cfg = Config::new();
+    assert_eq!(cfg.save_to_string().unwrap(), "");
+    assert!(cfg.load_from_string(
+        "name = \"a \\\"b\\\"\"; mask = 0xFF; big = 10000000000L;
+         ratio = 0.5; debug = true; ports = [ 80, 81 ]; empty = ( );
+         server : { hosts = ( { host = \"web\"; } ); };").is_ok());
+
+    let text = cfg.save_to_string().unwrap();
+    assert_eq!(text, "name = \"a \\\"b\\\"\";\nmask = 0xFF;\n\
+        big = 10000000000L;\nratio = 0.5;\ndebug = true;\n\
+        ports = [ 80, 81 ];\nempty = ( );\nserver : \n{\n  hosts = ( {\n\
+        \x20     host = \"web\";\n    } );\n};\n");
+
+    let mut copy = Config::new();
+    assert!(copy.load_from_string(text.as_str()).is_ok());
+    assert_eq!(copy.read_many(&[""]), cfg.read_many(&[""]));
+
+    let mut buffer = String::with_capacity(4096);
+    assert!(cfg.save_to_string_into(&mut buffer).is_ok());
+    assert_eq!(buffer, text);
+    assert!(cfg.save_to_string_into(&mut buffer).is_ok());
+    assert_eq!(buffer, text);
+    assert!(buffer.capacity() >= 4096);
+}
+
+#[test]
+fn test_save_to_writer() {
+    struct FailingWriter;
+
+    impl std::io::Write for FailingWriter {
+        fn write(&mut self, _ : &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("closed"))
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut text = String::new();
+    for index in 0..5000 {
+        text.push_str(&format!("group{} : {{ value = \"{}\"; }};\n", index,
+            index));
+    }
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(text).is_ok());
+
+    let mut out = Vec::new();
+    assert!(cfg.save_to_writer(&mut out).is_ok());
+    assert!(out.len() > 64 * 1024);
+    assert_eq!(String::from_utf8(out).unwrap(), cfg.save_to_string().unwrap());
+    assert_eq!(cfg.save_to_writer(FailingWriter), Err(Errors::SaveError));
+}
+
+#[test]
+fn test_save_non_finite() {
+    use std::fmt::Write;
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("ratio = 0.5; list = ( { x = 1.5; } );")
+        .is_ok());
+    assert!(cfg.set("list.[0].x", f64::INFINITY).is_ok());
+    assert_eq!(cfg.save_to_string(), Err(Errors::SaveError));
+    assert_eq!(cfg.save_to_writer(Vec::new()), Err(Errors::SaveError));
+
+    let file_name = std::env::temp_dir().join(format!("non-finite-{}.cfg",
+        std::process::id()));
+    assert_eq!(cfg.save_to_file(&file_name), Err(Errors::SaveError));
+    assert!(!file_name.exists());
+
+    assert!(cfg.set("list.[0].x", 2.5).is_ok());
+    assert!(cfg.save_to_string().is_ok());
+
+    let mut text = String::new();
+    assert!(write!(text, "{}", Value::Float(f64::NAN)).is_err());
+    assert!(write!(text, "{}", Value::List(vec![Value::Float(1.0),
+        Value::Float(f64::NEG_INFINITY)])).is_err());
+    assert_eq!(Value::Float(1.0).to_string(), "1.0");
+}
+
+#[test]
+fn test_duplicate_strings() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "cert = \"-----BEGIN CERTIFICATE-----\";
+         short = \"abc\"; other = \"abc\";
+         hosts = ( { cert = \"-----BEGIN CERTIFICATE-----\"; },
+                   { cert = \"-----BEGIN CERTIFICATE-----\"; } );
+         names = [ \"-----BEGIN CERTIFICATE-----\" ];").is_ok());
+
+    let duplicates = cfg.duplicate_strings(8);
+    assert_eq!(duplicates.len(), 1);
+    assert_eq!(duplicates[0].paths, vec!["cert", "hosts.[0].cert",
+        "hosts.[1].cert", "names.[0]"]);
+    assert_eq!(duplicates[0].saved_bytes(), 3 * (27 - 5));
+    assert_eq!(cfg.duplicate_strings(1).len(), 2);
+
+    assert_eq!(cfg.factor_duplicates(1).unwrap(), duplicates);
+    assert_eq!(cfg.value("hosts.[1].cert").unwrap().as_str(), Some("@cert"));
+    assert_eq!(cfg.value("names.[0]").unwrap().as_str(), Some("@cert"));
+    assert_eq!(cfg.value("other").unwrap().as_str(), Some("abc"));
+    assert!(cfg.duplicate_strings(1).len() == 1);
+
+    cfg.resolve_references(true);
+    assert_eq!(cfg.value("hosts.[1].cert").unwrap().as_str(),
+        Some("-----BEGIN CERTIFICATE-----"));
+}
+
+#[cfg(all(feature = "mmap", unix))]
+#[test]
+fn test_load_from_file_mmap() {
+    let file_name = Path::new("mmap_test.cfg");
+    fs::write(file_name, "server : { port = 80; };\r\n").unwrap();
+    let empty_name = Path::new("mmap_empty_test.cfg");
+    fs::write(empty_name, "").unwrap();
+    let broken_name = Path::new("mmap_broken_test.cfg");
+    fs::write(broken_name, "server : { port = ").unwrap();
+    let bom_name = Path::new("mmap_bom_test.cfg");
+    fs::write(bom_name, "\u{feff}port = 81;").unwrap();
+
+    let mut cfg = Config::new();
+    assert_eq!(cfg.load_from_file_mmap(broken_name), Err(Errors::ParseError));
+    assert_eq!(cfg.parse_error().unwrap().file.as_deref(), Some(broken_name));
+    assert!(cfg.load_from_file_mmap(file_name).is_ok());
+    assert!(cfg.parse_error().is_none());
+    assert_eq!(cfg.line_ending(), LineEnding::Crlf);
+    assert_eq!(cfg.value("server.port").unwrap().as_int32(), Some(80));
+    assert!(cfg.load_from_file_mmap(empty_name).is_ok());
+    assert!(cfg.value("server").is_none());
+    assert!(cfg.load_from_file_mmap(bom_name).is_ok());
+    assert_eq!(cfg.line_ending(), LineEnding::Lf);
+    assert_eq!(cfg.value("port").unwrap().as_int32(), Some(81));
+    assert_eq!(cfg.load_from_file_mmap(Path::new("mmap_missing.cfg")),
+        Err(Errors::FileNotExists));
+
+    let _ = fs::remove_file(file_name);
+    let _ = fs::remove_file(empty_name);
+    let _ = fs::remove_file(broken_name);
+    let _ = fs::remove_file(bom_name);
+}
+
+#[test]
+fn test_reload() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "server : { host = \"a\"; port = 80; }; ids = [ 1, 2 ];
+         mode = \"fast\"; old = true;").is_ok());
+    cfg.track_reads(true);
+    let _ = cfg.value("server.host").unwrap().as_str();
+    let _ = cfg.value("old").unwrap().as_bool();
+    let host = cfg.value("server.host").unwrap().element;
+
+    let changed = cfg.reload_from_string(
+        "server : { host = \"a\"; port = 8080; tls = true; };
+         ids = [ 1, 2, 3 ]; mode = 1;").unwrap();
+    assert_eq!(changed, vec!["old", "server.port", "server.tls", "ids",
+        "mode"]);
+    assert_eq!(cfg.unused_settings(), vec!["server.port", "server.tls",
+        "ids", "mode"]);
+    assert_eq!(cfg.value("server.host").unwrap().element, host);
+    assert_eq!(cfg.value("server.port").unwrap().as_int32(), Some(8080));
+    assert_eq!(cfg.value("ids").unwrap().as_array().count(), 3);
+    assert_eq!(cfg.value("mode").unwrap().as_int32(), Some(1));
+    assert!(cfg.value("old").is_none());
+
+    assert_eq!(cfg.reload_from_string("server : { host = ").unwrap_err(),
+        Errors::ParseError);
+    assert_eq!(cfg.value("server.port").unwrap().as_int32(), Some(8080));
+    assert_eq!(cfg.reload_from_string(
+        "server : { host = \"a\"; port = 8080; tls = true; };
+         ids = [ 1, 2, 3 ]; mode = 1;"), Ok(Vec::new()));
+}
+
+#[test]
+fn test_index_wide_groups() {
+    let mut text = String::from("hosts : {\n");
+    for index in 0..200 {
+        text.push_str(&format!("  host{} : {{ port = {}; }};\n", index,
+            index));
+    }
+    text.push_str("};\nlist = ( { a = 1; } );");
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(text.as_str()).is_ok());
+    cfg.index_wide_groups(Some(100));
+    assert_eq!(cfg.value("hosts.host150.port").unwrap().as_int32(),
+        Some(150));
+    assert_eq!(cfg.value("hosts/host7/port").unwrap().as_int32(), Some(7));
+    assert_eq!(cfg.value("list.[0].a").unwrap().as_int32(), Some(1));
+    assert!(cfg.value("hosts.host200").is_none());
+    assert!(cfg.value("").unwrap().is_section().unwrap());
+
+    assert!(cfg.writer("hosts.host10").unwrap().delete().is_ok());
+    assert!(cfg.value("hosts.host10.port").is_none());
+    assert!(cfg.writer("hosts").unwrap().create_section("host10").is_some());
+    assert!(cfg.value("hosts.host10").is_some());
+
+    cfg.index_wide_groups(None);
+    assert_eq!(cfg.value("hosts.host150.port").unwrap().as_int32(),
+        Some(150));
+}
+
+#[test]
+fn test_value_display() {
+    assert_eq!(Value::Int(8080).to_string(), "8080");
+    assert_eq!(Value::Int(1 << 40).to_string(), "1099511627776L");
+    assert_eq!(Value::from("a\"b").to_string(), "\"a\\\"b\"");
+    assert_eq!(Value::Array(vec![Value::Int(1), Value::Int(2)]).to_string(),
+        "[ 1, 2 ]");
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("server = { port = 80; };").is_ok());
+    let value = cfg.read_many(&["server"]).pop().flatten().unwrap();
+    assert_eq!(value.to_string(), "{\n  port = 80;\n}");
+}
+
+#[test]
+fn test_diff() {
+    let mut old = Config::new();
+    assert!(old.load_from_string(
+        "server : { port = 80; host = \"a\"; };
+        hosts = [ \"a\", \"b\" ];
+        mode = \"fast\";"
+    ).is_ok());
+    let mut new = Config::new();
+    assert!(new.load_from_string(
+        "server : { port = 8080; host = \"a\"; debug = true; };
+        hosts = [ \"a\" ];
+        mode = 1;"
+    ).is_ok());
+
+    assert_eq!(old.diff(&new), vec![
+        Change {
+            path : "server.port".to_string(),
+            kind : ChangeKind::Changed {
+                old : Value::Int(80),
+                new : Value::Int(8080)
+            }
+        },
+        Change {
+            path : "server.debug".to_string(),
+            kind : ChangeKind::Added(Value::Bool(true))
+        },
+        Change {
+            path : "hosts.[1]".to_string(),
+            kind : ChangeKind::Removed(Value::from("b"))
+        },
+        Change {
+            path : "mode".to_string(),
+            kind : ChangeKind::Changed {
+                old : Value::from("fast"),
+                new : Value::Int(1)
+            }
+        }
+    ]);
+    assert!(old.diff(&old).is_empty());
+}
+
+#[test]
+fn test_snapshot_string() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "zeta = -0.0000001;
+        alpha : { b = 0x10; a = 10000000000L; };
+        list = ( 2.5, \"x\", { d = true; c = [ 1, 2 ]; } );"
+    ).is_ok());
+
+    assert_eq!(cfg.to_snapshot_string_with_precision(3),
+"alpha :
+{
+  a = 10000000000L;
+  b = 16;
+};
+list = ( 2.500, \"x\", {
+  c = [ 1, 2 ];
+  d = true;
+} );
+zeta = 0.000;
+");
+
+    let mut reordered = Config::new();
+    assert!(reordered.load_from_string(
+        "list = ( 2.5000001, \"x\", { c = [ 1, 2 ]; d = true; } );
+        alpha : { a = 10000000000L; b = 16; };
+        zeta = 0.0;"
+    ).is_ok());
+    assert_eq!(cfg.to_snapshot_string(), reordered.to_snapshot_string());
+}
+
+#[test]
+fn test_config_source() {
+    fn check(source : &dyn ConfigSource) {
+        assert_eq!(source.get_int32("server.port"), Some(8080));
+        assert_eq!(source.get_int64("server.port"), Some(8080));
+        assert_eq!(source.get_int32("server.size"), None);
+        assert_eq!(source.get_int64("server.size"), Some(10000000000));
+        assert_eq!(source.get_float64("server.ratio"), Some(0.5));
+        assert_eq!(source.get_bool("server.debug"), Some(true));
+        assert_eq!(source.get_string("server.name"), Some("app".to_string()));
+        assert_eq!(source.get_int32("server.name"), None);
+        assert_eq!(source.get_float64("server.port"), None);
+        assert!(source.contains("server.debug"));
+        assert!(!source.contains("server.host"));
+    }
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "server : { port = 8080; size = 10000000000L; ratio = 0.5;
+            debug = true; name = \"app\"; };"
+    ).is_ok());
+    check(&cfg);
+
+    let mut mock = std::collections::HashMap::new();
+    mock.insert("server.port".to_string(), Value::from(8080));
+    mock.insert("server.size".to_string(), Value::Int(10000000000));
+    mock.insert("server.ratio".to_string(), Value::from(0.5));
+    mock.insert("server.debug".to_string(), Value::from(true));
+    mock.insert("server.name".to_string(), Value::from("app"));
+    check(&mock);
+}
+
+#[cfg(feature = "proptest")]
+proptest::proptest! {
+    #[test]
+    fn test_document_text_roundtrip(
+        (root, text) in crate::strategy::document_text()) {
+        let mut cfg = Config::new();
+        proptest::prop_assert!(cfg.load_from_string(text.as_str()).is_ok());
+        proptest::prop_assert_eq!(cfg.read_many(&[""]).pop().flatten(),
+            Some(root));
+        crate::testing::assert_roundtrip(&cfg);
+    }
+}
+
+#[test]
+fn test_assert_roundtrip() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "server : { port = 0x1F90; size = 10000000000L; ratio = 0.1;
+            name = \"a \\\"b\\\"\\n\"; };
+        hosts = [ \"a\", \"b\" ];
+        empty = ( );
+        list = ( 1, { x = 1.5; }, [ true ] );"
+    ).is_ok());
+    crate::testing::assert_roundtrip(&cfg);
+    crate::testing::assert_roundtrip_with_precision(&cfg, 17);
+}
+
+#[test]
+fn test_index_operators() {
+    let mut cfg = Config::new();
+    cfg.track_reads(true);
+    assert!(cfg.load_from_string(
+        "server : { port = 80; hosts = [ \"a\", \"b\" ]; };
+        servers = ( { port = 81; }, { port = 82; } );
+        unused = 1;"
+    ).is_ok());
+
+    assert_eq!(cfg["server"]["port"].as_int32(), Some(80));
+    assert_eq!(cfg["server.port"].as_int32(), Some(80));
+    assert_eq!(cfg["server"]["hosts"][1].as_string(), Some("b".to_string()));
+    assert_eq!(cfg["servers"][1]["port"].as_int32(), Some(82));
+    assert!(cfg["server"]["port"].exists());
+    assert!(!cfg["server"]["name"]["first"].exists());
+    assert_eq!(cfg["server"]["port"][0].as_int32(), None);
+    assert_eq!(cfg["servers"][5]["port"].as_int32(), None);
+
+    assert!(std::ptr::eq(&cfg["server"], &cfg["server"]));
+    let servers = cfg.value("servers").unwrap();
+    assert_eq!(servers.get(0).and_then(|server| {
+        server.get("port").and_then(|port| port.as_int32())
+    }), Some(81));
+    assert!(cfg.value("servers").unwrap().get(2).is_none());
+    assert!(cfg.value("server").unwrap().get("host").is_none());
+
+    cfg["servers"][0]["port"].as_int32();
+    assert_eq!(cfg.unused_settings(), vec!["unused"]);
+
+    assert!(cfg.writer("server.port").unwrap().delete().is_ok());
+    assert!(!cfg["server"]["port"].exists());
+    assert!(cfg.set("server.port", "x").is_ok());
+    assert_eq!(cfg["server"]["port"].as_str(), Some("x"));
+    cfg.track_reads(false);
+    assert_eq!(cfg["unused"].as_int32(), Some(1));
+    assert!(cfg.unused_settings().is_empty());
+}
+
+#[test]
+fn test_into_iterator() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "server : { host = \"a\"; port = 80; };
+        ports = [ 80, 443 ];"
+    ).is_ok());
+
+    let names : Vec<Option<String>> = (&cfg).into_iter()
+        .map(|(name, _)| name).collect();
+    assert_eq!(names, vec![Some("server".to_string()),
+        Some("ports".to_string())]);
+
+    let server = cfg.value("server").unwrap();
+    let mut members = Vec::new();
+    for (name, option) in &server {
+        members.push((name.unwrap(), option.value_type()));
+    }
+    assert_eq!(members, vec![
+        ("host".to_string(), Some(OptionType::StringType)),
+        ("port".to_string(), Some(OptionType::IntegerType))
+    ]);
+
+    let ports : Vec<(Option<String>, Option<i32>)> = cfg.value("ports")
+        .unwrap().into_iter().map(|(name, port)| (name, port.as_int32()))
+        .collect();
+    assert_eq!(ports, vec![(None, Some(80)), (None, Some(443))]);
+    assert_eq!(Config::new().into_iter().count(), 0);
+}
+
+#[test]
+fn test_typed_get() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "server : { port = 8080; big = 10000000000L; ratio = 0.5;
+            debug = true; name = \"app\"; };
+        ports = [ 80, 443 ];
+        mixed = ( 1, \"a\" );
+        negative = -1;"
+    ).is_ok());
+
+    assert_eq!(cfg.get::<u16>("server.port"), Ok(8080));
+    assert_eq!(cfg.get::<i32>("server.port"), Ok(8080));
+    assert_eq!(cfg.get::<i64>("server.big"), Ok(10000000000));
+    assert_eq!(cfg.get::<i32>("server.big"), Err(Errors::OutOfRange));
+    assert_eq!(cfg.get::<u32>("negative"), Err(Errors::OutOfRange));
+    assert_eq!(cfg.get::<f64>("server.ratio"), Ok(0.5));
+    assert_eq!(cfg.get::<f32>("server.ratio"), Ok(0.5));
+    assert_eq!(cfg.get::<bool>("server.debug"), Ok(true));
+    assert_eq!(cfg.get::<String>("server.name"), Ok("app".to_string()));
+    assert_eq!(cfg.get::<Vec<u16>>("ports"), Ok(vec![80, 443]));
+    assert_eq!(cfg.get::<Vec<i32>>("mixed"), Err(Errors::TypeMismatch));
+    assert_eq!(cfg.get::<Value>("ports"),
+        Ok(Value::Array(vec![Value::Int(80), Value::Int(443)])));
+
+    assert_eq!(cfg.get::<f64>("server.port"), Err(Errors::TypeMismatch));
+    assert_eq!(cfg.get::<String>("server"), Err(Errors::TypeMismatch));
+    assert_eq!(cfg.get::<i32>("server.host"), Err(Errors::ElementNotExists));
+    assert_eq!(cfg.get_or::<i32>("server.host", 1), Ok(1));
+    assert_eq!(cfg.get_or("server.port", 1u16), Ok(8080));
+    assert_eq!(cfg.get_or("server.name", 1u16), Err(Errors::TypeMismatch));
+}
+
+#[test]
+fn test_cfg_get_macro() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "server : { port = 70000; host = \"a\"; };").is_ok());
+
+    assert_eq!(crate::cfg_get!(cfg, "server.port" as u32), Ok(70000));
+    assert_eq!(crate::cfg_get!(cfg, "server.port" as u16, default 8080),
+        Err(Errors::OutOfRange));
+    assert_eq!(crate::cfg_get!(&cfg, "server.host" as String,
+        default "b".to_string()), Ok("a".to_string()));
+    assert_eq!(crate::cfg_get!(cfg, "server.workers" as u8, default 4),
+        Ok(4));
+    assert_eq!(crate::cfg_get!(cfg, "server.workers" as u8),
+        Err(Errors::ElementNotExists));
+}
+
+#[test]
+fn test_config_value_newtype() {
+    use crate::typed::ConfigValue;
+
+    #[derive(Debug, PartialEq)]
+    struct Port(u16);
+
+    impl ConfigValue for Port {
+        type Raw = u16;
+
+        fn from_raw(raw : u16) -> Result<Port, Errors> {
+            if raw == 0 { Err(Errors::OutOfRange) } else { Ok(Port(raw)) }
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Percent(f32);
+
+    impl ConfigValue for Percent {
+        type Raw = f32;
+
+        fn from_raw(raw : f32) -> Result<Percent, Errors> {
+            if (0.0..=100.0).contains(&raw) {
+                Ok(Percent(raw))
+            } else {
+                Err(Errors::OutOfRange)
+            }
+        }
+    }
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "port = 8080; ports = [ 80, 0 ]; load = 12.5; over = 120.0;
+        name = \"a\";"
+    ).is_ok());
+
+    assert_eq!(cfg.get::<Port>("port"), Ok(Port(8080)));
+    assert_eq!(cfg.get::<Vec<Port>>("ports"), Err(Errors::OutOfRange));
+    assert_eq!(cfg.get::<Percent>("load"), Ok(Percent(12.5)));
+    assert_eq!(cfg.get::<Percent>("over"), Err(Errors::OutOfRange));
+    assert_eq!(cfg.get::<Port>("name"), Err(Errors::TypeMismatch));
+    assert_eq!(cfg.get_or("missing", Port(80)), Ok(Port(80)));
+    assert_eq!(crate::cfg_get!(cfg, "port" as Port), Ok(Port(8080)));
+}
+
+#[test]
+fn test_i128() {
+    let mut cfg = Config::new();
+    let mut root = cfg.root();
+    assert!(root.write_i128("small", -5).is_ok());
+    assert!(root.write_i128("edge", i64::MIN as i128).is_ok());
+    assert!(root.write_i128("large", i128::MIN).is_ok());
+    let list = root.create_list("ids").unwrap();
+    assert!(list.write_i128(1).unwrap().write_i128(1 << 100).is_ok());
+
+    let text = cfg.save_to_string().unwrap();
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(text.as_str()).is_ok());
+    assert_eq!(cfg.value("small").unwrap().value_type(),
+        Some(OptionType::Int64Type));
+    assert_eq!(cfg.value("small").unwrap().as_i128(), Some(-5));
+    assert_eq!(cfg.value("edge").unwrap().as_i128(), Some(i64::MIN as i128));
+    assert_eq!(cfg.value("large").unwrap().as_i128(), Some(i128::MIN));
+    assert_eq!(cfg.get::<Vec<i128>>("ids"), Ok(vec![1, 1 << 100]));
+    assert_eq!(cfg.get::<u128>("large"), Err(Errors::OutOfRange));
+
+    assert!(cfg.load_from_string("a = \"12\"; b = \"i128:x\"; c = 1.5;")
+        .is_ok());
+    assert_eq!(cfg.value("a").unwrap().as_i128(), None);
+    assert_eq!(cfg.value("b").unwrap().as_i128(), None);
+    assert_eq!(cfg.get::<i128>("c"), Err(Errors::TypeMismatch));
+}
+
+#[cfg(feature = "base64")]
+#[test]
+fn test_base64_bytes() {
+    let mut cfg = Config::new();
+    let payload : Vec<u8> = (0..=255).collect();
+    assert!(cfg.root().write_base64_bytes("payload", &payload).is_ok());
+    assert!(cfg.root().write_base64_bytes("empty", &[]).is_ok());
+    let mut root = cfg.root();
+    let keys = root.create_array("keys").unwrap();
+    assert!(keys.write_base64_bytes(b"a").unwrap().write_base64_bytes(b"bc")
+        .is_ok());
+
+    let text = cfg.save_to_string().unwrap();
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(text.as_str()).is_ok());
+    assert_eq!(cfg.value("payload").unwrap().as_base64_bytes(),
+        Some(payload));
+    assert_eq!(cfg.value("empty").unwrap().as_base64_bytes(), Some(vec![]));
+    assert_eq!(cfg["keys"][1].as_base64_bytes(), Some(b"bc".to_vec()));
+
+    assert!(cfg.load_from_string("a = \"not base64!\"; b = 12;").is_ok());
+    assert_eq!(cfg.value("a").unwrap().as_base64_bytes(), None);
+    assert_eq!(cfg.value("b").unwrap().as_base64_bytes(), None);
+}
+
+#[test]
+fn test_color() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("
+        fg = \"#FF8000\"; bg = \"#10203040\"; short = \"#fff\";
+        plain = \"ff8000\"; bad = \"#ff80zz\"; number = 16744448;
+    ").is_ok());
+    assert_eq!(cfg["fg"].as_color(), Some((0xff, 0x80, 0x00, 0xff)));
+    assert_eq!(cfg["bg"].as_color(), Some((0x10, 0x20, 0x30, 0x40)));
+    assert_eq!(cfg["short"].as_color(), None);
+    assert_eq!(cfg["plain"].as_color(), None);
+    assert_eq!(cfg["bad"].as_color(), None);
+    assert_eq!(cfg["number"].as_color(), None);
+    assert_eq!(cfg["missing"].as_color(), None);
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_as_regex() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("
+        filters = ( \"^a+$\", \"(unclosed\" ); port = 80;
+    ").is_ok());
+    assert!(cfg["filters"][0].as_regex().unwrap().is_match("aaa"));
+
+    let error = cfg["filters"][1].as_regex().unwrap_err();
+    assert_eq!(error.path, "filters.[1]");
+    assert!(error.to_string().starts_with(
+        "filters.[1]: invalid regular expression"));
+    assert_eq!(cfg["port"].as_regex().unwrap_err().message,
+        "expected string setting");
+    assert_eq!(cfg["filters"].as_regex().unwrap_err().message,
+        "expected string setting");
+    assert_eq!(cfg["missing"].as_regex().unwrap_err().message,
+        "setting does not exist");
+}
+
+#[cfg(feature = "semver")]
+#[test]
+fn test_as_version() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("
+        plugin : { version = \"2.0.0-beta.1\"; requires = \"^1.3\";
+            broken = \"1.x.y\"; };
+    ").is_ok());
+    let version = cfg["plugin"]["version"].as_version().unwrap();
+    assert_eq!((version.major, version.pre.as_str()), (2, "beta.1"));
+    let requires = cfg["plugin"]["requires"].as_version_req().unwrap();
+    assert!(requires.matches(&semver::Version::new(1, 9, 0)));
+    assert!(!requires.matches(&semver::Version::new(2, 0, 0)));
+
+    let error = cfg["plugin"]["broken"].as_version().unwrap_err();
+    assert_eq!(error.path, "plugin.broken");
+    assert!(error.message.starts_with("invalid version:"));
+    assert!(cfg["plugin"]["broken"].as_version_req().is_err());
+    assert_eq!(cfg["plugin"]["missing"].as_version().unwrap_err().message,
+        "setting does not exist");
+}
+
+#[test]
+fn test_load_encodings() {
+    let text = "server : { host = \"h\u{e9}te\"; port = 80; };\n";
+    let utf16 : Vec<u16> = text.encode_utf16().collect();
+    let mut utf8_bom = vec![0xef, 0xbb, 0xbf];
+    utf8_bom.extend_from_slice(text.as_bytes());
+    let mut utf16le = vec![0xff, 0xfe];
+    utf16le.extend(utf16.iter().flat_map(|unit| unit.to_le_bytes()));
+    let mut utf16be = vec![0xfe, 0xff];
+    utf16be.extend(utf16.iter().flat_map(|unit| unit.to_be_bytes()));
+    let utf16le_plain = utf16le[2..].to_vec();
+
+    let file_name = std::env::temp_dir().join(format!("encodings-{}.cfg",
+        std::process::id()));
+    for bytes in &[utf8_bom, utf16le, utf16be, utf16le_plain] {
+        fs::write(&file_name, bytes).unwrap();
+        let mut cfg = Config::new();
+        assert!(cfg.load_from_file(&file_name).is_ok());
+        assert_eq!(cfg["server"]["host"].as_str(), Some("h\u{e9}te"));
+        assert_eq!(cfg["server"]["port"].as_int32(), Some(80));
+    }
+
+    fs::write(&file_name, [0xff, 0xfe, 0x61]).unwrap();
+    assert_eq!(Config::new().load_from_file(&file_name),
+        Err(Errors::ParseError));
+    fs::remove_file(&file_name).unwrap();
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("\u{feff}port = 80;").is_ok());
+    assert_eq!(cfg["port"].as_int32(), Some(80));
+}
+
+#[test]
+fn test_line_ending() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("a = 1;\r\nb : { c = \"x\\ny\"; };\r\n")
+        .is_ok());
+    assert_eq!(cfg.line_ending(), LineEnding::Crlf);
+    assert_eq!(cfg.save_to_string().unwrap(),
+        "a = 1;\r\nb : \r\n{\r\n  c = \"x\\ny\";\r\n};\r\n");
+    let mut out = Vec::new();
+    assert!(cfg.save_to_writer(&mut out).is_ok());
+    assert_eq!(out, cfg.save_to_string().unwrap().into_bytes());
+
+    let file_name = std::env::temp_dir().join(format!("line-ending-{}.cfg",
+        std::process::id()));
+    assert!(cfg.save_to_file(&file_name).is_ok());
+    let saved = fs::read_to_string(&file_name).unwrap();
+    assert!(saved.contains("\r\n"));
+    assert_eq!(saved.matches('\n').count(), saved.matches("\r\n").count());
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_file(&file_name).is_ok());
+    assert_eq!(cfg.line_ending(), LineEnding::Crlf);
+    cfg.set_line_ending(LineEnding::Lf);
+    assert!(cfg.save_to_file(&file_name).is_ok());
+    assert!(!fs::read_to_string(&file_name).unwrap().contains('\r'));
+    fs::remove_file(&file_name).unwrap();
+
+    assert!(cfg.load_from_string("a = 1;\n").is_ok());
+    assert_eq!(cfg.line_ending(), LineEnding::Lf);
+}
+
+#[test]
+fn test_move_child_to() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("a = 1; b = 2; c = 3; list = (1, 2, 3);")
+        .is_ok());
+    assert!(cfg.root().write_int32("d", 4).is_ok());
+    let names = |cfg : &Config| cfg.into_iter()
+        .map(|(name, _)| name.unwrap()).collect::<Vec<_>>();
+    assert_eq!(names(&cfg), ["a", "b", "c", "list", "d"]);
+
+    assert!(cfg.root().move_child_to("d", 1).is_ok());
+    assert_eq!(names(&cfg), ["a", "d", "b", "c", "list"]);
+    assert!(cfg.root().move_child_to(0, 4).is_ok());
+    assert_eq!(names(&cfg), ["d", "b", "c", "list", "a"]);
+    assert_eq!(cfg.save_to_string().unwrap(),
+        "d = 4;\nb = 2;\nc = 3;\nlist = ( 1, 2, 3 );\na = 1;\n");
+
+    let list = cfg.writer("list").unwrap();
+    assert!(list.move_child_to(2, 0).is_ok());
+    assert_eq!(cfg["list"][0].as_int32(), Some(3));
+    assert_eq!(cfg.value("list.[2]").unwrap().as_int32(), Some(2));
+
+    assert_eq!(cfg.root().move_child_to("missing", 0),
+        Err(Errors::ElementNotExists));
+    assert_eq!(cfg.root().move_child_to("a", 5), Err(Errors::OutOfRange));
+}
+
+#[test]
+fn test_build_validate() {
+    let file_name = std::env::temp_dir().join(format!("build-{}.cfg",
+        std::process::id()));
+    fs::write(&file_name, "server : {\n  port = 80;\n\n  host = ;\n};\n")
+        .unwrap();
+    let error = build::validate(&file_name).err().unwrap();
+    assert_eq!((error.file.as_path(), error.line), (file_name.as_path(), 4));
+    assert!(error.to_string().starts_with(&format!("{}:4: ",
+        file_name.display())));
+
+    fs::write(&file_name, "port = 80; name = \"a \\\"b\\\"\";\n").unwrap();
+    let cfg = build::validate(&file_name).ok().unwrap();
+    assert_eq!(cfg["port"].as_int32(), Some(80));
+    assert_eq!(build::constant_source("DEFAULTS", "name = \"x\";\n"),
+        "pub const DEFAULTS : &str = \"name = \\\"x\\\";\\n\";\n");
+    fs::remove_file(&file_name).unwrap();
+
+    assert_eq!(build::validate(&file_name).err().unwrap().message,
+        "file not found");
+}
+
+#[test]
+fn test_keys() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "plugins : { zeta : { on = true; }; alpha = 1; }; list = ( 1, 2 );"
+    ).is_ok());
+    let plugins = cfg.value("plugins").unwrap();
+    assert_eq!(plugins.keys().collect::<Vec<_>>(), ["zeta", "alpha"]);
+    assert_eq!(plugins.children().count(), 2);
+    assert_eq!(cfg.value("list").unwrap().keys().count(), 0);
+    assert_eq!(cfg.value("plugins.alpha").unwrap().keys().count(), 0);
+}
+
+#[test]
+fn test_len_and_at() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(
+        "ports = [ 80, 443 ]; hosts = ( \"a\", { name = \"b\"; } );
+        server : { }; port = 80;"
+    ).is_ok());
+    let hosts = cfg.value("hosts").unwrap();
+    assert_eq!(hosts.len(), Some(2));
+    assert_eq!(hosts.at(0).unwrap().as_str(), Some("a"));
+    assert_eq!(hosts.at(1).unwrap().value("name").unwrap().as_str(),
+        Some("b"));
+    assert!(hosts.at(2).is_none());
+    assert_eq!(cfg.value("ports").unwrap().at(1).unwrap().as_int32(),
+        Some(443));
+    assert_eq!(cfg.value("server").unwrap().is_empty(), Some(true));
+    assert_eq!(cfg.value("port").unwrap().len(), None);
+    assert!(cfg.value("port").unwrap().at(0).is_none());
+    assert_eq!(cfg["missing"].len(), None);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_deserialize() {
+    use serde::Deserialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    enum Mode { Fast, Limited(u32), Custom { level : i8 } }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Server {
+        host : String,
+        port : u16,
+        ratio : f32,
+        tls : Option<bool>,
+        tags : Vec<String>,
+        pair : (i64, String),
+        limits : BTreeMap<String, u64>,
+        modes : Vec<Mode>
+    }
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("
+        server : {
+            host = \"localhost\"; port = 8080; ratio = 0.5;
+            tags = [ \"a\", \"b\" ]; pair = ( 5000000000L, \"x\" );
+            limits : { files = 10; sockets = 20; };
+            modes = ( \"Fast\", { Limited = 3; },
+                { Custom : { level = -2; }; } );
+        };
+        bad : { host = \"h\"; port = 70000; };
+    ").is_ok());
+
+    let server : Server = cfg.value("server").unwrap().deserialize().unwrap();
+    assert_eq!(server, Server {
+        host : "localhost".to_string(),
+        port : 8080,
+        ratio : 0.5,
+        tls : None,
+        tags : vec!["a".to_string(), "b".to_string()],
+        pair : (5000000000, "x".to_string()),
+        limits : vec![("files".to_string(), 10), ("sockets".to_string(), 20)]
+            .into_iter().collect(),
+        modes : vec![Mode::Fast, Mode::Limited(3), Mode::Custom { level : -2 }]
+    });
+
+    let error = cfg.value("bad").unwrap().deserialize::<Server>().unwrap_err();
+    assert_eq!(error.path, "bad.port");
+    let error = cfg.value("server.limits").unwrap().deserialize::<Server>()
+        .unwrap_err();
+    assert_eq!(error.path, "server.limits");
+    assert!(error.message.contains("missing field"));
+
+    let root : BTreeMap<String, BTreeMap<String, String>> = Config::new()
+        .deserialize().unwrap();
+    assert!(root.is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serialize_into() {
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    enum Mode { Fast, Limited(u32), Custom { level : i8 } }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Server {
+        host : String,
+        ratio : f64,
+        tls : Option<bool>,
+        pair : (i64, String),
+        limits : BTreeMap<String, u64>,
+        modes : Vec<Mode>
+    }
+
+    let server = Server {
+        host : "localhost".to_string(),
+        ratio : 0.25,
+        tls : None,
+        pair : (1, "x".to_string()),
+        limits : vec![("files".to_string(), 10)].into_iter().collect(),
+        modes : vec![Mode::Fast, Mode::Limited(3), Mode::Custom { level : 2 }]
+    };
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("server = 1; other = 2;").is_ok());
+    assert!(cfg.serialize_into("server", &server).is_ok());
+    assert!(cfg.value("server.tls").is_none());
+    assert!(cfg.value("server.pair").unwrap().is_list().unwrap());
+    assert_eq!(cfg.value("other").unwrap().as_int32(), Some(2));
+    let read : Server = cfg.value("server").unwrap().deserialize().unwrap();
+    assert_eq!(read, server);
+
+    let mut cfg = Config::new();
+    assert!(cfg.serialize_into("", &server).is_ok());
+    assert_eq!(cfg.deserialize::<Server>().unwrap(), server);
+
+    let error = cfg.serialize_into("big", &vec![u64::MAX]).unwrap_err();
+    assert_eq!(error.path, "big.[0]");
+    assert!(cfg.serialize_into("", &5).is_err());
+    assert!(cfg.serialize_into("host.child", &5).is_err());
+}
+
+#[test]
+fn test_parse_error() {
+    let mut cfg = Config::new();
+    assert_eq!(cfg.load_from_string("a = 1;\nb = 2;\nc = ;\n"),
+        Err(Errors::ParseError));
+    let error = cfg.parse_error().unwrap().clone();
+    assert_eq!((error.file.as_ref(), error.line, error.kind),
+        (None, 3, ParseErrorKind::Parse));
+    assert!(!error.message.is_empty());
+    assert_eq!(error.to_string(), format!("line 3: {}", error.message));
+
+    let file_name = std::env::temp_dir().join(format!("parse-error-{}.cfg",
+        std::process::id()));
+    fs::write(&file_name, "a = 1;\nb = [ 1, \"x\" ];\n").unwrap();
+    assert!(cfg.load_from_file(&file_name).is_err());
+    let error = cfg.parse_error().unwrap();
+    assert_eq!((error.file.as_ref(), error.line), (Some(&file_name), 2));
+    assert!(error.to_string().starts_with(&format!("{}:2: ",
+        file_name.display())));
+    fs::remove_file(&file_name).unwrap();
+
+    assert!(cfg.load_from_string("a = 1;").is_ok());
+    assert!(cfg.parse_error().is_none());
+}
+
+#[test]
+fn test_errors_display() {
+    assert_eq!(Errors::ElementNotExists.to_string(), "setting does not exist");
+    let boxed : Box<dyn std::error::Error> = Box::new(Errors::ConfigFrozen);
+    assert_eq!(boxed.to_string(), "config is frozen");
+
+    let read = || -> Result<i32, Box<dyn std::error::Error>> {
+        Ok(Config::new().get::<i32>("missing")?)
+    };
+    assert_eq!(read().unwrap_err().to_string(), "setting does not exist");
+}
+
+#[test]
+fn test_set_members() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("
+        a = 1; port = 80; host = \"a\"; ratio = 1; on = false; z = 0;
+        list = ( 1 );
+    ").is_ok());
+    let mut root = cfg.root();
+    assert!(root.set_int32("port", 8080).unwrap().set_string("host", "b")
+        .unwrap().set_float64("ratio", 0.5).unwrap().set_bool("on", true)
+        .is_some());
+    assert!(root.set_int64("big", 1 << 40).is_some());
+    assert_eq!(cfg.save_to_string().unwrap(),
+        "a = 1;\nport = 8080;\nhost = \"b\";\nratio = 0.5;\non = true;\n\
+        z = 0;\nlist = ( 1 );\nbig = 1099511627776L;\n");
+    assert_eq!(cfg.value("ratio").unwrap().value_type(),
+        Some(OptionType::FloatType));
+
+    assert!(cfg.root().set_int64("port", 1 << 40).is_some());
+    assert_eq!(cfg.value("port").unwrap().as_int64(), Some(1 << 40));
+    assert_eq!(cfg.value("port").unwrap().value_type(),
+        Some(OptionType::Int64Type));
+    assert_eq!(cfg.into_iter().nth(1).unwrap().0.as_deref(), Some("port"));
+
+    let mut list = cfg.writer("list").unwrap();
+    assert!(list.set_int32("x", 1).is_none());
+}
+
+#[test]
+fn test_set_creates_parents() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("server : { host = \"a\"; }; list = ( 1 );")
+        .is_ok());
+    assert!(cfg.set("server.network.port", 8080).is_ok());
+    assert!(cfg.set("a.b.c", true).is_ok());
+    assert_eq!(cfg.value("server.network.port").unwrap().as_int32(),
+        Some(8080));
+    assert_eq!(cfg.value("a.b.c").unwrap().as_bool(), Some(true));
+    assert_eq!(cfg.value("server.host").unwrap().as_str(), Some("a"));
+
+    assert_eq!(cfg.set("missing.[0].port", 1), Err(Errors::ElementNotExists));
+    assert!(cfg.value("missing").is_none());
+    assert_eq!(cfg.set("list.name.port", 1), Err(Errors::ElementNotExists));
+    assert_eq!(cfg.set("server.host.port", 1),
+        Err(Errors::ElementNotExists));
+}
+
+#[test]
+fn test_lookup_shortcuts() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("
+        server : { port = 80; host = \"a\"; tls = true; ratio = 0.5;
+            size = 1099511627776L; };
+    ").is_ok());
+    assert!(cfg.exists("server.port"));
+    assert!(!cfg.exists("server.user"));
+    assert_eq!(cfg.int32("server.port"), Some(80));
+    assert_eq!(cfg.int64("server.size"), Some(1 << 40));
+    assert_eq!(cfg.float64("server.ratio"), Some(0.5));
+    assert_eq!(cfg.bool("server.tls"), Some(true));
+    assert_eq!(cfg.string("server.host").as_deref(), Some("a"));
+    assert_eq!(cfg.string("server.port"), None);
+
+    assert_eq!(cfg.int32_or("server.port", 1), 80);
+    assert_eq!(cfg.int32_or("server.host", 1), 1);
+    assert_eq!(cfg.int64_or("server.missing", 2), 2);
+    assert_eq!(cfg.float64_or("server.missing", 1.5), 1.5);
+    assert!(cfg.bool_or("server.missing", true));
+    assert_eq!(cfg.string_or("server.user", "root"), "root");
+}
+
+#[test]
+fn test_append_elements() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("
+        ports = [ 80 ]; ratios = [ ]; servers = ( ); server : { port = 80; };
+    ").is_ok());
+
+    let mut ports = cfg.writer("ports").unwrap();
+    assert!(ports.append_int32(443).is_some());
+    assert!(ports.append_string("http").is_none());
+    assert!(ports.append_group().is_none());
+    assert!(cfg.writer("ratios").unwrap().append_float64(0.5).is_some());
+    assert!(cfg.writer("ratios").unwrap().append_bool(true).is_none());
+
+    let mut servers = cfg.writer("servers").unwrap();
+    assert!(servers.append_string("a").and_then(|servers| {
+        servers.append_bool(true)
+    }).is_some());
+    let server = servers.append_group().unwrap();
+    assert!(server.write_int32("port", 81).is_ok());
+
+    assert!(cfg.writer("server").unwrap().append_int32(1).is_none());
+    assert!(cfg.writer("server.port").unwrap().append_int32(1).is_none());
+    assert!(cfg.writer("missing").is_none());
+
+    assert_eq!(cfg.value("ports").unwrap().as_array()
+        .map(|port| port.as_int32()).collect::<Vec<_>>(),
+        vec![Some(80), Some(443)]);
+    assert_eq!(cfg.value("ratios.[0]").unwrap().as_float64(), Some(0.5));
+    assert_eq!(cfg.value("servers").unwrap().len(), Some(3));
+    assert_eq!(cfg.value("servers.[0]").unwrap().as_string(),
+        Some("a".to_string()));
+    assert_eq!(cfg.value("servers.[2].port").unwrap().as_int32(), Some(81));
+}
+
+#[test]
+fn test_write_arrays() {
+    let mut cfg = Config::new();
+    let root = cfg.root();
+    assert!(root.write_int32_array("ports", &[80, 443]).is_ok());
+    assert!(root.write_int64_array("sizes", &[1 << 40]).is_ok());
+    assert!(root.write_float64_array("ratios", &[0.5, 1.5]).is_ok());
+    assert!(root.write_bool_array("flags", &[true, false]).is_ok());
+    assert!(root.write_string_array("hosts", &["a", "b"]).is_ok());
+    assert!(root.write_int32_array("empty", &[]).is_ok());
+
+    assert_eq!(root.write_int32_array("ports", &[1]).err(),
+        Some(Errors::DuplicateName));
+    assert_eq!(root.write_string_array("bad", &["a", "b\0c"]).err(),
+        Some(Errors::InvalidString));
+    assert!(cfg.value("bad").is_none());
+    assert_eq!(cfg.writer("ports").unwrap().write_int32_array("x", &[1])
+        .err(), Some(Errors::TypeMismatch));
+
+    assert_eq!(cfg.value("ports").unwrap().as_array()
+        .map(|port| port.as_int32()).collect::<Vec<_>>(),
+        vec![Some(80), Some(443)]);
+    assert_eq!(cfg.value("sizes.[0]").unwrap().as_int64(), Some(1 << 40));
+    assert_eq!(cfg.value("ratios.[1]").unwrap().as_float64(), Some(1.5));
+    assert_eq!(cfg.value("flags.[1]").unwrap().as_bool(), Some(false));
+    assert_eq!(cfg.value("hosts.[1]").unwrap().as_string(),
+        Some("b".to_string()));
+    assert_eq!(cfg.value("empty").unwrap().len(), Some(0));
+    assert_eq!(cfg.value("").unwrap().keys().collect::<Vec<_>>(),
+        ["ports", "sizes", "ratios", "flags", "hosts", "empty"]);
+}
+
+#[test]
+fn test_as_vec() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("
+        ports = [ 80, 443 ]; sizes = [ 1099511627776L ]; ratios = [ 0.5 ];
+        flags = [ true, false ]; hosts = ( \"a\", \"b\" ); empty = [ ];
+        mixed = ( 1, \"a\" ); nested = ( [ 1 ] ); port = 80;
+    ").is_ok());
+
+    let reader = |path : &str| cfg.value(path).unwrap();
+    assert_eq!(reader("ports").as_vec_int32(), Ok(vec![80, 443]));
+    assert_eq!(reader("sizes").as_vec_int64(), Ok(vec![1 << 40]));
+    assert_eq!(reader("ratios").as_vec_float64(), Ok(vec![0.5]));
+    assert_eq!(reader("flags").as_vec_bool(), Ok(vec![true, false]));
+    assert_eq!(reader("hosts").as_vec_string(),
+        Ok(vec!["a".to_string(), "b".to_string()]));
+    assert_eq!(reader("empty").as_vec_string(), Ok(vec![]));
+    assert_eq!(reader("nested").as_vec::<Vec<i32>>(), Ok(vec![vec![1]]));
+
+    assert_eq!(reader("ports").as_vec::<u8>(), Err(Errors::OutOfRange));
+    assert_eq!(reader("ports").as_vec_string(), Err(Errors::TypeMismatch));
+    assert_eq!(reader("mixed").as_vec_int32(), Err(Errors::TypeMismatch));
+    assert_eq!(reader("port").as_vec_int32(), Err(Errors::TypeMismatch));
+    assert_eq!(cfg["missing"].as_vec_int32(), Err(Errors::ElementNotExists));
+}
+
+#[test]
+fn test_value_round_trip() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("
+        server : { host = \"a\"; ports = [ 80 ]; tls = true; };
+        users = ( \"root\" ); port = 80; last = 1;
+    ").is_ok());
+
+    let mut server = cfg.value("server").unwrap().to_value().unwrap();
+    if let Value::Group(members) = &mut server {
+        members.insert("ports".to_string(),
+            Value::Array(vec![Value::Int(80), Value::Int(443)]));
+        members.shift_remove("tls");
+        members.insert("workers".to_string(), Value::Int(4));
+    }
+    assert!(cfg.writer("server").unwrap().from_value(&server).is_ok());
+    assert_eq!(cfg.value("server").unwrap().to_value(), Some(server));
+    assert_eq!(cfg.value("").unwrap().keys().collect::<Vec<_>>(),
+        ["server", "users", "port", "last"]);
+
+    let users = Value::List(vec![Value::from("a"), Value::Int(1)]);
+    assert!(cfg.writer("users").unwrap().from_value(&users).is_ok());
+    assert_eq!(cfg.value("users").unwrap().to_value(), Some(users));
+    assert!(cfg.writer("port").unwrap().from_value(&Value::Int(8080)).is_ok());
+    assert_eq!(cfg.int32("port"), Some(8080));
+
+    assert_eq!(cfg.writer("port").unwrap().from_value(&Value::from("a")),
+        Err(Errors::TypeMismatch));
+    assert_eq!(cfg.writer("users").unwrap()
+        .from_value(&Value::Array(vec![])), Err(Errors::TypeMismatch));
+    assert_eq!(cfg.value("port").unwrap().to_value(), Some(Value::Int(8080)));
+    assert_eq!(cfg["missing"].to_value(), None);
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn test_toml_conversion() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("
+        name = \"app\"; size = 1099511627776L; ratio = 0.5;
+        server : { port = 80; hosts = [ \"a\", \"b\" ]; };
+        users = ( { name = \"root\"; }, 1 );
+    ").is_ok());
+
+    let (document, skipped) = cfg.to_toml();
+    assert!(skipped.is_empty());
+    let (copy, skipped) = Config::from_toml(&document).unwrap();
+    assert!(skipped.is_empty());
+    assert_eq!(copy.value("").unwrap().to_value(),
+        cfg.value("").unwrap().to_value());
+
+    let (cfg, skipped) = Config::from_toml("
+        title = \"x\"
+        \"bad key\" = 1
+        ports = [ 80, 443 ]
+        mixed = [ 1, \"a\" ]
+        [owner]
+        born = 1979-05-27
+        [[servers]]
+        port = 81
+    ").unwrap();
+    assert_eq!(skipped.iter().map(|s| s.path.as_str()).collect::<Vec<_>>(),
+        ["bad key", "owner.born"]);
+    assert_eq!(cfg.value("ports").unwrap().is_array(), Some(true));
+    assert_eq!(cfg.value("mixed").unwrap().is_list(), Some(true));
+    assert_eq!(cfg.value("owner").unwrap().len(), Some(0));
+    assert_eq!(cfg.value("servers.[0].port").unwrap().as_int32(), Some(81));
+
+    let error = Config::from_toml("a = 1\nb = \n").err().unwrap();
+    assert_eq!(error.line, 2);
+}
+
+#[test]
+fn test_shared_config() {
+    use crate::shared::SharedConfig;
+    use std::thread;
+
+    fn assert_send<T : Send>() {}
+    assert_send::<Config>();
+    assert_send::<SharedConfig>();
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("counter = 0;").is_ok());
+    let shared = SharedConfig::new(cfg);
+
+    let workers : Vec<_> = (0..4).map(|_| {
+        let shared = shared.clone();
+        thread::spawn(move || {
+            for _ in 0..10 {
+                let mut config = shared.write();
+                let counter = config.int32("counter").unwrap();
+                config.set("counter", counter + 1).unwrap();
+            }
+        })
+    }).collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    assert_eq!(shared.read().int32("counter"), Some(40));
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("counter = 1;").is_ok());
+    let previous = shared.replace(cfg);
+    assert_eq!(previous.int32("counter"), Some(40));
+    assert_eq!(shared.read().int32("counter"), Some(1));
+}
+
+#[cfg(feature = "watch")]
+#[test]
+fn test_reloadable_config() {
+    use crate::reloadable::ReloadableConfig;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let file_name = std::env::temp_dir().join(format!("reloadable-{}.cfg",
+        std::process::id()));
+    fs::write(&file_name, "port = 80;\n").unwrap();
+    let cfg = ReloadableConfig::new(&file_name).unwrap();
+    assert_eq!(cfg.read().int32("port"), Some(80));
+
+    let (sender, reloads) = mpsc::channel();
+    let sender = std::sync::Mutex::new(sender);
+    cfg.subscribe(move |cfg| {
+        sender.lock().unwrap().send(cfg.int32("port")).unwrap();
+    });
+
+    fs::write(&file_name, "port = 81;\n").unwrap();
+    assert_eq!(reloads.recv_timeout(Duration::from_secs(5)).unwrap(),
+        Some(81));
+    assert_eq!(cfg.shared().read().int32("port"), Some(81));
+
+    fs::write(&file_name, "port = ;\n").unwrap();
+    assert_eq!(cfg.reload(), Err(Errors::ParseError));
+    assert_eq!(cfg.read().int32("port"), Some(81));
+    fs::remove_file(&file_name).unwrap();
+}
+
+#[test]
+fn test_layered_config() {
+    use crate::layered::LayeredConfig;
+
+    let mut cfg = LayeredConfig::new();
+    assert!(cfg.push_string("defaults", "
+        server = { host = \"localhost\"; port = 80; };
+        ports = [ 1, 2 ];
+        debug = false;
+    ").is_ok());
+    assert!(cfg.push_optional_file("user",
+        Path::new("/nonexistent/user.cfg")).is_ok());
+    assert!(cfg.push_string("system", "
+        server = { port = 8080; workers = 4; };
+        ports = [ 3 ];
+    ").is_ok());
+    cfg.push("overrides", Config::new());
+    assert!(cfg.layer_mut("overrides").unwrap().set("debug", true).is_ok());
+
+    assert_eq!(cfg.layer_names().collect::<Vec<_>>(),
+        ["defaults", "user", "system", "overrides"]);
+    assert_eq!(cfg.get_int32("server.port"), Some(8080));
+    assert_eq!(cfg.get_string("server.host"), Some("localhost".to_string()));
+    assert_eq!(cfg.get_bool("debug"), Some(true));
+    assert_eq!(cfg.layer_of("server.host"), Some("defaults"));
+    assert_eq!(cfg.layer_of("debug"), Some("overrides"));
+    assert_eq!(cfg.get_value("ports"), Some(Value::Array(vec![3.into()])));
+    assert!(!cfg.contains("server.threads"));
+
+    let server = cfg.get_value("server").unwrap();
+    assert_eq!(server.to_string().replace(char::is_whitespace, ""),
+        "{host=\"localhost\";port=8080;workers=4;}");
+    assert_eq!(cfg.value("server").unwrap().len(), Some(2));
+
+    let flat = cfg.flatten().unwrap();
+    assert_eq!(flat.int32("server.workers"), Some(4));
+    assert_eq!(flat.string("server.host"), Some("localhost".to_string()));
+
+    assert!(cfg.layer_mut("system").unwrap()
+        .load_from_string("server = { port = 81; };").is_ok());
+    assert_eq!(cfg.get_int32("server.port"), Some(81));
+    assert!(cfg.push_file("missing", Path::new("/nonexistent/x.cfg"))
+        .is_err());
+}
+
+#[test]
+fn test_merge_strategies() {
+    use crate::config::MergeStrategy;
+
+    let site = "
+        server = { host = \"site\"; port = 80; };
+        hosts = [ \"a\" ];
+        users = ( 1 );
+        level = 1;
+    ";
+    let mut local = Config::new();
+    assert!(local.load_from_string("
+        server = { port = 8080; workers = 4; };
+        hosts = [ \"b\", \"c\" ];
+        users = ( \"x\" );
+        level = [ 2 ];
+        extra = true;
+    ").is_ok());
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(site).is_ok());
+    assert!(cfg.merge(&local, MergeStrategy::Overwrite).is_ok());
+    assert_eq!(cfg.string("server.host"), Some("site".to_string()));
+    assert_eq!(cfg.int32("server.port"), Some(8080));
+    assert_eq!(cfg.int32("server.workers"), Some(4));
+    assert_eq!(cfg.value("hosts").unwrap().len(), Some(2));
+    assert_eq!(cfg.value("level").unwrap().is_array(), Some(true));
+    assert_eq!(cfg.bool("extra"), Some(true));
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(site).is_ok());
+    assert!(cfg.merge(&local, MergeStrategy::KeepExisting).is_ok());
+    assert_eq!(cfg.int32("server.port"), Some(80));
+    assert_eq!(cfg.int32("server.workers"), Some(4));
+    assert_eq!(cfg.value("hosts").unwrap().len(), Some(1));
+    assert_eq!(cfg.int32("level"), Some(1));
+    assert_eq!(cfg.bool("extra"), Some(true));
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string(site).is_ok());
+    assert!(cfg.merge(&local, MergeStrategy::AppendArrays).is_ok());
+    assert_eq!(cfg.value("hosts").unwrap().as_vec_string().unwrap(),
+        ["a", "b", "c"]);
+    assert_eq!(cfg.value("users").unwrap().len(), Some(2));
+    assert_eq!(cfg.value("level").unwrap().is_array(), Some(true));
+    assert_eq!(cfg.int32("server.port"), Some(8080));
+
+    let copy = cfg.clone();
+    assert!(cfg.merge(&copy, MergeStrategy::AppendArrays).is_ok());
+    assert_eq!(cfg.value("hosts").unwrap().len(), Some(6));
+}
+
+#[test]
+fn test_apply_env() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("
+        server = { port = 80; host = \"localhost\"; };
+        ratio = 1.5;
+        name = \"app\";
+        hosts = [ \"a\" ];
+    ").is_ok());
+
+    std::env::set_var("RUSTCONFIG_TEST_SERVER__PORT", "8080");
+    std::env::set_var("RUSTCONFIG_TEST_NAME", "42");
+    std::env::set_var("RUSTCONFIG_TEST_RATIO", "2");
+    std::env::set_var("RUSTCONFIG_TEST_LOG__LEVEL", "debug");
+    std::env::set_var("RUSTCONFIG_TEST_LOG__VERBOSE", "TRUE");
+    std::env::set_var("RUSTCONFIG_TEST_MASK", "0xff");
+    std::env::set_var("RUSTCONFIG_TEST_BAD__", "1");
+    std::env::set_var("RUSTCONFIG_TESTING", "1");
+    assert!(cfg.apply_env("RUSTCONFIG_TEST").is_ok());
+
+    assert_eq!(cfg.int32("server.port"), Some(8080));
+    assert_eq!(cfg.string("server.host"), Some("localhost".to_string()));
+    assert_eq!(cfg.string("name"), Some("42".to_string()));
+    assert_eq!(cfg.value("ratio").unwrap().as_float64(), Some(2.0));
+    assert_eq!(cfg.string("log.level"), Some("debug".to_string()));
+    assert_eq!(cfg.bool("log.verbose"), Some(true));
+    assert_eq!(cfg.int32("mask"), Some(255));
+    assert!(cfg.value("ing").is_none());
+
+    std::env::set_var("RUSTCONFIG_TEST_SERVER__PORT", "http");
+    assert_eq!(cfg.apply_env("RUSTCONFIG_TEST"), Err(Errors::TypeMismatch));
+    assert_eq!(cfg.string("log.level"), Some("debug".to_string()));
+    std::env::set_var("RUSTCONFIG_TEST_SERVER__PORT", "81");
+    std::env::set_var("RUSTCONFIG_TEST_HOSTS", "b");
+    assert_eq!(cfg.apply_env("RUSTCONFIG_TEST"), Err(Errors::TypeMismatch));
+    assert_eq!(cfg.int32("server.port"), Some(8080));
+}
+
+#[test]
+fn test_format_options() {
+    use crate::config::FormatOptions;
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("
+        server : { port = 80; ratio = 0.125; hosts = [ \"a\" ]; };
+        scale = 2.0;
+    ").is_ok());
+    assert_eq!(cfg.format_options(), FormatOptions::default());
+    let text = cfg.save_to_string().unwrap();
+
+    let options = FormatOptions::new()
+        .tab_width(4)
+        .float_precision(2)
+        .semicolon_separators(false)
+        .colon_assignment_for_groups(false)
+        .colon_assignment_for_non_groups(true)
+        .open_brace_on_separate_line(false);
+    cfg.set_format_options(options);
+    assert_eq!(cfg.format_options(), options);
+    assert_eq!(cfg.save_to_string().unwrap(), "server = {\n    port : 80\n    \
+        ratio : 0.12\n    hosts : [ \"a\" ]\n}\nscale : 2.0\n");
+
+    let copy = cfg.extract("server").unwrap();
+    assert_eq!(copy.format_options(), options);
+
+    let file_name = std::env::temp_dir().join(format!("format-{}.cfg",
+        std::process::id()));
+    assert!(cfg.save_to_file(&file_name).is_ok());
+    let saved = fs::read_to_string(&file_name).unwrap();
+    assert!(!saved.contains(';'));
+    assert!(saved.contains("    port : 80"));
+    assert!(saved.contains("ratio : 0.12\n"));
+    fs::remove_file(&file_name).unwrap();
+
+    cfg.set_format_options(FormatOptions::default());
+    assert_eq!(cfg.save_to_string().unwrap(), text);
+    assert!(cfg.save_to_file(&file_name).is_ok());
+    assert!(fs::read_to_string(&file_name).unwrap()
+        .contains("ratio = 0.125;\n"));
+    fs::remove_file(&file_name).unwrap();
+    assert_eq!(FormatOptions::new().tab_width(40),
+        FormatOptions::new().tab_width(15));
+}
+
+#[test]
+fn test_int_format() {
+    use crate::config::IntFormat;
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("
+        mask = 0x1F;
+        big = 0x100000000L;
+        port = 80;
+        flags = [ 1, 2 ];
+        name = \"x\";
+    ").is_ok());
+    assert_eq!(cfg.value("mask").unwrap().format(), Some(IntFormat::Hex));
+    assert_eq!(cfg.value("big").unwrap().format(), Some(IntFormat::Hex));
+    assert_eq!(cfg.value("port").unwrap().format(), Some(IntFormat::Default));
+    assert_eq!(cfg.value("name").unwrap().format(), None);
+
+    assert!(cfg.writer("port").unwrap().set_format(IntFormat::Hex).is_ok());
+    assert!(cfg.writer("flags").unwrap().set_format(IntFormat::Hex).is_ok());
+    assert!(cfg.writer("big").unwrap().set_format(IntFormat::Default)
+        .is_ok());
+    assert_eq!(cfg.writer("name").unwrap().set_format(IntFormat::Hex),
+        Err(Errors::TypeMismatch));
+    assert_eq!(cfg.save_to_string().unwrap(),
+        "mask = 0x1F;\nbig = 4294967296L;\nport = 0x50;\n\
+        flags = [ 0x1, 0x2 ];\nname = \"x\";\n");
+
+    let mut copy = Config::new();
+    assert!(copy.load_from_string(cfg.save_to_string().unwrap()).is_ok());
+    assert_eq!(copy.value("flags.[1]").unwrap().format(),
+        Some(IntFormat::Hex));
+    assert_eq!(copy.int32("port"), Some(80));
+}
+
+#[test]
+fn test_setting_location() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("
+        name = \"app\";
+        servers = ( { host = \"a\"; }, { host = \"b\"; port = 80; } );
+    ").is_ok());
+
+    let port = cfg.value("servers.[1].port").unwrap();
+    assert_eq!(port.name(), Some("port"));
+    assert_eq!(port.index(), Some(1));
+    assert_eq!(port.path(), "servers.[1].port");
+
+    let server = port.parent().unwrap();
+    assert_eq!(server.name(), None);
+    assert_eq!(server.index(), Some(1));
+    assert_eq!(server.path(), "servers.[1]");
+    assert_eq!(cfg.value(server.path()).unwrap().len(), Some(2));
+
+    let root = cfg.value("").unwrap();
+    assert_eq!((root.index(), root.path()), (None, String::new()));
+    assert_eq!(cfg.value("servers").unwrap().index(), Some(1));
+}
+
+#[test]
+fn test_aggregate_value_types() {
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("
+        server = { port = 80; };
+        ports = [ 80 ];
+        users = ( \"a\" );
+    ").is_ok());
+
+    assert_eq!(cfg.value("").unwrap().value_type(),
+        Some(OptionType::GroupType));
+    assert_eq!(cfg.value("server").unwrap().value_type(),
+        Some(OptionType::GroupType));
+    assert_eq!(cfg.value("ports").unwrap().value_type(),
+        Some(OptionType::ArrayType));
+    assert_eq!(cfg.value("users").unwrap().value_type(),
+        Some(OptionType::ListType));
+    assert_eq!(cfg.value("missing").and_then(|o| o.value_type()), None);
+
+    let schema = Schema::new()
+        .require("server", OptionType::GroupType)
+        .require("ports", OptionType::ListType);
+    let errors = cfg.validate(&schema);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ValidationErrorKind::WrongType {
+        expected : OptionType::ListType,
+        found : Some(OptionType::ArrayType)
+    });
+}
+
+#[test]
+fn test_load_from_reader() {
+    use std::io::{self, Cursor, Read};
+
+    struct Failing;
+
+    impl Read for Failing {
+        fn read(&mut self, _buf : &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("closed"))
+        }
+    }
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_reader(Cursor::new("port = 80;\r\n")).is_ok());
+    assert_eq!(cfg.int32("port"), Some(80));
+    assert_eq!(cfg.line_ending(), LineEnding::Crlf);
+
+    let text : Vec<u8> = "\u{feff}name = \"ä\";".encode_utf16()
+        .flat_map(|unit| unit.to_le_bytes()).collect();
+    assert!(cfg.load_from_reader(text.as_slice()).is_ok());
+    assert_eq!(cfg.string("name"), Some("ä".to_string()));
+
+    let mut out = Vec::new();
+    assert!(cfg.save_to_writer(&mut out).is_ok());
+    let mut copy = Config::new();
+    assert!(copy.load_from_reader(out.as_slice()).is_ok());
+    assert_eq!(copy.string("name"), Some("ä".to_string()));
+
+    assert_eq!(cfg.load_from_reader(Failing), Err(Errors::ParseError));
+    assert_eq!(cfg.load_from_reader(&b"a = 1;\0"[..]),
+        Err(Errors::InvalidString));
+    assert_eq!(cfg.load_from_reader(&b"a = ;"[..]), Err(Errors::ParseError));
+    assert_eq!(cfg.parse_error().unwrap().line, 1);
+}
+
+#[test]
+fn test_save_to_file_atomic() {
+    let directory = std::env::temp_dir().join(format!("atomic-{}",
+        std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let file_name = directory.join("app.cfg");
+    let backup = directory.join("app.cfg.bak");
+
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("port = 80;\r\n").is_ok());
+    assert!(cfg.save_to_file_atomic_with_backup(&file_name).is_ok());
+    assert!(!backup.exists());
+    assert_eq!(fs::read_to_string(&file_name).unwrap(), "port = 80;\r\n");
+
+    assert!(cfg.set("port", 8080).is_ok());
+    assert!(cfg.save_to_file_atomic_with_backup(&file_name).is_ok());
+    assert_eq!(fs::read_to_string(&backup).unwrap(), "port = 80;\r\n");
+    assert!(cfg.set("port", 8081).is_ok());
+    assert!(cfg.save_to_file_atomic(&file_name).is_ok());
+    assert_eq!(fs::read_to_string(&backup).unwrap(), "port = 80;\r\n");
+
+    let mut saved = Config::new();
+    assert!(saved.load_from_file(&file_name).is_ok());
+    assert_eq!(saved.int32("port"), Some(8081));
+    assert_eq!(fs::read_dir(&directory).unwrap().count(), 2);
+
+    assert_eq!(cfg.save_to_file_atomic(&directory.join("missing/app.cfg")),
+        Err(Errors::SaveError));
+    assert_eq!(fs::read_dir(&directory).unwrap().count(), 2);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_save_to_file_atomic_threads() {
+    let directory = std::env::temp_dir().join(format!("atomic-threads-{}",
+        std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let file_name = directory.join("app.cfg");
+
+    let threads : Vec<_> = (0..8).map(|port| {
+        let file_name = file_name.clone();
+        std::thread::spawn(move || {
+            let mut cfg = Config::new();
+            assert!(cfg.load_from_string(format!("port = {};", port))
+                .is_ok());
+            for _ in 0..20 {
+                assert!(cfg.save_to_file_atomic(&file_name).is_ok());
+            }
+        })
+    }).collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let mut saved = Config::new();
+    assert!(saved.load_from_file(&file_name).is_ok());
+    assert!(saved.int32("port").is_some());
+    assert_eq!(fs::read_dir(&directory).unwrap().count(), 1);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_write_errors() {
+    let mut cfg = Config::new();
+    let mut root = cfg.root();
+    assert!(root.write_int32("port", 80).is_ok());
+    assert_eq!(root.write_int32("port", 81).err(),
+        Some(Errors::DuplicateName));
+    assert_eq!(root.write_bool("1st", true).err(), Some(Errors::InvalidName));
+    assert_eq!(root.write_int64("a\0b", 1).err(),
+        Some(Errors::InvalidString));
+    assert_eq!(root.write_string("host", "local\0host").err(),
+        Some(Errors::InvalidString));
+
+    let ports = root.create_array("ports").unwrap();
+    assert!(ports.write_int32(80).and_then(|ports| ports.write_int32(443))
+        .is_ok());
+    assert_eq!(ports.write_string("http").err(), Some(Errors::TypeMismatch));
+
+    let mut root = cfg.root();
+    let list = root.create_list("mixed").unwrap();
+    assert!(list.write_int32(1).and_then(|list| list.write_string("a"))
+        .is_ok());
+
+    let port = cfg.writer("port").unwrap();
+    assert_eq!(port.write_int32("value", 1).err(),
+        Some(Errors::TypeMismatch));
+    assert!(cfg.value("host").is_none());
+    assert_eq!(cfg.value("ports").unwrap().len(), Some(2));
+}
+
+#[test]
+fn test_invalid_strings() {
+    let mut cfg = Config::new();
+    assert_eq!(cfg.load_from_string("a = 1;\0b = 2;"),
+        Err(Errors::InvalidString));
+    assert!(cfg.load_from_string("a = 1;").is_ok());
+
+    assert_eq!(cfg.set("x.y\0z", 1), Err(Errors::InvalidString));
+    assert!(cfg.value("x").is_none());
+    assert_eq!(cfg.set("b", "c\0d"), Err(Errors::InvalidString));
+    assert!(cfg.value("b").is_none());
+    assert!(cfg.value("a\0").is_none());
+
+    assert_eq!(cfg.save_to_file(Path::new("invalid\0.cfg")),
+        Err(Errors::InvalidString));
+    assert_eq!(cfg.include_dir(Path::new("dir\0")),
+        Err(Errors::InvalidString));
+    assert!(cfg.include_dir(Path::new("dir")).is_ok());
+    assert_eq!(cfg.value("a").unwrap().as_int32(), Some(1));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_non_utf8_path() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let file_name = Path::new(OsStr::from_bytes(b"test_non_utf8_\xff.cfg"));
+    let mut cfg = Config::new();
+    assert!(cfg.load_from_string("port = 80;").is_ok());
+    assert!(cfg.save_to_file(file_name).is_ok());
+
+    let mut copy = Config::new();
+    assert!(copy.load_from_file(file_name).is_ok());
+    assert_eq!(copy.value("port").unwrap().as_int32(), Some(80));
+
+    assert!(fs::write(file_name, "port = ;").is_ok());
+    assert!(copy.load_from_file(file_name).is_err());
+    assert_eq!(copy.parse_error().unwrap().file.as_deref(), Some(file_name));
+    assert!(fs::remove_file(file_name).is_ok());
+}
+
+#[test]
+fn test_clone_config() {
+    let mut template = Config::new();
+    assert!(template.load_from_string("server : { port = 0xFF; \
+        hosts = [ \"a\", \"b\" ]; };\nlimits = ( 1, \"x\" );").is_ok());
+    let mut tenant = template.clone();
+    assert!(tenant.set("server.port", 8080).is_ok());
+    assert_eq!(template.value("server.port").unwrap().as_int32(), Some(255));
+    assert_eq!(template.clone().value("server.port").unwrap().format(),
+        Some(crate::config::IntFormat::Hex));
+    assert_eq!(tenant.value("limits.[1]").unwrap().as_str(), Some("x"));
+    drop(template);
+    assert_eq!(tenant.value("server.hosts.[1]").unwrap().as_str(), Some("b"));
+
+    let mut other = Config::new();
+    let server = tenant.value("server").unwrap();
+    assert!(other.set("copy", server.clone_subtree().unwrap()).is_ok());
+    assert_eq!(other.value("copy.port").unwrap().as_int32(), Some(8080));
+
+    let group = other.create_section("group").unwrap();
+    assert!(group.copy_from(&server).is_ok());
+    assert_eq!(group.copy_from(&server).err(), Some(Errors::DuplicateName));
+    let element = tenant.value("limits.[0]").unwrap();
+    assert_eq!(group.copy_from(&element).err(), Some(Errors::InvalidName));
+    assert_eq!(other.value("group.server.hosts").unwrap().len(), Some(2));
+    assert!(other.root().copy_from(&tenant.value("limits").unwrap()).is_ok());
+    assert_eq!(other.value("limits.[0]").unwrap().as_int32(), Some(1));
+
+    let list = other.writer("limits").unwrap();
+    assert!(list.copy_from(&element).is_ok());
+    assert_eq!(other.value("limits").unwrap().len(), Some(3));
+}
+
+#[test]
+fn test_config_equality() {
+    let mut cfg = Config::new();
+    let mut other = Config::new();
+    assert!(cfg.load_from_string("server : { port = 80; hosts = [ \"a\" ]; \
+        };\nratio = 0.5;").is_ok());
+    assert!(other.load_from_string("ratio = 0.5;\nserver : {\n  hosts = \
+        [\"a\"]\n  port = 0x50\n};").is_ok());
+    assert!(cfg == other);
+    assert!(Config::new() == Config::new());
+    assert!(cfg.value("server").unwrap() == other.value("server").unwrap());
+
+    assert!(other.set("server.hosts.[1]", "b").is_ok());
+    assert!(cfg != other);
+    assert!(cfg.value("server").unwrap() != other.value("server").unwrap());
+    assert!(cfg.value("ratio").unwrap() == other.value("ratio").unwrap());
+
+    let mut int64 = Config::new();
+    assert!(int64.load_from_string("ratio = 0.5;\nserver : { port = 80L; \
+        hosts = [ \"a\" ]; };").is_ok());
+    assert!(cfg != int64);
+
+    let mut list = Config::new();
+    assert!(list.load_from_string("a = ( 1, 2 );").is_ok());
+    let mut array = Config::new();
+    assert!(array.load_from_string("a = [ 1, 2 ];").is_ok());
+    assert!(list != array);
+    assert!(list.value("a.[0]").unwrap() == array.value("a.[0]").unwrap());
+}
+
+#[test]
+fn test_apply_patch() {
+    let mut old = Config::new();
+    let mut new = Config::new();
+    assert!(old.load_from_string("server : { port = 80; hosts = [ \"a\", \
+        \"b\", \"c\" ]; };\nlog : { level = \"info\"; };").is_ok());
+    assert!(new.load_from_string("server : { port = 8080; hosts = [ \"x\" ]; \
+        tls : { enabled = true; }; };\nlimits = ( 1, 2 );").is_ok());
+
+    let mut target = old.clone();
+    assert!(target.apply_patch(&old.diff(&new)).is_ok());
+    assert!(target == new);
+    assert!(target.diff(&new).is_empty());
+
+    let patch = vec![
+        Change {
+            path : "server.port".to_string(),
+            kind : ChangeKind::Changed {
+                old : Value::Int(80),
+                new : Value::Int(81)
+            }
+        },
+        Change {
+            path : "missing".to_string(),
+            kind : ChangeKind::Removed(Value::Int(1))
+        }
+    ];
+    assert_eq!(old.apply_patch(&patch), Err(Errors::ElementNotExists));
+    assert_eq!(old.value("server.port").unwrap().as_int32(), Some(80));
+    assert!(old.apply_patch(&patch[..1]).is_ok());
+    assert_eq!(old.value("server.port").unwrap().as_int32(), Some(81));
+}
@@ -0,0 +1,236 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Helpers for testing generated configs.
+//!
+//! Snapshot text is stable between runs and library versions: group
+//! members are sorted by name and floats are written with fixed number of
+//! decimal digits, so it can be compared with golden files. Round-trip
+//! assertions check that saved config parses back to the same settings.
+
+use crate::config::Config;
+use crate::diff::ChangeKind;
+use crate::format;
+use crate::value::{self, Value};
+
+use std::fmt::Write;
+
+/// Decimal digits of floats in snapshot text.
+pub const DEFAULT_FLOAT_PRECISION : usize = 6;
+
+// Return float with fixed number of decimal digits. Negative values rounded
+// to zero are written without sign.
+fn float_text(value : f64, precision : usize) -> String {
+    let text = format!("{:.*}", precision, value);
+    match text.strip_prefix('-') {
+        Some(zero) if zero.chars().all(|c| c == '0' || c == '.') => {
+            zero.to_string()
+        },
+        _ => { text }
+    }
+}
+
+// Write value in snapshot layout, nested groups start at depth.
+fn write_value(out : &mut String, value : &Value, depth : usize,
+    precision : usize) {
+    match value {
+        Value::Int(val) => {
+            if *val >= i32::MIN as i64 && *val <= i32::MAX as i64 {
+                let _ = write!(out, "{}", val);
+            } else {
+                let _ = write!(out, "{}L", val);
+            }
+        },
+        Value::Float(val) => { out.push_str(&float_text(*val, precision)); },
+        Value::Bool(val) => { let _ = write!(out, "{}", val); },
+        Value::Str(val) => { format::write_string(out, val); },
+        Value::Array(elements) | Value::List(elements) => {
+            let (open, close) = match value {
+                Value::Array(_) => { ('[', ']') },
+                _ => { ('(', ')') }
+            };
+
+            out.push(open);
+            for (index, element) in elements.iter().enumerate() {
+                out.push_str(if index == 0 { " " } else { ", " });
+                write_value(out, element, depth, precision);
+            }
+            if !elements.is_empty() {
+                out.push(' ');
+            }
+            out.push(close);
+        },
+        Value::Group(_) => {
+            out.push_str("{\n");
+            write_members(out, value, depth + 1, precision);
+            format::indent(out, depth);
+            out.push('}');
+        }
+    }
+}
+
+// Write group members sorted by name.
+fn write_members(out : &mut String, group : &Value, depth : usize,
+    precision : usize) {
+    let children = match group {
+        Value::Group(children) => { children },
+        _ => { return }
+    };
+
+    let mut names : Vec<&String> = children.keys().collect();
+    names.sort();
+    for name in names {
+        let child = &children[name];
+        format::indent(out, depth);
+        out.push_str(name);
+        match child {
+            Value::Group(_) => {
+                out.push_str(" :\n");
+                format::indent(out, depth);
+            },
+            _ => { out.push_str(" = "); }
+        }
+        write_value(out, child, depth, precision);
+        out.push_str(";\n");
+    }
+}
+
+impl Config {
+
+    /// Return config text normalized for snapshot tests, floats have
+    /// `DEFAULT_FLOAT_PRECISION` decimal digits.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("b = 0.1; a = { y = 2; x = 1; };").is_ok() {
+    ///     assert_eq!(cfg.to_snapshot_string(),
+    ///         "a :\n{\n  x = 1;\n  y = 2;\n};\nb = 0.100000;\n");
+    /// }
+    /// ```
+    pub fn to_snapshot_string(&self) -> String {
+        self.to_snapshot_string_with_precision(DEFAULT_FLOAT_PRECISION)
+    }
+
+    /// Return config text normalized for snapshot tests, floats have
+    /// `precision` decimal digits.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("ratio = 0.333333333;").is_ok() {
+    ///     assert_eq!(cfg.to_snapshot_string_with_precision(2),
+    ///         "ratio = 0.33;\n");
+    /// }
+    /// ```
+    pub fn to_snapshot_string_with_precision(&self, precision : usize)
+        -> String {
+        let mut out = String::new();
+        if let Some(root) = self.root_element.and_then(value::read) {
+            write_members(&mut out, &root, 0, precision);
+        }
+        out
+    }
+}
+
+/// Save config to text, parse it back and panic if settings differ. Floats
+/// are equal if they match in `DEFAULT_FLOAT_PRECISION` decimal digits.
+///
+/// # Panics
+/// Panics if config can't be saved, saved text can't be parsed or parsed
+/// settings differ, message lists changed paths and the saved text.
+///
+/// # Example
+/// ```
+/// use librustconfig::config::Config;
+/// use librustconfig::testing::assert_roundtrip;
+///
+/// let mut cfg = Config::new();
+/// cfg.set("server.port", 8080).ok();
+/// assert_roundtrip(&cfg);
+/// ```
+pub fn assert_roundtrip(config : &Config) {
+    assert_roundtrip_with_precision(config, DEFAULT_FLOAT_PRECISION);
+}
+
+/// Save config to text, parse it back and panic if settings differ. Floats
+/// are equal if they match in `precision` decimal digits.
+///
+/// # Panics
+/// Panics if config can't be saved, saved text can't be parsed or parsed
+/// settings differ.
+///
+/// # Example
+/// ```
+/// use librustconfig::config::Config;
+/// use librustconfig::testing::assert_roundtrip_with_precision;
+///
+/// let mut cfg = Config::new();
+/// if cfg.load_from_string("ratio = 0.1;").is_ok() {
+///     assert_roundtrip_with_precision(&cfg, 15);
+/// }
+/// ```
+pub fn assert_roundtrip_with_precision(config : &Config, precision : usize) {
+    let text = match config.save_to_string() {
+        Ok(text) => { text },
+        Err(error) => { panic!("config can't be saved: {:?}", error) }
+    };
+    let mut parsed = Config::new();
+    if let Err(error) = parsed.load_from_string(text.as_str()) {
+        panic!("saved config can't be parsed: {:?}\n{}", error, text);
+    }
+
+    let changes : Vec<String> = config.diff(&parsed).into_iter()
+        .filter_map(|change| match change.kind {
+            ChangeKind::Changed {
+                old : Value::Float(old),
+                new : Value::Float(new)
+            } if float_text(old, precision) == float_text(new, precision) => {
+                None
+            },
+            ChangeKind::Added(new) => {
+                Some(format!("{}: added {}", change.path, new))
+            },
+            ChangeKind::Removed(old) => {
+                Some(format!("{}: removed {}", change.path, old))
+            },
+            ChangeKind::Changed { old, new } => {
+                Some(format!("{}: {} became {}", change.path, old, new))
+            }
+        }).collect();
+    if !changes.is_empty() {
+        panic!("config differs after round trip:\n{}\n{}",
+            changes.join("\n"), text);
+    }
+}
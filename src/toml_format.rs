@@ -0,0 +1,251 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! TOML conversion, `toml` feature.
+//!
+//! Settings are converted where both formats have the same types: integers,
+//! floats, booleans, strings, groups as tables, arrays and lists as arrays.
+//! TOML arrays of scalars of one type become libconfig arrays, other arrays
+//! become lists. Settings which can't be represented, like TOML dates or
+//! keys which aren't valid setting names, are skipped and reported. TOML
+//! puts tables after other keys, so order of group members may change.
+
+use crate::config::{Config, ParseDiagnostic, ParseErrorKind};
+use crate::path;
+use crate::value::{self, Value};
+use libconfig_sys as raw;
+
+use indexmap::IndexMap;
+use std::ffi::CStr;
+
+/// Setting skipped by conversion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Skipped {
+    /// Setting path in source document.
+    pub path : String,
+    /// Why setting can't be represented.
+    pub reason : String
+}
+
+// Create report of skipped setting.
+fn skip(path : String, reason : &str) -> Skipped {
+    Skipped { path, reason : reason.to_string() }
+}
+
+// Convert setting to TOML value, None if it is skipped.
+fn to_toml_value(option : *mut raw::config_setting_t, path : &str,
+    skipped : &mut Vec<Skipped>) -> Option<toml::Value> {
+    let value = unsafe {
+        match raw::config_setting_type(option) as i16 {
+            raw::CONFIG_TYPE_INT => {
+                toml::Value::Integer(raw::config_setting_get_int(option) as i64)
+            },
+            raw::CONFIG_TYPE_INT64 => {
+                toml::Value::Integer(raw::config_setting_get_int64(option))
+            },
+            raw::CONFIG_TYPE_FLOAT => {
+                toml::Value::Float(raw::config_setting_get_float(option))
+            },
+            raw::CONFIG_TYPE_BOOL => {
+                toml::Value::Boolean(raw::config_setting_get_bool(option) ==
+                    raw::CONFIG_TRUE)
+            },
+            raw::CONFIG_TYPE_STRING => {
+                let text = raw::config_setting_get_string(option);
+                match (!text.is_null()).then(|| CStr::from_ptr(text).to_str()) {
+                    Some(Ok(text)) => { toml::Value::String(text.to_string()) },
+                    _ => {
+                        skipped.push(skip(path.to_string(),
+                            "string is not valid UTF-8"));
+                        return None;
+                    }
+                }
+            },
+            raw::CONFIG_TYPE_ARRAY | raw::CONFIG_TYPE_LIST => {
+                let length = raw::config_setting_length(option);
+                toml::Value::Array((0..length).filter_map(|index| {
+                    let child = raw::config_setting_get_elem(option,
+                        index as u32);
+                    to_toml_value(child, &path::join(path,
+                        &format!("[{}]", index)), skipped)
+                }).collect())
+            },
+            raw::CONFIG_TYPE_GROUP => {
+                toml::Value::Table(to_table(option, path, skipped))
+            },
+            _ => {
+                skipped.push(skip(path.to_string(), "setting has no value"));
+                return None;
+            }
+        }
+    };
+    Some(value)
+}
+
+// Convert group members to TOML table.
+fn to_table(group : *mut raw::config_setting_t, path : &str,
+    skipped : &mut Vec<Skipped>) -> toml::Table {
+    let mut table = toml::Table::new();
+    for index in 0..unsafe { raw::config_setting_length(group) } {
+        let child = unsafe {
+            raw::config_setting_get_elem(group, index as u32)
+        };
+        let name = raw::config_setting_name(child);
+        if name.is_null() {
+            continue;
+        }
+        let name = unsafe { CStr::from_ptr(name) }.to_string_lossy();
+        let child_path = path::join(path, &name);
+        if let Some(value) = to_toml_value(child, &child_path, skipped) {
+            table.insert(name.into_owned(), value);
+        }
+    }
+    table
+}
+
+// Convert TOML value to owned value, None if it is skipped.
+fn from_toml_value(value : &toml::Value, path : &str,
+    skipped : &mut Vec<Skipped>) -> Option<Value> {
+    match value {
+        toml::Value::Integer(val) => { Some(Value::Int(*val)) },
+        toml::Value::Float(val) => { Some(Value::Float(*val)) },
+        toml::Value::Boolean(val) => { Some(Value::Bool(*val)) },
+        toml::Value::String(val) => { Some(Value::Str(val.clone())) },
+        toml::Value::Datetime(_) => {
+            skipped.push(skip(path.to_string(),
+                "dates are not supported by libconfig"));
+            None
+        },
+        toml::Value::Array(elements) => {
+            let elements : Vec<Value> = elements.iter().enumerate()
+                .filter_map(|(index, element)| {
+                    from_toml_value(element, &path::join(path,
+                        &format!("[{}]", index)), skipped)
+                }).collect();
+            let scalar = |value : &Value| {
+                !matches!(value, Value::Array(_) | Value::List(_) |
+                    Value::Group(_))
+            };
+            let same_type = elements.windows(2).all(|pair| {
+                std::mem::discriminant(&pair[0]) ==
+                    std::mem::discriminant(&pair[1])
+            });
+            if same_type && elements.iter().all(scalar) {
+                Some(Value::Array(elements))
+            } else {
+                Some(Value::List(elements))
+            }
+        },
+        toml::Value::Table(table) => {
+            Some(Value::Group(from_table(table, path, skipped)))
+        }
+    }
+}
+
+// Convert TOML table to group members.
+fn from_table(table : &toml::Table, path : &str,
+    skipped : &mut Vec<Skipped>) -> IndexMap<String, Value> {
+    let mut group = IndexMap::new();
+    for (name, value) in table.iter() {
+        let child_path = path::join(path, name);
+        if !path::valid_name(name) {
+            skipped.push(skip(child_path, "key is not valid setting name"));
+            continue;
+        }
+        if let Some(value) = from_toml_value(value, &child_path, skipped) {
+            group.insert(name.clone(), value);
+        }
+    }
+    group
+}
+
+impl Config {
+
+    /// Convert config to TOML document, `toml` feature. Returns document and
+    /// settings which couldn't be converted.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { port = 80; };").is_ok() {
+    ///     let (document, skipped) = cfg.to_toml();
+    ///     assert_eq!(document, "[server]\nport = 80\n");
+    ///     assert!(skipped.is_empty());
+    /// }
+    /// ```
+    pub fn to_toml(&self) -> (String, Vec<Skipped>) {
+        let mut skipped = Vec::new();
+        let table = match self.root_element {
+            Some(root) => { to_table(root, "", &mut skipped) },
+            None => { toml::Table::new() }
+        };
+        (table.to_string(), skipped)
+    }
+
+    /// Create config from TOML document, `toml` feature. Returns config and
+    /// settings which couldn't be converted, or error if document is not
+    /// valid TOML.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let (cfg, skipped) = Config::from_toml(
+    ///     "[server]\nport = 80\nstarted = 2024-01-01\n").unwrap();
+    /// assert_eq!(cfg.value("server.port").unwrap().as_int32(), Some(80));
+    /// assert_eq!(skipped[0].path, "server.started");
+    /// ```
+    pub fn from_toml(text : &str)
+        -> Result<(Config, Vec<Skipped>), ParseDiagnostic> {
+        let table = text.parse::<toml::Table>().map_err(|error| {
+            ParseDiagnostic {
+                file : None,
+                line : error.span().map_or(0, |span| {
+                    text[..span.start].matches('\n').count() as u32 + 1
+                }),
+                message : error.message().to_string(),
+                kind : ParseErrorKind::Parse
+            }
+        })?;
+
+        let mut skipped = Vec::new();
+        let cfg = Config::new();
+        if let Some(root) = cfg.root_element {
+            for (name, value) in from_table(&table, "", &mut skipped) {
+                if value::write(root, &name, &value).is_err() {
+                    skipped.push(skip(name, "setting can't be written"));
+                }
+            }
+        }
+        Ok((cfg, skipped))
+    }
+}
@@ -0,0 +1,191 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Read access tracking.
+//!
+//! When tracking is enabled, every setting read through readers created by
+//! the config is recorded, so settings never consumed by the application
+//! can be reported.
+
+use crate::cache;
+use crate::config::{Config, OptionReader};
+use crate::index::ChildReaders;
+use crate::path;
+use libconfig_sys as raw;
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::ffi::CStr;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+/// Set of settings read through the API.
+pub(crate) type Tracker = Rc<RefCell<HashSet<*mut raw::config_setting_t>>>;
+
+impl Config {
+
+    /// Enable or disable recording of settings read through the API.
+    /// Recorded reads are cleared when config is loaded again.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// cfg.track_reads(true);
+    /// ```
+    pub fn track_reads(&mut self, enable : bool) {
+        cache::invalidate(&self.generation);
+        self.tracker = if enable {
+            Some(Rc::new(RefCell::new(HashSet::new())))
+        } else {
+            None
+        };
+    }
+
+    /// Return paths of settings that were never read since tracking was
+    /// enabled. Arrays are reported as a whole, elements of lists are
+    /// reported with `[N]` segments. Returns empty list if tracking is
+    /// disabled.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// cfg.track_reads(true);
+    /// if cfg.load_from_string("port = 80; legacy_port = 81;").is_ok() {
+    ///     let _ = cfg.value("port").unwrap().as_int32();
+    ///     assert_eq!(cfg.unused_settings(), vec!["legacy_port"]);
+    /// }
+    /// ```
+    pub fn unused_settings(&self) -> Vec<String> {
+        let mut unused = Vec::new();
+        if let (Some(tracker), Some(root)) =
+            (&self.tracker, self.root_element) {
+            collect_unused(&tracker.borrow(), root, "", &mut unused);
+        }
+        unused
+    }
+
+    // Create reader of element sharing config read tracker.
+    pub(crate) fn reader(&self, element : Option<*mut raw::config_setting_t>)
+        -> OptionReader<'_> {
+        OptionReader {
+            element,
+            tracker : self.tracker.clone(),
+            resolved : self.resolve_references,
+            children : ChildReaders::default(),
+            marker : PhantomData
+        }
+    }
+
+    // Forget recorded reads, settings are going to be replaced.
+    pub(crate) fn clear_reads(&self) {
+        if let Some(tracker) = &self.tracker {
+            tracker.borrow_mut().clear();
+        }
+    }
+}
+
+impl<'a> OptionReader<'a> {
+
+    // Create reader of another element sharing read tracker.
+    pub(crate) fn derive(&self, element : Option<*mut raw::config_setting_t>)
+        -> OptionReader<'a> {
+        OptionReader {
+            element,
+            tracker : self.tracker.clone(),
+            resolved : self.resolved,
+            children : ChildReaders::default(),
+            marker : PhantomData
+        }
+    }
+
+    // Record read of element.
+    pub(crate) fn mark_read(&self) {
+        if let (Some(tracker), Some(element)) = (&self.tracker, self.element) {
+            tracker.borrow_mut().insert(element);
+        }
+    }
+}
+
+// Collect paths of unread settings of group or list.
+fn collect_unused(read : &HashSet<*mut raw::config_setting_t>,
+    option : *mut raw::config_setting_t, option_path : &str,
+    unused : &mut Vec<String>) {
+
+    let group = raw::config_setting_is_group(option) == raw::CONFIG_TRUE;
+    let collection_read = !group && read.contains(&option);
+
+    for index in 0..unsafe { raw::config_setting_length(option) } {
+        let child = unsafe {
+            raw::config_setting_get_elem(option, index as u32)
+        };
+        if child.is_null() {
+            continue;
+        }
+
+        let name = if group {
+            let name = raw::config_setting_name(child);
+            if name.is_null() {
+                continue;
+            }
+            match unsafe { CStr::from_ptr(name) }.to_str() {
+                Ok(name) => { name.to_string() },
+                Err(_) => { continue }
+            }
+        } else {
+            format!("[{}]", index)
+        };
+        let child_path = path::join(option_path, &name);
+
+        if raw::config_setting_is_group(child) == raw::CONFIG_TRUE ||
+            raw::config_setting_is_list(child) == raw::CONFIG_TRUE {
+            collect_unused(read, child, &child_path, unused);
+        } else if raw::config_setting_is_array(child) == raw::CONFIG_TRUE {
+            if !read.contains(&child) && !any_element_read(read, child) {
+                unused.push(child_path);
+            }
+        } else if !read.contains(&child) && !collection_read {
+            unused.push(child_path);
+        }
+    }
+}
+
+// Return true if any element of array was read.
+fn any_element_read(read : &HashSet<*mut raw::config_setting_t>,
+    option : *mut raw::config_setting_t) -> bool {
+    (0..unsafe { raw::config_setting_length(option) }).any(|index| {
+        read.contains(&unsafe {
+            raw::config_setting_get_elem(option, index as u32)
+        })
+    })
+}
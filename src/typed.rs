@@ -0,0 +1,473 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Typed reads of settings.
+//!
+//! Types implementing `FromOptionValue` can be read with a single
+//! `Config::get::<T>(path)` call or `cfg_get!` macro. Integers are
+//! range-checked, so reading `port = 70000;` as `u16` is an error instead
+//! of silent truncation.
+
+use crate::config::{Config, Errors, OptionReader, OptionType, Result};
+use crate::value::{self, Value};
+
+use std::convert::TryFrom;
+
+/// Conversion of setting to Rust value.
+pub trait FromOptionValue : Sized {
+    /// Convert setting, return `TypeMismatch` if setting has another type
+    /// and `OutOfRange` if value doesn't fit into target type.
+    fn from_option(option : &OptionReader) -> Result<Self>;
+}
+
+/// Domain type read from setting of another type, so validation lives in
+/// the type instead of every place it is read. Types implementing it can be
+/// read with `Config::get`.
+///
+/// # Example
+/// ```
+/// use librustconfig::config::{Config, Errors};
+/// use librustconfig::typed::ConfigValue;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Port(u16);
+///
+/// impl ConfigValue for Port {
+///     type Raw = u16;
+///
+///     fn from_raw(raw : u16) -> Result<Port, Errors> {
+///         if raw == 0 { Err(Errors::OutOfRange) } else { Ok(Port(raw)) }
+///     }
+/// }
+///
+/// let mut cfg = Config::new();
+/// if cfg.load_from_string("port = 8080; unset = 0;").is_ok() {
+///     assert_eq!(cfg.get::<Port>("port"), Ok(Port(8080)));
+///     assert_eq!(cfg.get::<Port>("unset"), Err(Errors::OutOfRange));
+/// }
+/// ```
+pub trait ConfigValue : Sized {
+    /// Type setting is read as before validation.
+    type Raw : FromOptionValue;
+
+    /// Validate read value, errors are returned by `Config::get`.
+    fn from_raw(raw : Self::Raw) -> Result<Self>;
+}
+
+impl<T> FromOptionValue for T where T: ConfigValue {
+    fn from_option(option : &OptionReader) -> Result<T> {
+        T::from_raw(T::Raw::from_option(option)?)
+    }
+}
+
+// Read integer setting of any width.
+fn integer(option : &OptionReader) -> Result<i64> {
+    match option.value_type() {
+        Some(OptionType::IntegerType) | Some(OptionType::Int64Type) => {
+            option.as_int64().ok_or(Errors::ElementNotExists)
+        },
+        _ => { Err(Errors::TypeMismatch) }
+    }
+}
+
+macro_rules! integer_from_option {
+    ($($integer:ty),*) => {
+        $(
+            impl FromOptionValue for $integer {
+                fn from_option(option : &OptionReader) -> Result<$integer> {
+                    <$integer>::try_from(integer(option)?)
+                        .map_err(|_| Errors::OutOfRange)
+                }
+            }
+        )*
+    };
+}
+
+integer_from_option!(i8, i16, i32, i64, u8, u16, u32, u64, usize, isize);
+
+impl FromOptionValue for i128 {
+    fn from_option(option : &OptionReader) -> Result<i128> {
+        option.as_i128().ok_or(Errors::TypeMismatch)
+    }
+}
+
+impl FromOptionValue for u128 {
+    fn from_option(option : &OptionReader) -> Result<u128> {
+        u128::try_from(i128::from_option(option)?)
+            .map_err(|_| Errors::OutOfRange)
+    }
+}
+
+impl FromOptionValue for f64 {
+    fn from_option(option : &OptionReader) -> Result<f64> {
+        match option.value_type() {
+            Some(OptionType::FloatType) => {
+                option.as_float64().ok_or(Errors::ElementNotExists)
+            },
+            _ => { Err(Errors::TypeMismatch) }
+        }
+    }
+}
+
+impl FromOptionValue for f32 {
+    fn from_option(option : &OptionReader) -> Result<f32> {
+        let val = f64::from_option(option)?;
+        if val.is_finite() && val.abs() > f32::MAX as f64 {
+            return Err(Errors::OutOfRange);
+        }
+        Ok(val as f32)
+    }
+}
+
+impl FromOptionValue for bool {
+    fn from_option(option : &OptionReader) -> Result<bool> {
+        match option.value_type() {
+            Some(OptionType::BooleanType) => {
+                option.as_bool().ok_or(Errors::ElementNotExists)
+            },
+            _ => { Err(Errors::TypeMismatch) }
+        }
+    }
+}
+
+impl FromOptionValue for String {
+    fn from_option(option : &OptionReader) -> Result<String> {
+        match option.value_type() {
+            Some(OptionType::StringType) => {
+                option.as_string().ok_or(Errors::ElementNotExists)
+            },
+            _ => { Err(Errors::TypeMismatch) }
+        }
+    }
+}
+
+impl FromOptionValue for Value {
+    fn from_option(option : &OptionReader) -> Result<Value> {
+        option.mark_read();
+        option.element.and_then(value::read).ok_or(Errors::ElementNotExists)
+    }
+}
+
+impl<T> FromOptionValue for Vec<T> where T: FromOptionValue {
+    fn from_option(option : &OptionReader) -> Result<Vec<T>> {
+        if option.is_array() != Some(true) && option.is_list() != Some(true) {
+            return Err(Errors::TypeMismatch);
+        }
+        option.mark_read();
+        option.children().map(|element| T::from_option(&element)).collect()
+    }
+}
+
+/// Read setting converted to type, with optional default used if setting
+/// not exists. Expands to `Config::get` or `Config::get_or` call and
+/// returns `Result`, settings of another type or out of range are errors.
+///
+/// # Example
+/// ```
+/// use librustconfig::cfg_get;
+/// use librustconfig::config::Config;
+///
+/// let mut cfg = Config::new();
+/// if cfg.load_from_string("server : { port = 9090; };").is_ok() {
+///     assert_eq!(cfg_get!(cfg, "server.port" as u16), Ok(9090));
+///     assert_eq!(cfg_get!(cfg, "server.workers" as u32, default 4), Ok(4));
+/// }
+/// ```
+#[macro_export]
+macro_rules! cfg_get {
+    ($config:expr, $path:literal as $type:ty) => {
+        $config.get::<$type>($path)
+    };
+    ($config:expr, $path:literal as $type:ty, default $default:expr) => {
+        $config.get_or::<$type>($path, $default)
+    };
+}
+
+impl Config {
+
+    /// Read setting at path converted to `T`. Returns `ElementNotExists` if
+    /// setting not exists, `TypeMismatch` if it has another type and
+    /// `OutOfRange` if value doesn't fit into `T`. Path is `&str`, so type
+    /// can be set with turbofish.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::{Config, Errors};
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("port = 8080; hosts = [ \"a\", \"b\" ];")
+    ///     .is_ok() {
+    ///     assert_eq!(cfg.get::<u16>("port"), Ok(8080));
+    ///     assert_eq!(cfg.get::<u8>("port"), Err(Errors::OutOfRange));
+    ///     assert_eq!(cfg.get::<Vec<String>>("hosts").unwrap().len(), 2);
+    /// }
+    /// ```
+    pub fn get<T>(&self, path : &str) -> Result<T>
+        where T: FromOptionValue {
+        let option = self.value(path).ok_or(Errors::ElementNotExists)?;
+        T::from_option(&option)
+    }
+
+    /// Read setting at path converted to `T`, return default if setting
+    /// not exists. Settings of another type or out of range are errors.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("workers = 4;").is_ok() {
+    ///     assert_eq!(cfg.get_or::<u32>("workers", 1), Ok(4));
+    ///     assert_eq!(cfg.get_or::<u32>("threads", 1), Ok(1));
+    /// }
+    /// ```
+    pub fn get_or<T>(&self, path : &str, default : T) -> Result<T>
+        where T: FromOptionValue {
+        match self.get(path) {
+            Err(Errors::ElementNotExists) => { Ok(default) },
+            result => { result }
+        }
+    }
+
+    /// Return true if setting at path exists.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { port = 80; };").is_ok() {
+    ///     assert!(cfg.exists("server.port"));
+    ///     assert!(!cfg.exists("server.host"));
+    /// }
+    /// ```
+    pub fn exists(&self, path : &str) -> bool {
+        self.value(path).is_some()
+    }
+
+    /// Read i32 setting at path, None if it not exists or has another type.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { port = 80; };").is_ok() {
+    ///     assert_eq!(cfg.int32("server.port"), Some(80));
+    /// }
+    /// ```
+    pub fn int32(&self, path : &str) -> Option<i32> {
+        self.get(path).ok()
+    }
+
+    /// Read i64 setting at path, None if it not exists or has another type.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("size = 1099511627776L;").is_ok() {
+    ///     assert_eq!(cfg.int64("size"), Some(1 << 40));
+    /// }
+    /// ```
+    pub fn int64(&self, path : &str) -> Option<i64> {
+        self.get(path).ok()
+    }
+
+    /// Read f64 setting at path, None if it not exists or has another type.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("ratio = 0.5;").is_ok() {
+    ///     assert_eq!(cfg.float64("ratio"), Some(0.5));
+    /// }
+    /// ```
+    pub fn float64(&self, path : &str) -> Option<f64> {
+        self.get(path).ok()
+    }
+
+    /// Read bool setting at path, None if it not exists or has another type.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("debug = true;").is_ok() {
+    ///     assert_eq!(cfg.bool("debug"), Some(true));
+    /// }
+    /// ```
+    pub fn bool(&self, path : &str) -> Option<bool> {
+        self.get(path).ok()
+    }
+
+    /// Read string setting at path, None if it not exists or has another
+    /// type.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("host = \"localhost\";").is_ok() {
+    ///     assert_eq!(cfg.string("host").as_deref(), Some("localhost"));
+    /// }
+    /// ```
+    pub fn string(&self, path : &str) -> Option<String> {
+        self.get(path).ok()
+    }
+
+    /// Read i32 setting at path, default if it not exists or has another
+    /// type.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let cfg = Config::new();
+    /// assert_eq!(cfg.int32_or("server.port", 80), 80);
+    /// ```
+    pub fn int32_or(&self, path : &str, default : i32) -> i32 {
+        self.int32(path).unwrap_or(default)
+    }
+
+    /// Read i64 setting at path, default if it not exists or has another
+    /// type.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let cfg = Config::new();
+    /// assert_eq!(cfg.int64_or("size", 1 << 40), 1 << 40);
+    /// ```
+    pub fn int64_or(&self, path : &str, default : i64) -> i64 {
+        self.int64(path).unwrap_or(default)
+    }
+
+    /// Read f64 setting at path, default if it not exists or has another
+    /// type.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let cfg = Config::new();
+    /// assert_eq!(cfg.float64_or("ratio", 0.5), 0.5);
+    /// ```
+    pub fn float64_or(&self, path : &str, default : f64) -> f64 {
+        self.float64(path).unwrap_or(default)
+    }
+
+    /// Read bool setting at path, default if it not exists or has another
+    /// type.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let cfg = Config::new();
+    /// assert!(!cfg.bool_or("debug", false));
+    /// ```
+    pub fn bool_or(&self, path : &str, default : bool) -> bool {
+        self.bool(path).unwrap_or(default)
+    }
+
+    /// Read string setting at path, default if it not exists or has another
+    /// type.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let cfg = Config::new();
+    /// assert_eq!(cfg.string_or("host", "localhost"), "localhost");
+    /// ```
+    pub fn string_or<S>(&self, path : &str, default : S) -> String
+        where S: Into<String> {
+        self.string(path).unwrap_or_else(|| default.into())
+    }
+}
+
+impl OptionReader<'_> {
+
+    /// Read array or list into vector, converting every element like
+    /// `Config::get`. Returns `TypeMismatch` if setting is not a collection
+    /// or any element has another type.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::{Config, Errors};
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("ports = [ 80, 443 ]; mixed = ( 1, \"a\" );")
+    ///     .is_ok() {
+    ///     let ports = cfg.value("ports").unwrap();
+    ///     assert_eq!(ports.as_vec::<u16>(), Ok(vec![80, 443]));
+    ///     let mixed = cfg.value("mixed").unwrap();
+    ///     assert_eq!(mixed.as_vec::<i32>(), Err(Errors::TypeMismatch));
+    /// }
+    /// ```
+    pub fn as_vec<T>(&self) -> Result<Vec<T>> where T: FromOptionValue {
+        if !self.exists() {
+            return Err(Errors::ElementNotExists);
+        }
+        Vec::<T>::from_option(self)
+    }
+
+    /// Read array or list of i32 values into vector.
+    pub fn as_vec_int32(&self) -> Result<Vec<i32>> {
+        self.as_vec()
+    }
+
+    /// Read array or list of i64 values into vector.
+    pub fn as_vec_int64(&self) -> Result<Vec<i64>> {
+        self.as_vec()
+    }
+
+    /// Read array or list of f64 values into vector.
+    pub fn as_vec_float64(&self) -> Result<Vec<f64>> {
+        self.as_vec()
+    }
+
+    /// Read array or list of bool values into vector.
+    pub fn as_vec_bool(&self) -> Result<Vec<bool>> {
+        self.as_vec()
+    }
+
+    /// Read array or list of strings into vector.
+    pub fn as_vec_string(&self) -> Result<Vec<String>> {
+        self.as_vec()
+    }
+}
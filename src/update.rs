@@ -0,0 +1,166 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! In place updates of group members.
+//!
+//! `write_*` methods add new settings and fail if the name is taken. `set_*`
+//! methods update existing member instead, keeping its position. Member of
+//! another type is replaced by new setting at the same position, so `set_*`
+//! borrow the writer mutably and no writer of replaced member stays alive.
+
+use crate::config::OptionWriter;
+use crate::freeze;
+use crate::key;
+use crate::value::{self, Value};
+use libconfig_sys as raw;
+
+use std::ptr;
+
+impl<'a> OptionWriter<'a> {
+
+    /// Set i32 value of group member, add it if it not exists.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("port = 80;").is_ok() {
+    ///     assert!(cfg.root().write_int32("port", 8080).is_err());
+    ///     assert!(cfg.root().set_int32("port", 8080).is_some());
+    ///     assert_eq!(cfg.value("port").unwrap().as_int32(), Some(8080));
+    /// }
+    /// ```
+    pub fn set_int32<S>(&mut self, name : S, value : i32)
+        -> Option<&mut OptionWriter<'a>> where S: AsRef<str> {
+        self.upsert(name.as_ref(), &Value::from(value),
+            |writer, name| writer.write_int32(name, value).is_ok())
+    }
+
+    /// Set i64 value of group member, add it if it not exists.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// assert!(cfg.root().set_int64("size", 1 << 40).is_some());
+    /// assert_eq!(cfg.value("size").unwrap().as_int64(), Some(1 << 40));
+    /// ```
+    pub fn set_int64<S>(&mut self, name : S, value : i64)
+        -> Option<&mut OptionWriter<'a>> where S: AsRef<str> {
+        self.upsert(name.as_ref(), &Value::from(value),
+            |writer, name| writer.write_int64(name, value).is_ok())
+    }
+
+    /// Set f64 value of group member, add it if it not exists.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// assert!(cfg.root().set_float64("ratio", 0.5).is_some());
+    /// assert_eq!(cfg.value("ratio").unwrap().as_float64(), Some(0.5));
+    /// ```
+    pub fn set_float64<S>(&mut self, name : S, value : f64)
+        -> Option<&mut OptionWriter<'a>> where S: AsRef<str> {
+        self.upsert(name.as_ref(), &Value::from(value),
+            |writer, name| writer.write_float64(name, value).is_ok())
+    }
+
+    /// Set bool value of group member, add it if it not exists.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// assert!(cfg.root().set_bool("enabled", true).is_some());
+    /// assert_eq!(cfg.value("enabled").unwrap().as_bool(), Some(true));
+    /// ```
+    pub fn set_bool<S>(&mut self, name : S, value : bool)
+        -> Option<&mut OptionWriter<'a>> where S: AsRef<str> {
+        self.upsert(name.as_ref(), &Value::from(value),
+            |writer, name| writer.write_bool(name, value).is_ok())
+    }
+
+    /// Set string value of group member, add it if it not exists.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    ///
+    /// let mut cfg = Config::new();
+    /// assert!(cfg.root().set_string("host", "localhost").is_some());
+    /// assert_eq!(cfg.value("host").unwrap().as_str(), Some("localhost"));
+    /// ```
+    pub fn set_string<S, V>(&mut self, name : S, value : V)
+        -> Option<&mut OptionWriter<'a>> where S: AsRef<str>, V: AsRef<str> {
+        let value = value.as_ref();
+        self.upsert(name.as_ref(), &Value::from(value),
+            |writer, name| writer.write_string(name, value).is_ok())
+    }
+
+    // Assign value to existing member or add it with writer. Member that
+    // can't hold the value is replaced at the same position.
+    fn upsert<F>(&mut self, name : &str, value : &Value, write : F)
+        -> Option<&mut OptionWriter<'a>>
+        where F: Fn(&OptionWriter<'a>, &str) -> bool {
+        let element = self.element?;
+        if raw::config_setting_is_group(element) != raw::CONFIG_TRUE ||
+            !freeze::writable(element) {
+            return None;
+        }
+
+        let member = key::with_c_str(name, |name| unsafe {
+            raw::config_setting_get_member(element, name.as_ptr())
+        }).unwrap_or(ptr::null_mut());
+        if member.is_null() {
+            return write(self, name).then_some(self);
+        }
+        if value::assign(member, value).ok()? {
+            return Some(self);
+        }
+
+        let index = unsafe { raw::config_setting_index(member) } as usize;
+        let removed = key::with_c_str(name, |name| unsafe {
+            raw::config_setting_remove(element, name.as_ptr())
+        })?;
+        if removed != raw::CONFIG_TRUE {
+            return None;
+        }
+        if !write(self, name) {
+            return None;
+        }
+        self.move_child_to(name, index).ok()?;
+        Some(self)
+    }
+}
@@ -0,0 +1,471 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Owned configuration values.
+
+use crate::config::{Errors, OptionReader, OptionWriter, Result};
+use crate::format;
+use crate::freeze;
+use crate::key;
+use crate::typed::FromOptionValue;
+use libconfig_sys as raw;
+
+use indexmap::IndexMap;
+use std::ffi::{CStr, CString};
+use std::fmt;
+
+/// Owned value of configuration setting.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+    Array(Vec<Value>),
+    List(Vec<Value>),
+    Group(IndexMap<String, Value>)
+}
+
+// Format value in libconfig syntax. NaN and infinity can't be formatted and
+// return error.
+impl fmt::Display for Value {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        format::write_value(&mut out, self, 0)?;
+        f.write_str(&out)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value : i32) -> Value {
+        Value::Int(value as i64)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value : i64) -> Value {
+        Value::Int(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value : f64) -> Value {
+        Value::Float(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value : bool) -> Value {
+        Value::Bool(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value : &str) -> Value {
+        Value::Str(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value : String) -> Value {
+        Value::Str(value)
+    }
+}
+
+impl OptionReader<'_> {
+
+    /// Read setting with all its children into owned value, None if setting
+    /// not exists.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    /// use librustconfig::value::Value;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("ports = [ 80, 443 ];").is_ok() {
+    ///     assert_eq!(cfg.value("ports").unwrap().to_value(),
+    ///         Some(Value::Array(vec![Value::Int(80), Value::Int(443)])));
+    /// }
+    /// ```
+    pub fn to_value(&self) -> Option<Value> {
+        Value::from_option(self).ok()
+    }
+}
+
+impl OptionWriter<'_> {
+
+    /// Replace value of setting in place, keeping its name and position.
+    /// Scalars must be replaced by compatible values, groups, arrays and
+    /// lists by values of the same kind, whose children replace all current
+    /// ones. Returns `TypeMismatch` otherwise. Writer is borrowed mutably, as
+    /// removed children must not be written through other writers.
+    ///
+    /// # Example
+    /// ```
+    /// use librustconfig::config::Config;
+    /// use librustconfig::value::Value;
+    ///
+    /// let mut cfg = Config::new();
+    /// if cfg.load_from_string("server : { port = 80; };").is_ok() {
+    ///     let mut server = cfg.value("server").unwrap().to_value().unwrap();
+    ///     if let Value::Group(members) = &mut server {
+    ///         members.insert("host".to_string(), Value::from("localhost"));
+    ///     }
+    ///     assert!(cfg.writer("server").unwrap().from_value(&server).is_ok());
+    ///     assert_eq!(cfg.value("server.host").unwrap().as_string(),
+    ///         Some("localhost".to_string()));
+    /// }
+    /// ```
+    pub fn from_value(&mut self, value : &Value) -> Result<()> {
+        let option = self.element.ok_or(Errors::ElementNotExists)?;
+        if !freeze::writable(option) {
+            return Err(Errors::ConfigFrozen);
+        }
+
+        let children = match (raw::config_setting_type(option) as i16, value) {
+            (raw::CONFIG_TYPE_ARRAY, Value::Array(elements)) |
+            (raw::CONFIG_TYPE_LIST, Value::List(elements)) => {
+                elements.iter().map(|element| ("", element)).collect()
+            },
+            (raw::CONFIG_TYPE_GROUP, Value::Group(members)) => {
+                members.iter().map(|(name, member)| (name.as_str(), member))
+                    .collect::<Vec<_>>()
+            },
+            _ => {
+                return if assign(option, value)? {
+                    Ok(())
+                } else {
+                    Err(Errors::TypeMismatch)
+                };
+            }
+        };
+
+        while unsafe { raw::config_setting_length(option) } > 0 {
+            if unsafe { raw::config_setting_remove_elem(option, 0) } !=
+                raw::CONFIG_TRUE {
+                return Err(Errors::WriteError);
+            }
+        }
+        for (name, child) in children {
+            write(option, name, child)?;
+        }
+        Ok(())
+    }
+}
+
+// Read setting with all its children.
+pub(crate) fn read(option : *mut raw::config_setting_t) -> Option<Value> {
+    unsafe {
+        match raw::config_setting_type(option) as i16 {
+            raw::CONFIG_TYPE_INT => {
+                Some(Value::Int(raw::config_setting_get_int(option) as i64))
+            },
+            raw::CONFIG_TYPE_INT64 => {
+                Some(Value::Int(raw::config_setting_get_int64(option)))
+            },
+            raw::CONFIG_TYPE_FLOAT => {
+                Some(Value::Float(raw::config_setting_get_float(option)))
+            },
+            raw::CONFIG_TYPE_BOOL => {
+                Some(Value::Bool(raw::config_setting_get_bool(option) ==
+                    raw::CONFIG_TRUE))
+            },
+            raw::CONFIG_TYPE_STRING => {
+                let str = raw::config_setting_get_string(option);
+                if str.is_null() {
+                    return None;
+                }
+                Some(Value::Str(CStr::from_ptr(str).to_str().ok()?
+                    .to_string()))
+            },
+            raw::CONFIG_TYPE_ARRAY => {
+                Some(Value::Array(read_elements(option)?))
+            },
+            raw::CONFIG_TYPE_LIST => {
+                Some(Value::List(read_elements(option)?))
+            },
+            raw::CONFIG_TYPE_GROUP => {
+                let mut group = IndexMap::new();
+                for index in 0..raw::config_setting_length(option) {
+                    let child = raw::config_setting_get_elem(option,
+                        index as u32);
+                    if child.is_null() {
+                        return None;
+                    }
+                    let name = raw::config_setting_name(child);
+                    if name.is_null() {
+                        return None;
+                    }
+                    group.insert(CStr::from_ptr(name).to_str().ok()?
+                        .to_string(), read(child)?);
+                }
+                Some(Value::Group(group))
+            },
+            _ => { None }
+        }
+    }
+}
+
+// Read all collection elements.
+fn read_elements(option : *mut raw::config_setting_t) -> Option<Vec<Value>> {
+    let length = unsafe { raw::config_setting_length(option) };
+    let mut elements = Vec::with_capacity(length as usize);
+
+    for index in 0..length {
+        let child = unsafe {
+            raw::config_setting_get_elem(option, index as u32)
+        };
+        if child.is_null() {
+            return None;
+        }
+        elements.push(read(child)?);
+    }
+    Some(elements)
+}
+
+// Check if any string or member name in value contains NUL character.
+pub(crate) fn contains_nul(value : &Value) -> bool {
+    match value {
+        Value::Str(val) => { val.contains('\0') },
+        Value::Array(elements) | Value::List(elements) => {
+            elements.iter().any(contains_nul)
+        },
+        Value::Group(children) => {
+            children.iter().any(|(name, child)| {
+                name.contains('\0') || contains_nul(child)
+            })
+        },
+        _ => { false }
+    }
+}
+
+// Create new setting with value in parent group or collection. Name is
+// ignored for collection elements.
+pub(crate) fn write(parent : *mut raw::config_setting_t, name : &str,
+    value : &Value) -> Result<*mut raw::config_setting_t> {
+
+    let value_type = match value {
+        Value::Int(val) => {
+            if *val >= i32::MIN as i64 && *val <= i32::MAX as i64 {
+                raw::CONFIG_TYPE_INT
+            } else {
+                raw::CONFIG_TYPE_INT64
+            }
+        },
+        Value::Float(_) => { raw::CONFIG_TYPE_FLOAT },
+        Value::Bool(_) => { raw::CONFIG_TYPE_BOOL },
+        Value::Str(_) => { raw::CONFIG_TYPE_STRING },
+        Value::Array(_) => { raw::CONFIG_TYPE_ARRAY },
+        Value::List(_) => { raw::CONFIG_TYPE_LIST },
+        Value::Group(_) => { raw::CONFIG_TYPE_GROUP }
+    };
+
+    let option = key::with_c_str(name, |name| unsafe {
+        raw::config_setting_add(parent, name.as_ptr(), value_type as i32)
+    }).ok_or(Errors::InvalidString)?;
+    if option.is_null() {
+        return Err(Errors::WriteError);
+    }
+
+    let result = unsafe {
+        match value {
+            Value::Int(val) => {
+                if value_type == raw::CONFIG_TYPE_INT {
+                    raw::config_setting_set_int(option, *val as i32)
+                } else {
+                    raw::config_setting_set_int64(option, *val)
+                }
+            },
+            Value::Float(val) => { raw::config_setting_set_float(option, *val) },
+            Value::Bool(val) => {
+                raw::config_setting_set_bool(option, if *val {
+                    raw::CONFIG_TRUE
+                } else {
+                    raw::CONFIG_FALSE
+                })
+            },
+            Value::Str(val) => {
+                let val = CString::new(val.as_str())
+                    .map_err(|_| Errors::InvalidString)?;
+                raw::config_setting_set_string(option, val.as_ptr())
+            },
+            Value::Array(elements) | Value::List(elements) => {
+                for element in elements.iter() {
+                    write(option, "", element)?;
+                }
+                raw::CONFIG_TRUE
+            },
+            Value::Group(children) => {
+                for (child_name, child) in children.iter() {
+                    write(option, child_name, child)?;
+                }
+                raw::CONFIG_TRUE
+            }
+        }
+    };
+
+    if result == raw::CONFIG_TRUE {
+        Ok(option)
+    } else {
+        Err(Errors::WriteError)
+    }
+}
+
+// Create deep copy of setting in parent group or collection, keeping exact
+// setting types and formats. Name is ignored for collection elements.
+pub(crate) fn copy(option : *mut raw::config_setting_t,
+    parent : *mut raw::config_setting_t, name : &str)
+    -> Result<*mut raw::config_setting_t> {
+
+    let value_type = raw::config_setting_type(option);
+    let copy = key::with_c_str(name, |name| unsafe {
+        raw::config_setting_add(parent, name.as_ptr(), value_type as i32)
+    }).ok_or(Errors::InvalidString)?;
+    if copy.is_null() {
+        return Err(Errors::WriteError);
+    }
+    fill(option, copy)?;
+    Ok(copy)
+}
+
+// Copy value, format and children of setting to empty setting of the same
+// type.
+pub(crate) fn fill(option : *mut raw::config_setting_t,
+    copy : *mut raw::config_setting_t) -> Result<()> {
+
+    let result = unsafe {
+        raw::config_setting_set_format(copy,
+            raw::config_setting_get_format(option));
+
+        match raw::config_setting_type(option) as i16 {
+            raw::CONFIG_TYPE_INT => {
+                raw::config_setting_set_int(copy,
+                    raw::config_setting_get_int(option))
+            },
+            raw::CONFIG_TYPE_INT64 => {
+                raw::config_setting_set_int64(copy,
+                    raw::config_setting_get_int64(option))
+            },
+            raw::CONFIG_TYPE_FLOAT => {
+                raw::config_setting_set_float(copy,
+                    raw::config_setting_get_float(option))
+            },
+            raw::CONFIG_TYPE_BOOL => {
+                raw::config_setting_set_bool(copy,
+                    raw::config_setting_get_bool(option))
+            },
+            raw::CONFIG_TYPE_STRING => {
+                raw::config_setting_set_string(copy,
+                    raw::config_setting_get_string(option))
+            },
+            raw::CONFIG_TYPE_ARRAY | raw::CONFIG_TYPE_LIST |
+            raw::CONFIG_TYPE_GROUP => {
+                copy_children(option, copy)?;
+                raw::CONFIG_TRUE
+            },
+            _ => { raw::CONFIG_FALSE }
+        }
+    };
+
+    if result == raw::CONFIG_TRUE {
+        Ok(())
+    } else {
+        Err(Errors::WriteError)
+    }
+}
+
+// Copy all children of group or collection to another one.
+pub(crate) fn copy_children(option : *mut raw::config_setting_t,
+    parent : *mut raw::config_setting_t) -> Result<()> {
+    for index in 0..unsafe { raw::config_setting_length(option) } {
+        let child = unsafe {
+            raw::config_setting_get_elem(option, index as u32)
+        };
+        if child.is_null() {
+            return Err(Errors::WriteError);
+        }
+
+        let name = raw::config_setting_name(child);
+        let name = if name.is_null() {
+            ""
+        } else {
+            unsafe { CStr::from_ptr(name) }.to_str()
+                .map_err(|_| Errors::WriteError)?
+        };
+        copy(child, parent, name)?;
+    }
+    Ok(())
+}
+
+// Set value of existing scalar setting in place. Returns false if setting
+// type can't hold the value.
+pub(crate) fn assign(option : *mut raw::config_setting_t, value : &Value)
+    -> Result<bool> {
+
+    let result = unsafe {
+        match (raw::config_setting_type(option) as i16, value) {
+            (raw::CONFIG_TYPE_INT, Value::Int(val)) => {
+                if *val < i32::MIN as i64 || *val > i32::MAX as i64 {
+                    return Ok(false);
+                }
+                raw::config_setting_set_int(option, *val as i32)
+            },
+            (raw::CONFIG_TYPE_INT64, Value::Int(val)) => {
+                raw::config_setting_set_int64(option, *val)
+            },
+            (raw::CONFIG_TYPE_FLOAT, Value::Int(val)) => {
+                raw::config_setting_set_float(option, *val as f64)
+            },
+            (raw::CONFIG_TYPE_FLOAT, Value::Float(val)) => {
+                raw::config_setting_set_float(option, *val)
+            },
+            (raw::CONFIG_TYPE_BOOL, Value::Bool(val)) => {
+                raw::config_setting_set_bool(option, if *val {
+                    raw::CONFIG_TRUE
+                } else {
+                    raw::CONFIG_FALSE
+                })
+            },
+            (raw::CONFIG_TYPE_STRING, Value::Str(val)) => {
+                let val = CString::new(val.as_str())
+                    .map_err(|_| Errors::InvalidString)?;
+                raw::config_setting_set_string(option, val.as_ptr())
+            },
+            _ => { return Ok(false) }
+        }
+    };
+
+    if result == raw::CONFIG_TRUE {
+        Ok(true)
+    } else {
+        Err(Errors::WriteError)
+    }
+}
@@ -0,0 +1,205 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Config file reloading.
+//!
+//! Watcher keeps config loaded from file and reloads it when the file
+//! changes. Callbacks registered for single settings are called only when
+//! the value of their setting changes between reloads.
+
+use crate::config::{Config, Errors, Result};
+use crate::value::{self, Value};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Callback called with old and new value of watched setting.
+type KeyCallback = Box<dyn FnMut(Option<&Value>, Option<&Value>)>;
+
+/// Watched setting.
+struct WatchedKey {
+    path : String,
+    value : Option<Value>,
+    callback : KeyCallback
+}
+
+/// Config file watcher.
+pub struct Watcher {
+    file_name : PathBuf,
+    // Modification time and size of the loaded file.
+    version : Option<(SystemTime, u64)>,
+    config : Config,
+    keys : Vec<WatchedKey>
+}
+
+// Return modification time and size of file.
+fn file_version(file_name : &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(file_name).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+// Read effective value of setting at path.
+fn effective_value(config : &Config, path : &str) -> Option<Value> {
+    value::read(config.value(path)?.element?)
+}
+
+impl Watcher {
+
+    /// Constructor.
+    /// Load config file to watch.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use librustconfig::watch::Watcher;
+    /// use std::path::Path;
+    ///
+    /// if let Ok(watcher) = Watcher::new(Path::new("app.cfg")) {
+    ///     /* ... */
+    /// }
+    /// ```
+    pub fn new(file_name : &Path) -> Result<Watcher> {
+        let version = file_version(file_name);
+        let mut config = Config::new();
+        config.load_from_file(file_name)?;
+
+        Ok(Watcher {
+            file_name : file_name.to_path_buf(),
+            version,
+            config,
+            keys : Vec::new()
+        })
+    }
+
+    /// Return currently loaded config.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use librustconfig::watch::Watcher;
+    /// use std::path::Path;
+    ///
+    /// let watcher = Watcher::new(Path::new("app.cfg")).unwrap();
+    /// let port = watcher.config().value("server.port");
+    /// ```
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Call callback with old and new value every time the value of setting
+    /// at path changes between reloads. Missing setting has None value.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use librustconfig::watch::Watcher;
+    /// use std::path::Path;
+    ///
+    /// let mut watcher = Watcher::new(Path::new("app.cfg")).unwrap();
+    /// watcher.watch_key("log.level", |old, new| {
+    ///     println!("log level changed from {:?} to {:?}", old, new);
+    /// });
+    /// ```
+    pub fn watch_key<S, F>(&mut self, path : S, callback : F)
+        where S: Into<String>, F: FnMut(Option<&Value>, Option<&Value>)
+        + 'static {
+        let path = path.into();
+        self.keys.push(WatchedKey {
+            value : effective_value(&self.config, &path),
+            path,
+            callback : Box::new(callback)
+        });
+    }
+
+    /// Reload config if the file changed since last load and call callbacks
+    /// of changed settings. Only changed settings are updated, see
+    /// `Config::reload_from_file`. Return true if config was reloaded. If the
+    /// file can't be parsed previously loaded config stays active.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use librustconfig::watch::Watcher;
+    /// use std::path::Path;
+    ///
+    /// let mut watcher = Watcher::new(Path::new("app.cfg")).unwrap();
+    /// if let Ok(true) = watcher.poll() {
+    ///     /* ... */
+    /// }
+    /// ```
+    pub fn poll(&mut self) -> Result<bool> {
+        let version = file_version(&self.file_name);
+        if version.is_none() || version == self.version {
+            return Ok(false);
+        }
+        self.version = version;
+
+        self.config.reload_from_file(&self.file_name)?;
+
+        for key in self.keys.iter_mut() {
+            let value = effective_value(&self.config, &key.path);
+            if value != key.value {
+                (key.callback)(key.value.as_ref(), value.as_ref());
+                key.value = value;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Poll file with interval and call callback with the reloaded config
+    /// every time it changes. Files that can't be parsed are skipped.
+    /// Watching stops when callback returns false.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use librustconfig::watch::Watcher;
+    /// use std::path::Path;
+    /// use std::time::Duration;
+    ///
+    /// let mut watcher = Watcher::new(Path::new("app.cfg")).unwrap();
+    /// let _ = watcher.run(Duration::from_secs(1), |cfg| {
+    ///     /* ... */
+    ///     true
+    /// });
+    /// ```
+    pub fn run<F>(&mut self, interval : Duration, mut callback : F)
+        -> Result<()> where F: FnMut(&Config) -> bool {
+        loop {
+            match self.poll() {
+                Ok(true) => {
+                    if !callback(&self.config) {
+                        return Ok(());
+                    }
+                },
+                Ok(false) | Err(Errors::ParseError) => {},
+                Err(error) => { return Err(error) }
+            }
+            thread::sleep(interval);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+/******************************************************************************/
+/*                               libRustConfig                                */
+/*                   rust wrapper around libconfig library                    */
+/*                  https://github.com/hyperrealm/libconfig                   */
+/*                                                                            */
+/* Copyright (c) 2020                                       Ivan Semenkov     */
+/* https://github.com/isemenkov/librustconfig               ivan@semenkov.pro */
+/*                                                          Ukraine           */
+/******************************************************************************/
+/*                                                                            */
+/* Permission is hereby granted,  free of charge,  to any person obtaining a  */
+/* copy of this software and associated documentation files (the "Software"), */
+/* to deal in the Software without restriction, including without limitation  */
+/* the rights to use, copy,  modify, merge, publish, distribute,  sublicense, */
+/* and/or  sell copies  of the Software,  and to permit persons  to whom  the */
+/* Software  is furnished to  do  so,  subject to  the following  conditions: */
+/*                                                                            */
+/* The above copyright notice and this permission notice shall be included in */
+/* all copies or substantial portions of the Software.                        */
+/*                                                                            */
+/* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR */
+/* IMPLIED,  INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF  MERCHANTABILITY, */
+/* FITNESS  FOR A PARTICULAR PURPOSE  AND NONINFRINGEMENT. IN  NO EVENT SHALL */
+/* THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER */
+/* LIABILITY,  WHETHER IN AN ACTION  OF CONTRACT,  TORT OR OTHERWISE, ARISING */
+/* FROM,  OUT OF  OR IN  CONNECTION WITH  THE SOFTWARE  OR THE  USE OR  OTHER */
+/* DEALINGS IN THE SOFTWARE.                                                  */
+/*                                                                            */
+/******************************************************************************/
+
+//! Command line tool runs. Run with `cargo test --features cli --test cli`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+// Run rustconfig with arguments.
+fn run(args : &[&Path]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rustconfig")).args(args).output()
+        .expect("rustconfig runs")
+}
+
+// Create empty directory for files of one test.
+fn directory(name : &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("rustconfig-{}-{}",
+        name, std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+#[test]
+fn fmt_check() {
+    let directory = directory("fmt-check");
+    let formatted = directory.join("formatted.cfg");
+    let unformatted = directory.join("unformatted.cfg");
+    fs::write(&formatted, "port = 80;\n").unwrap();
+    fs::write(&unformatted, "port=80; host = \"a\";").unwrap();
+
+    let output = run(&[Path::new("fmt"), Path::new("--check"), &formatted]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+
+    let output = run(&[Path::new("fmt"), Path::new("--check"), &formatted,
+        &unformatted]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(),
+        format!("{}\n", unformatted.display()));
+    assert_eq!(fs::read_to_string(&unformatted).unwrap(),
+        "port=80; host = \"a\";");
+
+    let output = run(&[Path::new("fmt"), &unformatted]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(fs::read_to_string(&unformatted).unwrap(),
+        "port = 80;\nhost = \"a\";\n");
+    let output = run(&[Path::new("fmt"), Path::new("--check"), &unformatted]);
+    assert_eq!(output.status.code(), Some(0));
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn fmt_skips_comments() {
+    let directory = directory("fmt-comments");
+    let commented = directory.join("commented.cfg");
+    let unformatted = directory.join("unformatted.cfg");
+    fs::write(&commented, "# port\nport=80;\n").unwrap();
+    fs::write(&unformatted, "port=80;").unwrap();
+
+    let output = run(&[Path::new("fmt"), Path::new("--check"), &commented,
+        &unformatted]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(),
+        format!("{}\n", unformatted.display()));
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert!(errors.contains(&format!("{}: comments", commented.display())));
+    assert!(errors.contains("1 file(s) not formatted, 1 file(s) can't be \
+        formatted"));
+
+    let output = run(&[Path::new("fmt"), &commented, &unformatted]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(fs::read_to_string(&commented).unwrap(),
+        "# port\nport=80;\n");
+    assert_eq!(fs::read_to_string(&unformatted).unwrap(), "port = 80;\n");
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn convert_output_is_formatted() {
+    let directory = directory("convert-formatted");
+    let source = directory.join("app.json");
+    let converted = directory.join("app.cfg");
+    fs::write(&source, "{ \"port\": 80, \"server\": { \"hosts\": [\"a\", \
+        \"b\"] } }").unwrap();
+
+    let output = run(&[Path::new("convert"), &source, Path::new("--to"),
+        Path::new("cfg"), Path::new("--output"), &converted]);
+    assert_eq!(output.status.code(), Some(0));
+    let output = run(&[Path::new("fmt"), Path::new("--check"), &converted]);
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = run(&[Path::new("get"), &converted,
+        Path::new("server.hosts.[1]")]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "b\n");
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn convert_json_round_trip() {
+    let directory = directory("convert-json");
+    let source = directory.join("app.cfg");
+    let json = directory.join("app.json");
+    let converted = directory.join("converted.cfg");
+    let text = "port = 80;\nbig = 10000000000L;\nratio = 0.5;\n\
+        debug = true;\nname = \"a \\\"b\\\"\";\nports = [ 80, 81 ];\n\
+        server : \n{\n  hosts = ( \"a\", 1 );\n};\n";
+    fs::write(&source, text).unwrap();
+
+    let output = run(&[Path::new("convert"), &source, Path::new("--to"),
+        Path::new("json"), Path::new("--output"), &json]);
+    assert_eq!(output.status.code(), Some(0));
+    let output = run(&[Path::new("convert"), &json, Path::new("--to"),
+        Path::new("cfg"), Path::new("--output"), &converted]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(fs::read_to_string(&converted).unwrap(), text);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn convert_unknown_format() {
+    let directory = directory("convert-unknown");
+    let source = directory.join("app.cfg");
+    fs::write(&source, "port = 80;\n").unwrap();
+
+    let to_ini = [Path::new("convert"), &source, Path::new("--to"),
+        Path::new("ini")];
+    let from_ini = [Path::new("convert"), &source, Path::new("--to"),
+        Path::new("json"), Path::new("--from"), Path::new("ini")];
+    for args in [&to_ini[..], &from_ini[..]].iter() {
+        let output = run(args);
+        assert_eq!(output.status.code(), Some(2));
+        assert!(output.stdout.is_empty());
+        let errors = String::from_utf8(output.stderr).unwrap();
+        assert!(errors.starts_with("rustconfig: unknown format 'ini'\n"));
+        assert!(errors.contains("usage: rustconfig"));
+    }
+    fs::remove_dir_all(&directory).unwrap();
+}